@@ -109,16 +109,6 @@ rec {
       # File a bug if you depend on any for non-debug work!
       debug = internal.debugCrate { inherit packageId; };
     };
-    "linkerclient" = rec {
-      packageId = "linkerclient";
-      build = internal.buildRustCrateWithFeatures {
-        packageId = "linkerclient";
-      };
-
-      # Debug support which might change between releases.
-      # File a bug if you depend on any for non-debug work!
-      debug = internal.debugCrate { inherit packageId; };
-    };
     "memfd" = rec {
       packageId = "memfd";
       build = internal.buildRustCrateWithFeatures {
@@ -215,6 +205,26 @@ rec {
         packageId = "yscloud-linker";
       };
 
+      # Debug support which might change between releases.
+      # File a bug if you depend on any for non-debug work!
+      debug = internal.debugCrate { inherit packageId; };
+    };
+    "yscloud-runtime" = rec {
+      packageId = "yscloud-runtime";
+      build = internal.buildRustCrateWithFeatures {
+        packageId = "yscloud-runtime";
+      };
+
+      # Debug support which might change between releases.
+      # File a bug if you depend on any for non-debug work!
+      debug = internal.debugCrate { inherit packageId; };
+    };
+    "yscloud-testkit" = rec {
+      packageId = "yscloud-testkit";
+      build = internal.buildRustCrateWithFeatures {
+        packageId = "yscloud-testkit";
+      };
+
       # Debug support which might change between releases.
       # File a bug if you depend on any for non-debug work!
       debug = internal.debugCrate { inherit packageId; };
@@ -284,6 +294,57 @@ rec {
           "rustc-dep-of-std" = [ "core" "compiler_builtins" ];
         };
       };
+      "ahash" = rec {
+        crateName = "ahash";
+        version = "0.8.12";
+        edition = "2018";
+        sha256 = "0xbsp9rlm5ki017c0w6ay8kjwinwm8knjncci95mii30rmwz25as";
+        authors = [
+          "Tom Kaitchuck <Tom.Kaitchuck@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "cfg-if";
+            packageId = "cfg-if 1.0.0";
+          }
+          {
+            name = "getrandom";
+            packageId = "getrandom 0.3.4";
+            optional = true;
+          }
+          {
+            name = "once_cell";
+            packageId = "once_cell";
+            usesDefaultFeatures = false;
+            target = { target, features }: (!((target."arch" == "arm") && (target."os" == "none")));
+            features = [ "alloc" ];
+          }
+          {
+            name = "serde";
+            packageId = "serde";
+            optional = true;
+          }
+          {
+            name = "zerocopy";
+            packageId = "zerocopy";
+            usesDefaultFeatures = false;
+            features = [ "simd" ];
+          }
+        ];
+        buildDependencies = [
+          {
+            name = "version_check";
+            packageId = "version_check";
+          }
+        ];
+        features = {
+          "atomic-polyfill" = [ "dep:portable-atomic" "once_cell/critical-section" ];
+          "compile-time-rng" = [ "const-random" ];
+          "default" = [ "std" "runtime-rng" ];
+          "runtime-rng" = [ "getrandom" ];
+        };
+        resolvedDefaultFeatures = [ "default" "getrandom" "runtime-rng" "serde" "std" ];
+      };
       "aho-corasick 0.6.10" = rec {
         crateName = "aho-corasick";
         version = "0.6.10";
@@ -302,11 +363,11 @@ rec {
         ];
 
       };
-      "aho-corasick 0.7.15" = rec {
+      "aho-corasick 1.1.5" = rec {
         crateName = "aho-corasick";
-        version = "0.7.15";
-        edition = "2015";
-        sha256 = "1rb8gzhljl8r87dpf2n5pnqnkl694casgns4ma0sqzd4zazzw13l";
+        version = "1.1.5";
+        edition = "2021";
+        sha256 = "1fhjkp2nbs7gg4y1b68hpc8028rpax8aiscfh9b60q78m4pn90n9";
         libName = "aho_corasick";
         authors = [
           "Andrew Gallant <jamslam@gmail.com>"
@@ -315,14 +376,17 @@ rec {
           {
             name = "memchr";
             packageId = "memchr";
+            optional = true;
             usesDefaultFeatures = false;
           }
         ];
         features = {
-          "default" = [ "std" ];
-          "std" = [ "memchr/use_std" ];
+          "default" = [ "std" "perf-literal" ];
+          "logging" = [ "dep:log" ];
+          "perf-literal" = [ "dep:memchr" ];
+          "std" = [ "memchr?/std" ];
         };
-        resolvedDefaultFeatures = [ "default" "std" ];
+        resolvedDefaultFeatures = [ "perf-literal" "std" ];
       };
       "ansi_term" = rec {
         crateName = "ansi_term";
@@ -549,7 +613,7 @@ rec {
           }
           {
             name = "syn";
-            packageId = "syn";
+            packageId = "syn 1.0.67";
           }
         ];
         features = {
@@ -614,7 +678,7 @@ rec {
           }
           {
             name = "syn";
-            packageId = "syn";
+            packageId = "syn 1.0.67";
           }
           {
             name = "toml";
@@ -630,6 +694,52 @@ rec {
         };
         resolvedDefaultFeatures = [ "config" "humansize" "num-traits" "percent-encoding" "serde" "toml" ];
       };
+      "async-stream" = rec {
+        crateName = "async-stream";
+        version = "0.2.1";
+        edition = "2018";
+        sha256 = "1x8az0155hq0s9fgjf8mws2as6pgfs4sykgxzkp44jai3468q1i2";
+        authors = [
+          "Carl Lerche <me@carllerche.com>"
+        ];
+        dependencies = [
+          {
+            name = "async-stream-impl";
+            packageId = "async-stream-impl";
+          }
+          {
+            name = "futures-core";
+            packageId = "futures-core";
+          }
+        ];
+
+      };
+      "async-stream-impl" = rec {
+        crateName = "async-stream-impl";
+        version = "0.2.1";
+        edition = "2018";
+        sha256 = "0w26845j6l731b1y4i07wll3jj9vadkr2r6cwpvhp1xg70xxpy95";
+        procMacro = true;
+        authors = [
+          "Carl Lerche <me@carllerche.com>"
+        ];
+        dependencies = [
+          {
+            name = "proc-macro2";
+            packageId = "proc-macro2";
+          }
+          {
+            name = "quote";
+            packageId = "quote";
+          }
+          {
+            name = "syn";
+            packageId = "syn 1.0.67";
+            features = [ "extra-traits" "full" "visit-mut" ];
+          }
+        ];
+
+      };
       "async-trait" = rec {
         crateName = "async-trait";
         version = "0.1.48";
@@ -650,7 +760,7 @@ rec {
           }
           {
             name = "syn";
-            packageId = "syn";
+            packageId = "syn 1.0.67";
             features = [ "full" "visit-mut" ];
           }
         ];
@@ -760,7 +870,21 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "std" ];
       };
-      "base64" = rec {
+      "base64 0.12.3" = rec {
+        crateName = "base64";
+        version = "0.12.3";
+        edition = "2018";
+        sha256 = "1zq33had71xh48n17g4kqs96szhx3yh7qibzwi4fk217n3vz0h9l";
+        authors = [
+          "Alice Maz <alice@alicemaz.com>"
+          "Marshall Pierce <marshall@mpierce.org>"
+        ];
+        features = {
+          "default" = [ "std" ];
+        };
+        resolvedDefaultFeatures = [ "default" "std" ];
+      };
+      "base64 0.13.0" = rec {
         crateName = "base64";
         version = "0.13.0";
         edition = "2018";
@@ -774,6 +898,21 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "std" ];
       };
+      "base64 0.21.7" = rec {
+        crateName = "base64";
+        version = "0.21.7";
+        edition = "2018";
+        sha256 = "0rw52yvsk75kar9wgqfwgb414kvil1gn7mqkrhn9zf1537mpsacx";
+        authors = [
+          "Alice Maz <alice@alicemaz.com>"
+          "Marshall Pierce <marshall@mpierce.org>"
+        ];
+        features = {
+          "default" = [ "std" ];
+          "std" = [ "alloc" ];
+        };
+        resolvedDefaultFeatures = [ "alloc" "default" "std" ];
+      };
       "binhelpers" = rec {
         crateName = "binhelpers";
         version = "0.1.0";
@@ -790,12 +929,48 @@ rec {
           }
           {
             name = "syn";
-            packageId = "syn";
+            packageId = "syn 1.0.67";
           }
         ];
 
       };
-      "bitflags" = rec {
+      "bit-set" = rec {
+        crateName = "bit-set";
+        version = "0.5.3";
+        edition = "2015";
+        sha256 = "1wcm9vxi00ma4rcxkl3pzzjli6ihrpn9cfdi0c5b4cvga2mxs007";
+        authors = [
+          "Alexis Beingessner <a.beingessner@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "bit-vec";
+            packageId = "bit-vec";
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "default" = [ "std" ];
+          "std" = [ "bit-vec/std" ];
+        };
+        resolvedDefaultFeatures = [ "default" "std" ];
+      };
+      "bit-vec" = rec {
+        crateName = "bit-vec";
+        version = "0.6.3";
+        edition = "2015";
+        sha256 = "1ywqjnv60cdh1slhz67psnp422md6jdliji6alq0gmly2xm9p7rl";
+        authors = [
+          "Alexis Beingessner <a.beingessner@gmail.com>"
+        ];
+        features = {
+          "default" = [ "std" ];
+          "serde_no_std" = [ "serde/alloc" ];
+          "serde_std" = [ "std" "serde/std" ];
+        };
+        resolvedDefaultFeatures = [ "std" ];
+      };
+      "bitflags 1.2.1" = rec {
         crateName = "bitflags";
         version = "1.2.1";
         edition = "2015";
@@ -807,6 +982,18 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" ];
       };
+      "bitflags 2.13.2" = rec {
+        crateName = "bitflags";
+        version = "2.13.2";
+        edition = "2021";
+        sha256 = "01hbgjwvid66850fzi76mvn5f2bqycx6sf165ng1kfjqq9bl1v9x";
+        authors = [
+          "The Rust Project Developers"
+        ];
+        features = {
+          "serde" = [ "serde_core" ];
+        };
+      };
       "bitmaps" = rec {
         crateName = "bitmaps";
         version = "2.1.0";
@@ -905,6 +1092,19 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" ];
       };
+      "bytecount" = rec {
+        crateName = "bytecount";
+        version = "0.6.9";
+        edition = "2018";
+        sha256 = "0pinq0n8zza8qr2lyc3yf17k963129kdbf0bwnmvdk1bpvh14n0p";
+        authors = [
+          "Andre Bogus <bogusandre@gmail.de>"
+          "Joshua Landau <joshua@landau.ws>"
+        ];
+        features = {
+        };
+        resolvedDefaultFeatures = [ "runtime-dispatch-simd" ];
+      };
       "byteorder" = rec {
         crateName = "byteorder";
         version = "1.4.3";
@@ -1041,7 +1241,7 @@ rec {
         dependencies = [
           {
             name = "bitflags";
-            packageId = "bitflags";
+            packageId = "bitflags 1.2.1";
           }
           {
             name = "textwrap";
@@ -1145,6 +1345,23 @@ rec {
         ];
 
       };
+      "deranged" = rec {
+        crateName = "deranged";
+        version = "0.5.9";
+        edition = "2024";
+        sha256 = "13nj445c8frp617vx84g1m5yp1gg63sg09gscbf907xdw8nfg7cy";
+        authors = [
+          "Jacob Pratt <jacob@jhpratt.dev>"
+        ];
+        features = {
+          "macros" = [ "dep:deranged-macros" ];
+          "num" = [ "dep:num-traits" ];
+          "quickcheck" = [ "dep:quickcheck" "alloc" ];
+          "rand" = [ "rand08" "rand09" "rand010" ];
+          "serde" = [ "dep:serde_core" ];
+        };
+        resolvedDefaultFeatures = [ "default" ];
+      };
       "digest" = rec {
         crateName = "digest";
         version = "0.9.0";
@@ -1208,6 +1425,29 @@ rec {
         ];
 
       };
+      "dyn-clone" = rec {
+        crateName = "dyn-clone";
+        version = "1.0.20";
+        edition = "2018";
+        sha256 = "0m956cxcg8v2n8kmz6xs5zl13k2fak3zkapzfzzp7pxih6hix26h";
+        libName = "dyn_clone";
+        authors = [
+          "David Tolnay <dtolnay@gmail.com>"
+        ];
+
+      };
+      "either" = rec {
+        crateName = "either";
+        version = "1.19.0";
+        edition = "2021";
+        sha256 = "1gjq21g0sgk5ylpj85zafcinwhh3jj91i6drhb4278vw2v17370f";
+        authors = [
+        ];
+        features = {
+          "default" = [ "std" ];
+          "use_std" = [ "std" ];
+        };
+      };
       "encoding_rs" = rec {
         crateName = "encoding_rs";
         version = "0.8.28";
@@ -1251,7 +1491,7 @@ rec {
           }
           {
             name = "syn";
-            packageId = "syn";
+            packageId = "syn 1.0.67";
           }
         ];
 
@@ -1282,7 +1522,7 @@ rec {
           }
           {
             name = "regex";
-            packageId = "regex 1.4.5";
+            packageId = "regex 1.13.1";
             optional = true;
             usesDefaultFeatures = false;
             features = [ "std" "perf" ];
@@ -1345,7 +1585,7 @@ rec {
           }
           {
             name = "syn";
-            packageId = "syn";
+            packageId = "syn 1.0.67";
           }
           {
             name = "synstructure";
@@ -1355,6 +1595,50 @@ rec {
         features = {
         };
       };
+      "fancy-regex" = rec {
+        crateName = "fancy-regex";
+        version = "0.11.0";
+        edition = "2018";
+        sha256 = "18j0mmzfycibhxhhhfja00dxd1vf8x5c28lbry224574h037qpxr";
+        authors = [
+          "Raph Levien <raph@google.com>"
+          "Robin Stocker <robin@nibor.org>"
+        ];
+        dependencies = [
+          {
+            name = "bit-set";
+            packageId = "bit-set";
+          }
+          {
+            name = "regex";
+            packageId = "regex 1.13.1";
+            usesDefaultFeatures = false;
+            features = [ "std" ];
+          }
+        ];
+        features = {
+          "default" = [ "unicode" "perf" ];
+          "perf" = [ "regex/perf" ];
+          "perf-cache" = [ "regex/perf-cache" ];
+          "perf-dfa" = [ "regex/perf-dfa" ];
+          "perf-inline" = [ "regex/perf-inline" ];
+          "perf-literal" = [ "regex/perf-literal" ];
+          "unicode" = [ "regex/unicode" ];
+        };
+        resolvedDefaultFeatures = [ "default" "perf" "unicode" ];
+      };
+      "fixedbitset" = rec {
+        crateName = "fixedbitset";
+        version = "0.2.0";
+        edition = "2015";
+        sha256 = "0kg03p777wc0dajd9pvlcnsyrwa8dhqwf0sd9r4dw0p82rs39arp";
+        authors = [
+          "bluss"
+        ];
+        features = {
+          "default" = [ "std" ];
+        };
+      };
       "fnv" = rec {
         crateName = "fnv";
         version = "1.0.7";
@@ -1415,6 +1699,35 @@ rec {
         ];
 
       };
+      "fraction" = rec {
+        crateName = "fraction";
+        version = "0.13.1";
+        edition = "2015";
+        sha256 = "0y6nh9qyfidm6hsp85wf1kv7l7nc9anzvj214bnln6ylz0fsw9rh";
+        authors = [
+          "dnsl48 <dnsl48@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "lazy_static";
+            packageId = "lazy_static";
+            optional = true;
+          }
+          {
+            name = "num";
+            packageId = "num 0.4.3";
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "default" = [ "with-bigint" "with-decimal" "with-dynaint" ];
+          "with-bigint" = [ "num/num-bigint" "num/std" "lazy_static" ];
+          "with-juniper-support" = [ "juniper" ];
+          "with-postgres-support" = [ "postgres-types" "byteorder" "bytes" ];
+          "with-serde-support" = [ "serde" "serde_derive" "num/serde" ];
+        };
+        resolvedDefaultFeatures = [ "lazy_static" "with-bigint" ];
+      };
       "fuchsia-cprng" = rec {
         crateName = "fuchsia-cprng";
         version = "0.1.1";
@@ -1436,7 +1749,7 @@ rec {
         dependencies = [
           {
             name = "bitflags";
-            packageId = "bitflags";
+            packageId = "bitflags 1.2.1";
           }
           {
             name = "fuchsia-zircon-sys";
@@ -1647,7 +1960,7 @@ rec {
           }
           {
             name = "syn";
-            packageId = "syn";
+            packageId = "syn 1.0.67";
             features = [ "full" ];
           }
         ];
@@ -1842,6 +2155,12 @@ rec {
             name = "cfg-if";
             packageId = "cfg-if 1.0.0";
           }
+          {
+            name = "js-sys";
+            packageId = "js-sys";
+            optional = true;
+            target = { target, features }: ((target."arch" == "wasm32") && (target."os" == "unknown"));
+          }
           {
             name = "libc";
             packageId = "libc";
@@ -1853,37 +2172,122 @@ rec {
             packageId = "wasi 0.10.0+wasi-snapshot-preview1";
             target = { target, features }: (target."os" == "wasi");
           }
+          {
+            name = "wasm-bindgen";
+            packageId = "wasm-bindgen";
+            optional = true;
+            usesDefaultFeatures = false;
+            target = { target, features }: ((target."arch" == "wasm32") && (target."os" == "unknown"));
+          }
         ];
         features = {
           "js" = [ "wasm-bindgen" "js-sys" ];
           "rustc-dep-of-std" = [ "compiler_builtins" "core" "libc/rustc-dep-of-std" "wasi/rustc-dep-of-std" ];
         };
-        resolvedDefaultFeatures = [ "std" ];
+        resolvedDefaultFeatures = [ "js" "js-sys" "std" "wasm-bindgen" ];
       };
-      "gimli" = rec {
-        crateName = "gimli";
-        version = "0.23.0";
-        edition = "2018";
-        sha256 = "1km657nwcrb0pnv7v0ldhgl9y8s889y2j9jckmws8k2i8bhkyl7n";
+      "getrandom 0.3.4" = rec {
+        crateName = "getrandom";
+        version = "0.3.4";
+        edition = "2021";
+        sha256 = "1zbpvpicry9lrbjmkd4msgj3ihff1q92i334chk7pzf46xffz7c9";
         authors = [
-          "Nick Fitzgerald <fitzgen@gmail.com>"
-          "Philip Craig <philipjcraig@gmail.com>"
+          "The Rand Project Developers"
         ];
-        features = {
-          "default" = [ "read" "write" "std" "fallible-iterator" "endian-reader" ];
-          "endian-reader" = [ "stable_deref_trait" ];
-          "rustc-dep-of-std" = [ "core" "alloc" "compiler_builtins" ];
-          "std" = [ "fallible-iterator/std" "stable_deref_trait/std" ];
-          "write" = [ "indexmap" ];
-        };
-        resolvedDefaultFeatures = [ "read" ];
-      };
-      "h2" = rec {
-        crateName = "h2";
-        version = "0.2.7";
-        edition = "2018";
-        sha256 = "0dd5jyxmmy88pdmvag7n41k9z1qs6sliagcyx4jss5292byjhisy";
-        authors = [
+        dependencies = [
+          {
+            name = "cfg-if";
+            packageId = "cfg-if 1.0.0";
+          }
+          {
+            name = "libc";
+            packageId = "libc";
+            usesDefaultFeatures = false;
+            target = { target, features }: (((target."os" == "linux") || (target."os" == "android")) && (!(((target."os" == "linux") && (target."env" == "")) || (target."getrandom_backend" == "custom") || (target."getrandom_backend" == "linux_raw") || (target."getrandom_backend" == "rdrand") || (target."getrandom_backend" == "rndr"))));
+          }
+          {
+            name = "libc";
+            packageId = "libc";
+            usesDefaultFeatures = false;
+            target = { target, features }: ((target."os" == "dragonfly") || (target."os" == "freebsd") || (target."os" == "hurd") || (target."os" == "illumos") || (target."os" == "cygwin") || ((target."os" == "horizon") && (target."arch" == "arm")));
+          }
+          {
+            name = "libc";
+            packageId = "libc";
+            usesDefaultFeatures = false;
+            target = { target, features }: ((target."os" == "haiku") || (target."os" == "redox") || (target."os" == "nto") || (target."os" == "aix"));
+          }
+          {
+            name = "libc";
+            packageId = "libc";
+            usesDefaultFeatures = false;
+            target = { target, features }: ((target."os" == "ios") || (target."os" == "visionos") || (target."os" == "watchos") || (target."os" == "tvos"));
+          }
+          {
+            name = "libc";
+            packageId = "libc";
+            usesDefaultFeatures = false;
+            target = { target, features }: ((target."os" == "macos") || (target."os" == "openbsd") || (target."os" == "vita") || (target."os" == "emscripten"));
+          }
+          {
+            name = "libc";
+            packageId = "libc";
+            usesDefaultFeatures = false;
+            target = { target, features }: (target."os" == "netbsd");
+          }
+          {
+            name = "libc";
+            packageId = "libc";
+            usesDefaultFeatures = false;
+            target = { target, features }: (target."os" == "solaris");
+          }
+          {
+            name = "libc";
+            packageId = "libc";
+            usesDefaultFeatures = false;
+            target = { target, features }: (target."os" == "vxworks");
+          }
+          {
+            name = "r-efi";
+            packageId = "r-efi";
+            usesDefaultFeatures = false;
+            target = { target, features }: ((target."os" == "uefi") && (target."getrandom_backend" == "efi_rng"));
+          }
+          {
+            name = "wasip2";
+            packageId = "wasip2";
+            usesDefaultFeatures = false;
+            target = { target, features }: ((target."arch" == "wasm32") && (target."os" == "wasi") && (target."env" == "p2"));
+          }
+        ];
+        features = {
+          "wasm_js" = [ "dep:wasm-bindgen" "dep:js-sys" ];
+        };
+      };
+      "gimli" = rec {
+        crateName = "gimli";
+        version = "0.23.0";
+        edition = "2018";
+        sha256 = "1km657nwcrb0pnv7v0ldhgl9y8s889y2j9jckmws8k2i8bhkyl7n";
+        authors = [
+          "Nick Fitzgerald <fitzgen@gmail.com>"
+          "Philip Craig <philipjcraig@gmail.com>"
+        ];
+        features = {
+          "default" = [ "read" "write" "std" "fallible-iterator" "endian-reader" ];
+          "endian-reader" = [ "stable_deref_trait" ];
+          "rustc-dep-of-std" = [ "core" "alloc" "compiler_builtins" ];
+          "std" = [ "fallible-iterator/std" "stable_deref_trait/std" ];
+          "write" = [ "indexmap" ];
+        };
+        resolvedDefaultFeatures = [ "read" ];
+      };
+      "h2" = rec {
+        crateName = "h2";
+        version = "0.2.7";
+        edition = "2018";
+        sha256 = "0dd5jyxmmy88pdmvag7n41k9z1qs6sliagcyx4jss5292byjhisy";
+        authors = [
           "Carl Lerche <me@carllerche.com>"
           "Sean McArthur <sean@seanmonstar.com>"
         ];
@@ -2068,7 +2472,7 @@ rec {
           }
           {
             name = "itoa";
-            packageId = "itoa";
+            packageId = "itoa 0.4.7";
           }
         ];
 
@@ -2188,11 +2592,11 @@ rec {
           }
           {
             name = "itoa";
-            packageId = "itoa";
+            packageId = "itoa 0.4.7";
           }
           {
             name = "pin-project";
-            packageId = "pin-project";
+            packageId = "pin-project 1.0.6";
           }
           {
             name = "socket2";
@@ -2371,6 +2775,7 @@ rec {
         features = {
           "serde-1" = [ "serde" ];
         };
+        resolvedDefaultFeatures = [ "std" ];
       };
       "instant" = rec {
         crateName = "instant";
@@ -2460,7 +2865,50 @@ rec {
         ];
 
       };
-      "itoa" = rec {
+      "iso8601" = rec {
+        crateName = "iso8601";
+        version = "0.6.6";
+        edition = "2021";
+        sha256 = "06y4800ak8v83drijrcavdly5wqrjylxnjs170zca3rbrxa35zag";
+        authors = [
+          "Jan-Erik Rediger <janerik@fnordig.de>"
+          "Hendrik Sollich <hendrik@hoodie.de>"
+        ];
+        dependencies = [
+          {
+            name = "nom";
+            packageId = "nom 8.0.0";
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "chrono" = [ "dep:chrono" "dep:num-traits" ];
+          "default" = [ "std" ];
+          "std" = [ "nom/std" ];
+        };
+        resolvedDefaultFeatures = [ "default" "std" ];
+      };
+      "itertools" = rec {
+        crateName = "itertools";
+        version = "0.8.2";
+        edition = "2015";
+        sha256 = "1154j48aw913v5jnyhpxialxhdn2sfpl4d7bwididyb1r05jsspm";
+        authors = [
+          "bluss"
+        ];
+        dependencies = [
+          {
+            name = "either";
+            packageId = "either";
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "default" = [ "use_std" ];
+        };
+        resolvedDefaultFeatures = [ "default" "use_std" ];
+      };
+      "itoa 0.4.7" = rec {
         crateName = "itoa";
         version = "0.4.7";
         edition = "2015";
@@ -2473,21 +2921,149 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "std" ];
       };
+      "itoa 1.0.18" = rec {
+        crateName = "itoa";
+        version = "1.0.18";
+        edition = "2021";
+        sha256 = "10jnd1vpfkb8kj38rlkn2a6k02afvj3qmw054dfpzagrpl6achlg";
+        authors = [
+          "David Tolnay <dtolnay@gmail.com>"
+        ];
+
+      };
       "js-sys" = rec {
         crateName = "js-sys";
-        version = "0.3.50";
-        edition = "2018";
-        sha256 = "0k526bcp63ma05pwxjwzfmn9zhmiqfxlqnzg8vw6g3sbx3izk69d";
+        version = "0.3.95";
+        edition = "2021";
+        sha256 = "1jhj3kgxxgwm0cpdjiz7i2qapqr7ya9qswadmr63dhwx3lnyjr19";
+        libName = "js_sys";
         authors = [
           "The wasm-bindgen Developers"
         ];
         dependencies = [
+          {
+            name = "once_cell";
+            packageId = "once_cell";
+            usesDefaultFeatures = false;
+          }
           {
             name = "wasm-bindgen";
             packageId = "wasm-bindgen";
+            usesDefaultFeatures = false;
           }
         ];
-
+        features = {
+          "default" = [ "std" "unsafe-eval" ];
+          "futures" = [ "dep:cfg-if" "dep:futures-util" ];
+          "futures-core-03-stream" = [ "futures" "dep:futures-core" ];
+          "std" = [ "wasm-bindgen/std" ];
+        };
+        resolvedDefaultFeatures = [ "default" "std" "unsafe-eval" ];
+      };
+      "jsonschema" = rec {
+        crateName = "jsonschema";
+        version = "0.17.1";
+        edition = "2021";
+        crateBin = [];
+        sha256 = "0y397fmb7qkah166lq5q39p9hizj9sls09xnvwc936pwgr7iy1ra";
+        authors = [
+          "dmitry.dygalo <dadygalo@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "ahash";
+            packageId = "ahash";
+            features = [ "serde" ];
+          }
+          {
+            name = "anyhow";
+            packageId = "anyhow";
+          }
+          {
+            name = "base64";
+            packageId = "base64 0.21.7";
+          }
+          {
+            name = "bytecount";
+            packageId = "bytecount";
+            features = [ "runtime-dispatch-simd" ];
+          }
+          {
+            name = "fancy-regex";
+            packageId = "fancy-regex";
+          }
+          {
+            name = "fraction";
+            packageId = "fraction";
+            usesDefaultFeatures = false;
+            features = [ "with-bigint" ];
+          }
+          {
+            name = "getrandom";
+            packageId = "getrandom 0.2.2";
+            target = { target, features }: (target."arch" == "wasm32");
+            features = [ "js" ];
+          }
+          {
+            name = "iso8601";
+            packageId = "iso8601";
+          }
+          {
+            name = "itoa";
+            packageId = "itoa 1.0.18";
+          }
+          {
+            name = "memchr";
+            packageId = "memchr";
+          }
+          {
+            name = "num-cmp";
+            packageId = "num-cmp";
+          }
+          {
+            name = "once_cell";
+            packageId = "once_cell";
+          }
+          {
+            name = "parking_lot";
+            packageId = "parking_lot 0.12.5";
+          }
+          {
+            name = "percent-encoding";
+            packageId = "percent-encoding";
+          }
+          {
+            name = "regex";
+            packageId = "regex 1.13.1";
+          }
+          {
+            name = "serde";
+            packageId = "serde";
+            features = [ "derive" ];
+          }
+          {
+            name = "serde_json";
+            packageId = "serde_json";
+          }
+          {
+            name = "time";
+            packageId = "time 0.3.55";
+            features = [ "parsing" "macros" ];
+          }
+          {
+            name = "url";
+            packageId = "url";
+          }
+          {
+            name = "uuid";
+            packageId = "uuid 1.28.0";
+          }
+        ];
+        features = {
+          "cli" = [ "clap" ];
+          "default" = [ "resolve-http" "resolve-file" "cli" ];
+          "resolve-http" = [ "reqwest" ];
+        };
       };
       "keccak" = rec {
         crateName = "keccak";
@@ -2556,7 +3132,7 @@ rec {
         devDependencies = [
           {
             name = "num";
-            packageId = "num";
+            packageId = "num 0.1.42";
           }
         ];
         features = {
@@ -2631,7 +3207,7 @@ rec {
           }
           {
             name = "bitflags";
-            packageId = "bitflags";
+            packageId = "bitflags 1.2.1";
           }
           {
             name = "cfg-if";
@@ -2658,11 +3234,10 @@ rec {
       };
       "libc" = rec {
         crateName = "libc";
-        version = "0.2.91";
-        edition = "2015";
-        sha256 = "1rq2clij7a22p2ik62486whd2shmgkicxv4gav30w4qprbvb25l9";
+        version = "0.2.190";
+        edition = "2021";
+        sha256 = "0y5yap4bfp7rfsldcbk9pb5alcgygca5xn1n2pmh181zdpf3spff";
         authors = [
-          "The Rust Project Developers"
         ];
         features = {
           "default" = [ "std" ];
@@ -2716,41 +3291,12 @@ rec {
           }
         ];
 
-      };
-      "linkerclient" = rec {
-        crateName = "linkerclient";
-        version = "0.1.0";
-        edition = "2018";
-        src = lib.cleanSourceWith { filter = sourceFilter;  src = ./experiments/linkerclient; };
-        authors = [
-          "Stacey Ell <stacey.ell@gmail.com>"
-        ];
-        dependencies = [
-          {
-            name = "nix";
-            packageId = "nix";
-          }
-          {
-            name = "serde";
-            packageId = "serde";
-            features = [ "derive" ];
-          }
-          {
-            name = "sockets";
-            packageId = "sockets";
-          }
-          {
-            name = "yscloud-config-model";
-            packageId = "yscloud-config-model";
-          }
-        ];
-
       };
       "lock_api" = rec {
         crateName = "lock_api";
-        version = "0.4.2";
-        edition = "2018";
-        sha256 = "04hkhfq308agxg9wwmzh7ncfiyyyhn0d49n07abppzdj6p8zz5nx";
+        version = "0.4.14";
+        edition = "2021";
+        sha256 = "0rg9mhx7vdpajfxvdjmgmlyrn20ligzqvn8ifmaz7dc79gkrjhr2";
         authors = [
           "Amanieu d'Antras <amanieu@gmail.com>"
         ];
@@ -2762,7 +3308,9 @@ rec {
           }
         ];
         features = {
+          "default" = [ "atomic_usize" ];
         };
+        resolvedDefaultFeatures = [ "atomic_usize" "default" ];
       };
       "log" = rec {
         crateName = "log";
@@ -2828,7 +3376,7 @@ rec {
         dependencies = [
           {
             name = "regex-automata";
-            packageId = "regex-automata";
+            packageId = "regex-automata 0.1.9";
           }
         ];
 
@@ -2846,18 +3394,21 @@ rec {
       };
       "memchr" = rec {
         crateName = "memchr";
-        version = "2.3.4";
-        edition = "2015";
-        sha256 = "098m9clfs495illlw00hv2gg67mhm7jflld3msyclvi5m9xc9q8f";
+        version = "2.8.3";
+        edition = "2021";
+        sha256 = "161xa63ipfanf8v3nb82xd5hqgydv55nzw59wyngqbz6alfaz2yg";
         authors = [
           "Andrew Gallant <jamslam@gmail.com>"
           "bluss"
         ];
         features = {
           "default" = [ "std" ];
+          "logging" = [ "dep:log" ];
+          "rustc-dep-of-std" = [ "core" ];
+          "std" = [ "alloc" ];
           "use_std" = [ "std" ];
         };
-        resolvedDefaultFeatures = [ "default" "std" "use_std" ];
+        resolvedDefaultFeatures = [ "alloc" "default" "std" "use_std" ];
       };
       "memfd" = rec {
         crateName = "memfd";
@@ -3163,6 +3714,19 @@ rec {
         ];
 
       };
+      "multimap" = rec {
+        crateName = "multimap";
+        version = "0.8.3";
+        edition = "2015";
+        sha256 = "0sicyz4n500vdhgcxn4g8jz97cp1ijir1rnbgph3pmx9ckz4dkp5";
+        authors = [
+          "Håvar Nøvik <havar.novik@gmail.com>"
+        ];
+        features = {
+          "default" = [ "serde_impl" ];
+          "serde_impl" = [ "serde" ];
+        };
+      };
       "native-tls" = rec {
         crateName = "native-tls";
         version = "0.2.7";
@@ -3335,7 +3899,7 @@ rec {
           }
           {
             name = "bitflags";
-            packageId = "bitflags";
+            packageId = "bitflags 1.2.1";
           }
           {
             name = "byteorder";
@@ -3539,7 +4103,7 @@ rec {
         dependencies = [
           {
             name = "bitflags";
-            packageId = "bitflags";
+            packageId = "bitflags 1.2.1";
           }
           {
             name = "cfg-if";
@@ -3642,6 +4206,27 @@ rec {
         };
         resolvedDefaultFeatures = [ "alloc" "bitvec" "default" "funty" "lexical" "lexical-core" "std" ];
       };
+      "nom 8.0.0" = rec {
+        crateName = "nom";
+        version = "8.0.0";
+        edition = "2021";
+        sha256 = "01cl5xng9d0gxf26h39m0l8lprgpa00fcc75ps1yzgbib1vn35yz";
+        authors = [
+          "contact@geoffroycouprie.com"
+        ];
+        dependencies = [
+          {
+            name = "memchr";
+            packageId = "memchr";
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "default" = [ "std" ];
+          "std" = [ "alloc" "memchr/std" ];
+        };
+        resolvedDefaultFeatures = [ "alloc" "std" ];
+      };
       "ntapi" = rec {
         crateName = "ntapi";
         version = "0.3.6";
@@ -3663,7 +4248,7 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "user" ];
       };
-      "num" = rec {
+      "num 0.1.42" = rec {
         crateName = "num";
         version = "0.1.42";
         edition = "2015";
@@ -3674,12 +4259,12 @@ rec {
         dependencies = [
           {
             name = "num-bigint";
-            packageId = "num-bigint";
+            packageId = "num-bigint 0.1.44";
             optional = true;
           }
           {
             name = "num-complex";
-            packageId = "num-complex";
+            packageId = "num-complex 0.1.43";
             optional = true;
           }
           {
@@ -3692,7 +4277,7 @@ rec {
           }
           {
             name = "num-rational";
-            packageId = "num-rational";
+            packageId = "num-rational 0.1.42";
             optional = true;
           }
           {
@@ -3710,9 +4295,63 @@ rec {
         };
         resolvedDefaultFeatures = [ "bigint" "complex" "default" "num-bigint" "num-complex" "num-rational" "rational" "rustc-serialize" ];
       };
-      "num-bigint" = rec {
-        crateName = "num-bigint";
-        version = "0.1.44";
+      "num 0.4.3" = rec {
+        crateName = "num";
+        version = "0.4.3";
+        edition = "2021";
+        sha256 = "08yb2fc1psig7pkzaplm495yp7c30m4pykpkwmi5bxrgid705g9m";
+        authors = [
+          "The Rust Project Developers"
+        ];
+        dependencies = [
+          {
+            name = "num-bigint";
+            packageId = "num-bigint 0.4.8";
+            optional = true;
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "num-complex";
+            packageId = "num-complex 0.4.6";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "num-integer";
+            packageId = "num-integer";
+            usesDefaultFeatures = false;
+            features = [ "i128" ];
+          }
+          {
+            name = "num-iter";
+            packageId = "num-iter";
+            usesDefaultFeatures = false;
+            features = [ "i128" ];
+          }
+          {
+            name = "num-rational";
+            packageId = "num-rational 0.4.2";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "num-traits";
+            packageId = "num-traits";
+            usesDefaultFeatures = false;
+            features = [ "i128" ];
+          }
+        ];
+        features = {
+          "alloc" = [ "dep:num-bigint" "num-rational/num-bigint" ];
+          "default" = [ "std" ];
+          "libm" = [ "num-complex/libm" "num-traits/libm" ];
+          "rand" = [ "num-bigint/rand" "num-complex/rand" ];
+          "serde" = [ "num-bigint/serde" "num-complex/serde" "num-rational/serde" ];
+          "std" = [ "dep:num-bigint" "num-bigint/std" "num-complex/std" "num-integer/std" "num-iter/std" "num-rational/std" "num-rational/num-bigint-std" "num-traits/std" ];
+        };
+        resolvedDefaultFeatures = [ "num-bigint" "std" ];
+      };
+      "num-bigint 0.1.44" = rec {
+        crateName = "num-bigint";
+        version = "0.1.44";
         edition = "2015";
         sha256 = "1hg80xd7vd5yqzks1h0zk2fcgqnf84m2cdj9q4cffk581nnrjf76";
         authors = [
@@ -3752,7 +4391,47 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "rand" "rustc-serialize" ];
       };
-      "num-complex" = rec {
+      "num-bigint 0.4.8" = rec {
+        crateName = "num-bigint";
+        version = "0.4.8";
+        edition = "2021";
+        sha256 = "0ry3xjal8f5xhdinani268ci13h14mf7j4w0y1gflfzhw3knk7n8";
+        libName = "num_bigint";
+        authors = [
+          "The Rust Project Developers"
+        ];
+        dependencies = [
+          {
+            name = "num-integer";
+            packageId = "num-integer";
+            usesDefaultFeatures = false;
+            features = [ "i128" ];
+          }
+          {
+            name = "num-traits";
+            packageId = "num-traits";
+            usesDefaultFeatures = false;
+            features = [ "i128" ];
+          }
+        ];
+        features = {
+          "default" = [ "std" ];
+          "std" = [ "num-integer/std" "num-traits/std" ];
+        };
+        resolvedDefaultFeatures = [ "std" ];
+      };
+      "num-cmp" = rec {
+        crateName = "num-cmp";
+        version = "0.1.0";
+        edition = "2015";
+        sha256 = "1alavi36shn32b3cwbmkncj1wal3y3cwzkm21bxy5yil5hp5ncv3";
+        authors = [
+          "Kang Seonghoon <public+git@mearie.org>"
+        ];
+        features = {
+        };
+      };
+      "num-complex 0.1.43" = rec {
         crateName = "num-complex";
         version = "0.1.43";
         edition = "2015";
@@ -3778,11 +4457,11 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "rustc-serialize" ];
       };
-      "num-integer" = rec {
-        crateName = "num-integer";
-        version = "0.1.44";
-        edition = "2015";
-        sha256 = "1nq152y3304as1iai95hqz8prqnc94lks1s7q05sfjdmcf56kk6j";
+      "num-complex 0.4.6" = rec {
+        crateName = "num-complex";
+        version = "0.4.6";
+        edition = "2021";
+        sha256 = "15cla16mnw12xzf5g041nxbjjm9m85hdgadd5dl5d0b30w9qmy3k";
         authors = [
           "The Rust Project Developers"
         ];
@@ -3791,26 +4470,56 @@ rec {
             name = "num-traits";
             packageId = "num-traits";
             usesDefaultFeatures = false;
+            features = [ "i128" ];
           }
         ];
-        buildDependencies = [
+        features = {
+          "default" = [ "std" ];
+          "libm" = [ "num-traits/libm" ];
+          "std" = [ "num-traits/std" ];
+        };
+        resolvedDefaultFeatures = [ "std" ];
+      };
+      "num-conv" = rec {
+        crateName = "num-conv";
+        version = "0.2.2";
+        edition = "2021";
+        sha256 = "0hg4f9bwmy7cwpxdkm165dmkfc8jhkkayci234jsmi5ssb33j5sj";
+        libName = "num_conv";
+        authors = [
+          "Jacob Pratt <jacob@jhpratt.dev>"
+        ];
+
+      };
+      "num-integer" = rec {
+        crateName = "num-integer";
+        version = "0.1.47";
+        edition = "2018";
+        sha256 = "02z1p3azy6p10n99skrab4a6hhfd4amf2i9gm8sxqd1p9dfxkqkw";
+        libName = "num_integer";
+        authors = [
+          "The Rust Project Developers"
+        ];
+        dependencies = [
           {
-            name = "autocfg";
-            packageId = "autocfg";
+            name = "num-traits";
+            packageId = "num-traits";
+            usesDefaultFeatures = false;
+            features = [ "i128" ];
           }
         ];
         features = {
           "default" = [ "std" ];
-          "i128" = [ "num-traits/i128" ];
           "std" = [ "num-traits/std" ];
         };
-        resolvedDefaultFeatures = [ "default" "std" ];
+        resolvedDefaultFeatures = [ "default" "i128" "std" ];
       };
       "num-iter" = rec {
         crateName = "num-iter";
-        version = "0.1.42";
-        edition = "2015";
-        sha256 = "0ndd9wb9qar50fdr16xm3i1zk6h2g9br56nml2n22kd56y1iq0mj";
+        version = "0.1.46";
+        edition = "2018";
+        sha256 = "12q4x0lp9l6bvsak1p5q24lvfzl99ak9vzmwhqbwksm1d6yh0a69";
+        libName = "num_iter";
         authors = [
           "The Rust Project Developers"
         ];
@@ -3819,27 +4528,22 @@ rec {
             name = "num-integer";
             packageId = "num-integer";
             usesDefaultFeatures = false;
+            features = [ "i128" ];
           }
           {
             name = "num-traits";
             packageId = "num-traits";
             usesDefaultFeatures = false;
-          }
-        ];
-        buildDependencies = [
-          {
-            name = "autocfg";
-            packageId = "autocfg";
+            features = [ "i128" ];
           }
         ];
         features = {
           "default" = [ "std" ];
-          "i128" = [ "num-integer/i128" "num-traits/i128" ];
           "std" = [ "num-integer/std" "num-traits/std" ];
         };
-        resolvedDefaultFeatures = [ "default" "std" ];
+        resolvedDefaultFeatures = [ "default" "i128" "std" ];
       };
-      "num-rational" = rec {
+      "num-rational 0.1.42" = rec {
         crateName = "num-rational";
         version = "0.1.42";
         edition = "2015";
@@ -3850,7 +4554,7 @@ rec {
         dependencies = [
           {
             name = "num-bigint";
-            packageId = "num-bigint";
+            packageId = "num-bigint 0.1.44";
             optional = true;
           }
           {
@@ -3876,11 +4580,46 @@ rec {
         };
         resolvedDefaultFeatures = [ "bigint" "default" "num-bigint" "rustc-serialize" ];
       };
+      "num-rational 0.4.2" = rec {
+        crateName = "num-rational";
+        version = "0.4.2";
+        edition = "2021";
+        sha256 = "093qndy02817vpgcqjnj139im3jl7vkq4h68kykdqqh577d18ggq";
+        authors = [
+          "The Rust Project Developers"
+        ];
+        dependencies = [
+          {
+            name = "num-bigint";
+            packageId = "num-bigint 0.4.8";
+            optional = true;
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "num-integer";
+            packageId = "num-integer";
+            usesDefaultFeatures = false;
+            features = [ "i128" ];
+          }
+          {
+            name = "num-traits";
+            packageId = "num-traits";
+            usesDefaultFeatures = false;
+            features = [ "i128" ];
+          }
+        ];
+        features = {
+          "default" = [ "num-bigint" "std" ];
+          "num-bigint-std" = [ "num-bigint/std" ];
+          "std" = [ "num-bigint?/std" "num-integer/std" "num-traits/std" ];
+        };
+        resolvedDefaultFeatures = [ "num-bigint" "num-bigint-std" "std" ];
+      };
       "num-traits" = rec {
         crateName = "num-traits";
-        version = "0.2.14";
-        edition = "2015";
-        sha256 = "144j176s2p76azy2ngk2vkdzgwdc0bc8c93jhki8c9fsbknb2r4s";
+        version = "0.2.19";
+        edition = "2021";
+        sha256 = "0h984rhdkkqd4ny9cif7y2azl3xdfb7768hb9irhpsch4q3gq787";
         authors = [
           "The Rust Project Developers"
         ];
@@ -3893,7 +4632,7 @@ rec {
         features = {
           "default" = [ "std" ];
         };
-        resolvedDefaultFeatures = [ "default" "std" ];
+        resolvedDefaultFeatures = [ "default" "i128" "std" ];
       };
       "num_cpus" = rec {
         crateName = "num_cpus";
@@ -3940,15 +4679,18 @@ rec {
       };
       "once_cell" = rec {
         crateName = "once_cell";
-        version = "1.7.2";
-        edition = "2018";
-        sha256 = "18qmpyfigg4ibdhjy5mwcjhzk9adwlgfaqv7nj430ivm86q0i2xg";
+        version = "1.21.4";
+        edition = "2021";
+        sha256 = "0l1v676wf71kjg2khch4dphwh1jp3291ffiymr2mvy1kxd5kwz4z";
         authors = [
           "Aleksey Kladov <aleksey.kladov@gmail.com>"
         ];
         features = {
           "alloc" = [ "race" ];
+          "atomic-polyfill" = [ "critical-section" ];
+          "critical-section" = [ "dep:critical-section" "portable-atomic" ];
           "default" = [ "std" ];
+          "parking_lot" = [ "dep:parking_lot_core" ];
           "std" = [ "alloc" ];
         };
         resolvedDefaultFeatures = [ "alloc" "default" "race" "std" ];
@@ -3974,7 +4716,7 @@ rec {
         dependencies = [
           {
             name = "bitflags";
-            packageId = "bitflags";
+            packageId = "bitflags 1.2.1";
           }
           {
             name = "cfg-if";
@@ -4067,7 +4809,7 @@ rec {
         ];
 
       };
-      "parking_lot" = rec {
+      "parking_lot 0.11.1" = rec {
         crateName = "parking_lot";
         version = "0.11.1";
         edition = "2018";
@@ -4086,7 +4828,7 @@ rec {
           }
           {
             name = "parking_lot_core";
-            packageId = "parking_lot_core";
+            packageId = "parking_lot_core 0.8.3";
           }
         ];
         features = {
@@ -4099,7 +4841,34 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" ];
       };
-      "parking_lot_core" = rec {
+      "parking_lot 0.12.5" = rec {
+        crateName = "parking_lot";
+        version = "0.12.5";
+        edition = "2021";
+        sha256 = "06jsqh9aqmc94j2rlm8gpccilqm6bskbd67zf6ypfc0f4m9p91ck";
+        authors = [
+          "Amanieu d'Antras <amanieu@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "lock_api";
+            packageId = "lock_api";
+          }
+          {
+            name = "parking_lot_core";
+            packageId = "parking_lot_core 0.9.12";
+          }
+        ];
+        features = {
+          "arc_lock" = [ "lock_api/arc_lock" ];
+          "deadlock_detection" = [ "parking_lot_core/deadlock_detection" ];
+          "nightly" = [ "parking_lot_core/nightly" "lock_api/nightly" ];
+          "owning_ref" = [ "lock_api/owning_ref" ];
+          "serde" = [ "lock_api/serde" ];
+        };
+        resolvedDefaultFeatures = [ "default" ];
+      };
+      "parking_lot_core 0.8.3" = rec {
         crateName = "parking_lot_core";
         version = "0.8.3";
         edition = "2018";
@@ -4123,7 +4892,7 @@ rec {
           }
           {
             name = "redox_syscall";
-            packageId = "redox_syscall";
+            packageId = "redox_syscall 0.2.5";
             target = { target, features }: (target."os" == "redox");
           }
           {
@@ -4141,6 +4910,43 @@ rec {
           "deadlock_detection" = [ "petgraph" "thread-id" "backtrace" ];
         };
       };
+      "parking_lot_core 0.9.12" = rec {
+        crateName = "parking_lot_core";
+        version = "0.9.12";
+        edition = "2021";
+        sha256 = "1hb4rggy70fwa1w9nb0svbyflzdc69h047482v2z3sx2hmcnh896";
+        authors = [
+          "Amanieu d'Antras <amanieu@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "cfg-if";
+            packageId = "cfg-if 1.0.0";
+          }
+          {
+            name = "libc";
+            packageId = "libc";
+            target = { target, features }: (target."unix" or false);
+          }
+          {
+            name = "redox_syscall";
+            packageId = "redox_syscall 0.5.18";
+            target = { target, features }: (target."os" == "redox");
+          }
+          {
+            name = "smallvec";
+            packageId = "smallvec";
+          }
+          {
+            name = "windows-link";
+            packageId = "windows-link";
+            target = { target, features }: (target."windows" or false);
+          }
+        ];
+        features = {
+          "deadlock_detection" = [ "petgraph" "backtrace" ];
+        };
+      };
       "paste" = rec {
         crateName = "paste";
         version = "1.0.5";
@@ -4181,7 +4987,50 @@ rec {
           "pretty-print" = [ "serde" "serde_json" ];
         };
       };
-      "pin-project" = rec {
+      "petgraph" = rec {
+        crateName = "petgraph";
+        version = "0.5.1";
+        edition = "2018";
+        sha256 = "1dzxda6z17sfxly11m8ja3iargh73pw0s1sdgjyp0qp5dm51cza6";
+        authors = [
+          "bluss"
+          "mitchmindtree"
+        ];
+        dependencies = [
+          {
+            name = "fixedbitset";
+            packageId = "fixedbitset";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "indexmap";
+            packageId = "indexmap";
+          }
+        ];
+        features = {
+          "all" = [ "unstable" "quickcheck" "matrix_graph" "stable_graph" "graphmap" ];
+          "default" = [ "graphmap" "stable_graph" "matrix_graph" ];
+          "serde-1" = [ "serde" "serde_derive" ];
+          "unstable" = [ "generate" ];
+        };
+      };
+      "pin-project 0.4.30" = rec {
+        crateName = "pin-project";
+        version = "0.4.30";
+        edition = "2018";
+        sha256 = "0nlxmsiq39bc73iryh92yslrp2jzlkdjjxd7rv5sjzpflljgkw1y";
+        authors = [
+        ];
+        dependencies = [
+          {
+            name = "pin-project-internal";
+            packageId = "pin-project-internal 0.4.30";
+            usesDefaultFeatures = false;
+          }
+        ];
+
+      };
+      "pin-project 1.0.6" = rec {
         crateName = "pin-project";
         version = "1.0.6";
         edition = "2018";
@@ -4192,13 +5041,38 @@ rec {
         dependencies = [
           {
             name = "pin-project-internal";
-            packageId = "pin-project-internal";
+            packageId = "pin-project-internal 1.0.6";
             usesDefaultFeatures = false;
           }
         ];
 
       };
-      "pin-project-internal" = rec {
+      "pin-project-internal 0.4.30" = rec {
+        crateName = "pin-project-internal";
+        version = "0.4.30";
+        edition = "2018";
+        sha256 = "07p6mwz6kz317a6n3p93yk4llj939ihqdz7d1mwl7gmyx468s745";
+        procMacro = true;
+        authors = [
+        ];
+        dependencies = [
+          {
+            name = "proc-macro2";
+            packageId = "proc-macro2";
+          }
+          {
+            name = "quote";
+            packageId = "quote";
+          }
+          {
+            name = "syn";
+            packageId = "syn 1.0.67";
+            features = [ "full" "visit-mut" ];
+          }
+        ];
+
+      };
+      "pin-project-internal 1.0.6" = rec {
         crateName = "pin-project-internal";
         version = "1.0.6";
         edition = "2018";
@@ -4218,7 +5092,7 @@ rec {
           }
           {
             name = "syn";
-            packageId = "syn";
+            packageId = "syn 1.0.67";
             features = [ "full" "visit-mut" ];
           }
         ];
@@ -4264,6 +5138,20 @@ rec {
         ];
 
       };
+      "powerfmt" = rec {
+        crateName = "powerfmt";
+        version = "0.2.1";
+        edition = "2021";
+        sha256 = "0n293rvnpisjmxdqhq6zmrvcdql7j1c4zrcy5053vrv5x6wr8qsa";
+        authors = [
+          "Jacob Pratt <jacob@jhpratt.dev>"
+        ];
+        features = {
+          "default" = [ "std" "macros" ];
+          "macros" = [ "dep:powerfmt-macros" ];
+          "std" = [ "alloc" ];
+        };
+      };
       "ppp" = rec {
         crateName = "ppp";
         version = "1.2.0";
@@ -4316,17 +5204,18 @@ rec {
       };
       "proc-macro2" = rec {
         crateName = "proc-macro2";
-        version = "1.0.24";
-        edition = "2018";
-        sha256 = "0wcabxzrddcjmryndw8fpyxcq6rw63m701vx86xxf03y3bp081qy";
+        version = "1.0.107";
+        edition = "2021";
+        sha256 = "1nb6ly8kp65f724kj73ippc7lvydss24sm2vagk6qpklpg4pwplq";
+        libName = "proc_macro2";
         authors = [
-          "Alex Crichton <alex@alexcrichton.com>"
           "David Tolnay <dtolnay@gmail.com>"
+          "Alex Crichton <alex@alexcrichton.com>"
         ];
         dependencies = [
           {
-            name = "unicode-xid";
-            packageId = "unicode-xid";
+            name = "unicode-ident";
+            packageId = "unicode-ident";
           }
         ];
         features = {
@@ -4334,8 +5223,143 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "proc-macro" ];
       };
-      "quick-error" = rec {
-        crateName = "quick-error";
+      "prost" = rec {
+        crateName = "prost";
+        version = "0.6.1";
+        edition = "2018";
+        sha256 = "04n2i5ryhi5pwl524pxmfxv5z6l5s9xwf9r9wdfs8i311bzawjff";
+        authors = [
+          "Dan Burkert <dan@danburkert.com>"
+        ];
+        dependencies = [
+          {
+            name = "bytes";
+            packageId = "bytes 0.5.6";
+          }
+          {
+            name = "prost-derive";
+            packageId = "prost-derive";
+            optional = true;
+          }
+        ];
+        features = {
+          "default" = [ "prost-derive" ];
+        };
+        resolvedDefaultFeatures = [ "default" "prost-derive" ];
+      };
+      "prost-build" = rec {
+        crateName = "prost-build";
+        version = "0.6.1";
+        edition = "2018";
+        sha256 = "09iggxnikpawp7bw2gvpq1v8c6prmv1kb18f6mlvvv0kr5w0dc82";
+        authors = [
+          "Dan Burkert <dan@danburkert.com>"
+        ];
+        dependencies = [
+          {
+            name = "bytes";
+            packageId = "bytes 0.5.6";
+          }
+          {
+            name = "heck";
+            packageId = "heck";
+          }
+          {
+            name = "itertools";
+            packageId = "itertools";
+          }
+          {
+            name = "log";
+            packageId = "log";
+          }
+          {
+            name = "multimap";
+            packageId = "multimap";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "petgraph";
+            packageId = "petgraph";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "prost";
+            packageId = "prost";
+          }
+          {
+            name = "prost-types";
+            packageId = "prost-types";
+          }
+          {
+            name = "tempfile";
+            packageId = "tempfile";
+          }
+        ];
+        buildDependencies = [
+          {
+            name = "which";
+            packageId = "which";
+            usesDefaultFeatures = false;
+          }
+        ];
+
+      };
+      "prost-derive" = rec {
+        crateName = "prost-derive";
+        version = "0.6.1";
+        edition = "2018";
+        sha256 = "0wlgn7nbb3x79xdlxkg47m05gxw6cd302hzc5yhi1pmcjnds2yjk";
+        procMacro = true;
+        authors = [
+          "Dan Burkert <dan@danburkert.com>"
+        ];
+        dependencies = [
+          {
+            name = "anyhow";
+            packageId = "anyhow";
+          }
+          {
+            name = "itertools";
+            packageId = "itertools";
+          }
+          {
+            name = "proc-macro2";
+            packageId = "proc-macro2";
+          }
+          {
+            name = "quote";
+            packageId = "quote";
+          }
+          {
+            name = "syn";
+            packageId = "syn 1.0.67";
+            features = [ "extra-traits" ];
+          }
+        ];
+
+      };
+      "prost-types" = rec {
+        crateName = "prost-types";
+        version = "0.6.1";
+        edition = "2018";
+        sha256 = "1ahp6j6b62zfv1d0hfns5rs8k76xgbv7dgkm9cq03h4p0rygcd0q";
+        authors = [
+          "Dan Burkert <dan@danburkert.com>"
+        ];
+        dependencies = [
+          {
+            name = "bytes";
+            packageId = "bytes 0.5.6";
+          }
+          {
+            name = "prost";
+            packageId = "prost";
+          }
+        ];
+
+      };
+      "quick-error" = rec {
+        crateName = "quick-error";
         version = "1.2.3";
         edition = "2015";
         sha256 = "1q6za3v78hsspisc197bg3g7rpc989qycy8ypr8ap8igv10ikl51";
@@ -4347,9 +5371,9 @@ rec {
       };
       "quote" = rec {
         crateName = "quote";
-        version = "1.0.9";
-        edition = "2018";
-        sha256 = "19rjmfqzk26rxbgxy5j2ckqc2v12sw2xw8l4gi8bzpn2bmsbkl63";
+        version = "1.0.47";
+        edition = "2021";
+        sha256 = "00ch0yyzvv6s671ik0kcsbw8nigdaj2g3fr61kcahwx48aqlvgqz";
         authors = [
           "David Tolnay <dtolnay@gmail.com>"
         ];
@@ -4366,6 +5390,19 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "proc-macro" ];
       };
+      "r-efi" = rec {
+        crateName = "r-efi";
+        version = "5.3.0";
+        edition = "2018";
+        sha256 = "03sbfm3g7myvzyylff6qaxk4z6fy76yv860yy66jiswc2m6b7kb9";
+        libName = "r_efi";
+        authors = [
+        ];
+        features = {
+          "examples" = [ "native" ];
+          "rustc-dep-of-std" = [ "core" ];
+        };
+      };
       "radium" = rec {
         crateName = "radium";
         version = "0.5.3";
@@ -4482,12 +5519,21 @@ rec {
             packageId = "rand_hc 0.2.0";
             target = { target, features }: (target."os" == "emscripten");
           }
+          {
+            name = "rand_pcg";
+            packageId = "rand_pcg";
+            optional = true;
+          }
         ];
         devDependencies = [
           {
             name = "rand_hc";
             packageId = "rand_hc 0.2.0";
           }
+          {
+            name = "rand_pcg";
+            packageId = "rand_pcg";
+          }
         ];
         features = {
           "alloc" = [ "rand_core/alloc" ];
@@ -4500,7 +5546,7 @@ rec {
           "stdweb" = [ "getrandom_package/stdweb" ];
           "wasm-bindgen" = [ "getrandom_package/wasm-bindgen" ];
         };
-        resolvedDefaultFeatures = [ "alloc" "default" "getrandom" "getrandom_package" "libc" "std" ];
+        resolvedDefaultFeatures = [ "alloc" "default" "getrandom" "getrandom_package" "libc" "rand_pcg" "small_rng" "std" ];
       };
       "rand 0.8.3" = rec {
         crateName = "rand";
@@ -4722,6 +5768,24 @@ rec {
         ];
 
       };
+      "rand_pcg" = rec {
+        crateName = "rand_pcg";
+        version = "0.2.1";
+        edition = "2018";
+        sha256 = "0ab4h6s6x3py833jk61lwadq83qd1c8bih2hgi6yps9rnv0x1aqn";
+        authors = [
+          "The Rand Project Developers"
+        ];
+        dependencies = [
+          {
+            name = "rand_core";
+            packageId = "rand_core 0.5.1";
+          }
+        ];
+        features = {
+          "serde1" = [ "serde" ];
+        };
+      };
       "rand_xoshiro" = rec {
         crateName = "rand_xoshiro";
         version = "0.4.0";
@@ -4760,7 +5824,7 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "std" ];
       };
-      "redox_syscall" = rec {
+      "redox_syscall 0.2.5" = rec {
         crateName = "redox_syscall";
         version = "0.2.5";
         edition = "2018";
@@ -4772,11 +5836,32 @@ rec {
         dependencies = [
           {
             name = "bitflags";
-            packageId = "bitflags";
+            packageId = "bitflags 1.2.1";
           }
         ];
 
       };
+      "redox_syscall 0.5.18" = rec {
+        crateName = "redox_syscall";
+        version = "0.5.18";
+        edition = "2021";
+        sha256 = "0b9n38zsxylql36vybw18if68yc9jczxmbyzdwyhb9sifmag4azd";
+        libName = "syscall";
+        authors = [
+          "Jeremy Soller <jackpot51@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "bitflags";
+            packageId = "bitflags 2.13.2";
+          }
+        ];
+        features = {
+          "default" = [ "userspace" ];
+          "rustc-dep-of-std" = [ "core" "bitflags/rustc-dep-of-std" ];
+        };
+        resolvedDefaultFeatures = [ "default" "userspace" ];
+      };
       "regex 0.2.11" = rec {
         crateName = "regex";
         version = "0.2.11";
@@ -4812,49 +5897,65 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" ];
       };
-      "regex 1.4.5" = rec {
+      "regex 1.13.1" = rec {
         crateName = "regex";
-        version = "1.4.5";
-        edition = "2015";
-        sha256 = "06awg9164h7w4pmbchmj9pkrqn78sa8y252ijqk1pfmyvpn5cw4m";
+        version = "1.13.1";
+        edition = "2021";
+        sha256 = "1391a0a4100ik8cp7l577p3ip3haqq03rd9c5vdr7vcfdixj687h";
         authors = [
           "The Rust Project Developers"
+          "Andrew Gallant <jamslam@gmail.com>"
         ];
         dependencies = [
           {
             name = "aho-corasick";
-            packageId = "aho-corasick 0.7.15";
+            packageId = "aho-corasick 1.1.5";
             optional = true;
+            usesDefaultFeatures = false;
           }
           {
             name = "memchr";
             packageId = "memchr";
             optional = true;
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "regex-automata";
+            packageId = "regex-automata 0.4.18";
+            usesDefaultFeatures = false;
+            features = [ "alloc" "syntax" "meta" "nfa-pikevm" ];
           }
           {
             name = "regex-syntax";
-            packageId = "regex-syntax 0.6.23";
+            packageId = "regex-syntax 0.8.11";
             usesDefaultFeatures = false;
           }
         ];
         features = {
           "default" = [ "std" "perf" "unicode" "regex-syntax/default" ];
-          "perf" = [ "perf-cache" "perf-dfa" "perf-inline" "perf-literal" ];
-          "perf-literal" = [ "aho-corasick" "memchr" ];
-          "unicode" = [ "unicode-age" "unicode-bool" "unicode-case" "unicode-gencat" "unicode-perl" "unicode-script" "unicode-segment" "regex-syntax/unicode" ];
-          "unicode-age" = [ "regex-syntax/unicode-age" ];
-          "unicode-bool" = [ "regex-syntax/unicode-bool" ];
-          "unicode-case" = [ "regex-syntax/unicode-case" ];
-          "unicode-gencat" = [ "regex-syntax/unicode-gencat" ];
-          "unicode-perl" = [ "regex-syntax/unicode-perl" ];
-          "unicode-script" = [ "regex-syntax/unicode-script" ];
-          "unicode-segment" = [ "regex-syntax/unicode-segment" ];
+          "logging" = [ "aho-corasick?/logging" "memchr?/logging" "regex-automata/logging" ];
+          "perf" = [ "perf-cache" "perf-dfa" "perf-onepass" "perf-backtrack" "perf-inline" "perf-literal" ];
+          "perf-backtrack" = [ "regex-automata/nfa-backtrack" ];
+          "perf-dfa" = [ "regex-automata/hybrid" ];
+          "perf-dfa-full" = [ "regex-automata/dfa-build" "regex-automata/dfa-search" ];
+          "perf-inline" = [ "regex-automata/perf-inline" ];
+          "perf-literal" = [ "dep:aho-corasick" "dep:memchr" "regex-automata/perf-literal" ];
+          "perf-onepass" = [ "regex-automata/dfa-onepass" ];
+          "std" = [ "aho-corasick?/std" "memchr?/std" "regex-automata/std" "regex-syntax/std" ];
+          "unicode" = [ "unicode-age" "unicode-bool" "unicode-case" "unicode-gencat" "unicode-perl" "unicode-script" "unicode-segment" "regex-automata/unicode" "regex-syntax/unicode" ];
+          "unicode-age" = [ "regex-automata/unicode-age" "regex-syntax/unicode-age" ];
+          "unicode-bool" = [ "regex-automata/unicode-bool" "regex-syntax/unicode-bool" ];
+          "unicode-case" = [ "regex-automata/unicode-case" "regex-syntax/unicode-case" ];
+          "unicode-gencat" = [ "regex-automata/unicode-gencat" "regex-syntax/unicode-gencat" ];
+          "unicode-perl" = [ "regex-automata/unicode-perl" "regex-automata/unicode-word-boundary" "regex-syntax/unicode-perl" ];
+          "unicode-script" = [ "regex-automata/unicode-script" "regex-syntax/unicode-script" ];
+          "unicode-segment" = [ "regex-automata/unicode-segment" "regex-syntax/unicode-segment" ];
           "unstable" = [ "pattern" ];
           "use_std" = [ "std" ];
         };
-        resolvedDefaultFeatures = [ "aho-corasick" "memchr" "perf" "perf-cache" "perf-dfa" "perf-inline" "perf-literal" "std" ];
+        resolvedDefaultFeatures = [ "default" "perf" "perf-backtrack" "perf-cache" "perf-dfa" "perf-inline" "perf-literal" "perf-onepass" "std" "unicode" "unicode-age" "unicode-bool" "unicode-case" "unicode-gencat" "unicode-perl" "unicode-script" "unicode-segment" ];
       };
-      "regex-automata" = rec {
+      "regex-automata 0.1.9" = rec {
         crateName = "regex-automata";
         version = "0.1.9";
         edition = "2015";
@@ -4881,6 +5982,67 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "regex-syntax" "std" ];
       };
+      "regex-automata 0.4.18" = rec {
+        crateName = "regex-automata";
+        version = "0.4.18";
+        edition = "2021";
+        sha256 = "1cml0rm0ssqfkibh9nh3gy4b6hbsbicj1rihpwf2a4v4nawm71dd";
+        libName = "regex_automata";
+        authors = [
+          "The Rust Project Developers"
+          "Andrew Gallant <jamslam@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "aho-corasick";
+            packageId = "aho-corasick 1.1.5";
+            optional = true;
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "memchr";
+            packageId = "memchr";
+            optional = true;
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "regex-syntax";
+            packageId = "regex-syntax 0.8.11";
+            optional = true;
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "default" = [ "std" "syntax" "perf" "unicode" "meta" "nfa" "dfa" "hybrid" ];
+          "dfa" = [ "dfa-build" "dfa-search" "dfa-onepass" ];
+          "dfa-build" = [ "nfa-thompson" "dfa-search" ];
+          "dfa-onepass" = [ "nfa-thompson" ];
+          "hybrid" = [ "alloc" "nfa-thompson" ];
+          "internal-instrument" = [ "internal-instrument-pikevm" ];
+          "internal-instrument-pikevm" = [ "logging" "std" ];
+          "logging" = [ "dep:log" "aho-corasick?/logging" "memchr?/logging" ];
+          "meta" = [ "syntax" "nfa-pikevm" ];
+          "nfa" = [ "nfa-thompson" "nfa-pikevm" "nfa-backtrack" ];
+          "nfa-backtrack" = [ "nfa-thompson" ];
+          "nfa-pikevm" = [ "nfa-thompson" ];
+          "nfa-thompson" = [ "alloc" ];
+          "perf" = [ "perf-inline" "perf-literal" ];
+          "perf-literal" = [ "perf-literal-substring" "perf-literal-multisubstring" ];
+          "perf-literal-multisubstring" = [ "dep:aho-corasick" ];
+          "perf-literal-substring" = [ "aho-corasick?/perf-literal" "dep:memchr" ];
+          "std" = [ "regex-syntax?/std" "memchr?/std" "aho-corasick?/std" "alloc" ];
+          "syntax" = [ "dep:regex-syntax" "alloc" ];
+          "unicode" = [ "unicode-age" "unicode-bool" "unicode-case" "unicode-gencat" "unicode-perl" "unicode-script" "unicode-segment" "unicode-word-boundary" "regex-syntax?/unicode" ];
+          "unicode-age" = [ "regex-syntax?/unicode-age" ];
+          "unicode-bool" = [ "regex-syntax?/unicode-bool" ];
+          "unicode-case" = [ "regex-syntax?/unicode-case" ];
+          "unicode-gencat" = [ "regex-syntax?/unicode-gencat" ];
+          "unicode-perl" = [ "regex-syntax?/unicode-perl" ];
+          "unicode-script" = [ "regex-syntax?/unicode-script" ];
+          "unicode-segment" = [ "regex-syntax?/unicode-segment" ];
+        };
+        resolvedDefaultFeatures = [ "alloc" "dfa-onepass" "hybrid" "meta" "nfa-backtrack" "nfa-pikevm" "nfa-thompson" "perf-inline" "perf-literal" "perf-literal-multisubstring" "perf-literal-substring" "std" "syntax" "unicode" "unicode-age" "unicode-bool" "unicode-case" "unicode-gencat" "unicode-perl" "unicode-script" "unicode-segment" "unicode-word-boundary" ];
+      };
       "regex-syntax 0.5.6" = rec {
         crateName = "regex-syntax";
         version = "0.5.6";
@@ -4911,6 +6073,22 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "unicode" "unicode-age" "unicode-bool" "unicode-case" "unicode-gencat" "unicode-perl" "unicode-script" "unicode-segment" ];
       };
+      "regex-syntax 0.8.11" = rec {
+        crateName = "regex-syntax";
+        version = "0.8.11";
+        edition = "2021";
+        sha256 = "1m25h5q2wp976fb9gc3dsc9l99svcvd5cri8lncb51c46ydgzxnn";
+        libName = "regex_syntax";
+        authors = [
+          "The Rust Project Developers"
+          "Andrew Gallant <jamslam@gmail.com>"
+        ];
+        features = {
+          "default" = [ "std" "unicode" ];
+          "unicode" = [ "unicode-age" "unicode-bool" "unicode-case" "unicode-gencat" "unicode-perl" "unicode-script" "unicode-segment" ];
+        };
+        resolvedDefaultFeatures = [ "default" "std" "unicode" "unicode-age" "unicode-bool" "unicode-case" "unicode-gencat" "unicode-perl" "unicode-script" "unicode-segment" ];
+      };
       "remove_dir_all" = rec {
         crateName = "remove_dir_all";
         version = "0.5.3";
@@ -4940,7 +6118,7 @@ rec {
         dependencies = [
           {
             name = "base64";
-            packageId = "base64";
+            packageId = "base64 0.13.0";
             target = { target, features }: (!(target."arch" == "wasm32"));
           }
           {
@@ -5200,6 +6378,18 @@ rec {
           }
         ];
 
+      };
+      "rustversion" = rec {
+        crateName = "rustversion";
+        version = "1.0.23";
+        edition = "2018";
+        sha256 = "07z2a843fs80fawwflj9jwn49k9b0bd0dhhbvy0ar69vaxd72m6g";
+        build = "build/build.rs";
+        procMacro = true;
+        authors = [
+          "David Tolnay <dtolnay@gmail.com>"
+        ];
+
       };
       "ryu" = rec {
         crateName = "ryu";
@@ -5233,6 +6423,85 @@ rec {
           }
         ];
 
+      };
+      "schemars" = rec {
+        crateName = "schemars";
+        version = "0.8.22";
+        edition = "2021";
+        sha256 = "05an9nbi18ynyxv1rjmwbg6j08j0496hd64mjggh53mwp3hjmgrz";
+        authors = [
+          "Graham Esau <gesau@hotmail.co.uk>"
+        ];
+        dependencies = [
+          {
+            name = "dyn-clone";
+            packageId = "dyn-clone";
+          }
+          {
+            name = "schemars_derive";
+            packageId = "schemars_derive";
+            optional = true;
+          }
+          {
+            name = "serde";
+            packageId = "serde";
+            features = [ "derive" ];
+          }
+          {
+            name = "serde_json";
+            packageId = "serde_json";
+          }
+          {
+            name = "uuid";
+            packageId = "uuid 0.8.2";
+            rename = "uuid08";
+            optional = true;
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "arrayvec" = [ "arrayvec05" ];
+          "bigdecimal" = [ "bigdecimal03" ];
+          "default" = [ "derive" ];
+          "derive" = [ "schemars_derive" ];
+          "derive_json_schema" = [ "impl_json_schema" ];
+          "impl_json_schema" = [ "derive" ];
+          "indexmap1" = [ "indexmap" ];
+          "preserve_order" = [ "indexmap" ];
+          "raw_value" = [ "serde_json/raw_value" ];
+          "uuid" = [ "uuid08" ];
+        };
+        resolvedDefaultFeatures = [ "default" "derive" "schemars_derive" "uuid" "uuid08" ];
+      };
+      "schemars_derive" = rec {
+        crateName = "schemars_derive";
+        version = "0.8.22";
+        edition = "2021";
+        sha256 = "0kakyzrp5801s4i043l4ilv96lzimnlh01pap958h66n99w6bqij";
+        procMacro = true;
+        authors = [
+          "Graham Esau <gesau@hotmail.co.uk>"
+        ];
+        dependencies = [
+          {
+            name = "proc-macro2";
+            packageId = "proc-macro2";
+          }
+          {
+            name = "quote";
+            packageId = "quote";
+          }
+          {
+            name = "serde_derive_internals";
+            packageId = "serde_derive_internals";
+          }
+          {
+            name = "syn";
+            packageId = "syn 2.0.119";
+            features = [ "extra-traits" ];
+          }
+        ];
+
       };
       "scopeguard" = rec {
         crateName = "scopeguard";
@@ -5294,7 +6563,7 @@ rec {
         dependencies = [
           {
             name = "bitflags";
-            packageId = "bitflags";
+            packageId = "bitflags 1.2.1";
           }
           {
             name = "core-foundation";
@@ -5428,20 +6697,52 @@ rec {
       };
       "serde" = rec {
         crateName = "serde";
-        version = "1.0.125";
-        edition = "2015";
-        sha256 = "0w8i0f4wsq4zd9vz1k6lq00066rjrgzlxkm25h8sfpss387cb3am";
+        version = "1.0.229";
+        edition = "2021";
+        sha256 = "1fp04fq4a79bpm61xz1zy0pbz4kpc7d771zii1k3inmszq55jj21";
         authors = [
           "Erick Tryzelaar <erick.tryzelaar@gmail.com>"
           "David Tolnay <dtolnay@gmail.com>"
         ];
         dependencies = [
+          {
+            name = "serde_core";
+            packageId = "serde_core";
+            usesDefaultFeatures = false;
+            features = [ "result" ];
+          }
           {
             name = "serde_derive";
             packageId = "serde_derive";
             optional = true;
           }
         ];
+        features = {
+          "alloc" = [ "serde_core/alloc" ];
+          "default" = [ "std" ];
+          "derive" = [ "serde_derive" ];
+          "rc" = [ "serde_core/rc" ];
+          "std" = [ "serde_core/std" ];
+          "unstable" = [ "serde_core/unstable" ];
+        };
+        resolvedDefaultFeatures = [ "default" "derive" "serde_derive" "std" ];
+      };
+      "serde_core" = rec {
+        crateName = "serde_core";
+        version = "1.0.229";
+        edition = "2021";
+        sha256 = "0j1ajiha76h3nmd976il9li6975k121xa7jb39ws8n0yqp4s5p37";
+        authors = [
+          "Erick Tryzelaar <erick.tryzelaar@gmail.com>"
+          "David Tolnay <dtolnay@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "serde_derive";
+            packageId = "serde_derive";
+            target = { target, features }: ();
+          }
+        ];
         devDependencies = [
           {
             name = "serde_derive";
@@ -5449,16 +6750,15 @@ rec {
           }
         ];
         features = {
-          "default" = [ "std" ];
-          "derive" = [ "serde_derive" ];
+          "default" = [ "std" "result" ];
         };
-        resolvedDefaultFeatures = [ "default" "derive" "serde_derive" "std" ];
+        resolvedDefaultFeatures = [ "alloc" "result" "std" ];
       };
       "serde_derive" = rec {
         crateName = "serde_derive";
-        version = "1.0.125";
-        edition = "2015";
-        sha256 = "0vfhndim4sa1i1x38dyvrxyq5v8zxjs0av05ldfkn82qpfibg4xh";
+        version = "1.0.229";
+        edition = "2021";
+        sha256 = "0j4k63i7h1bikxwz2c89ig0hrwbnl9mz1czn85xx99x5cc9dg9g7";
         procMacro = true;
         authors = [
           "Erick Tryzelaar <erick.tryzelaar@gmail.com>"
@@ -5468,20 +6768,56 @@ rec {
           {
             name = "proc-macro2";
             packageId = "proc-macro2";
+            usesDefaultFeatures = false;
+            features = [ "proc-macro" ];
           }
           {
             name = "quote";
             packageId = "quote";
+            usesDefaultFeatures = false;
+            features = [ "proc-macro" ];
           }
           {
             name = "syn";
-            packageId = "syn";
+            packageId = "syn 3.0.9";
+            usesDefaultFeatures = false;
+            features = [ "clone-impls" "derive" "parsing" "printing" "proc-macro" ];
           }
         ];
         features = {
         };
         resolvedDefaultFeatures = [ "default" ];
       };
+      "serde_derive_internals" = rec {
+        crateName = "serde_derive_internals";
+        version = "0.29.1";
+        edition = "2015";
+        sha256 = "04g7macx819vbnxhi52cx0nhxi56xlhrybgwybyy7fb9m4h6mlhq";
+        libPath = "lib.rs";
+        authors = [
+          "Erick Tryzelaar <erick.tryzelaar@gmail.com>"
+          "David Tolnay <dtolnay@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "proc-macro2";
+            packageId = "proc-macro2";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "quote";
+            packageId = "quote";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "syn";
+            packageId = "syn 2.0.119";
+            usesDefaultFeatures = false;
+            features = [ "clone-impls" "derive" "parsing" "printing" ];
+          }
+        ];
+
+      };
       "serde_json" = rec {
         crateName = "serde_json";
         version = "1.0.64";
@@ -5494,7 +6830,7 @@ rec {
         dependencies = [
           {
             name = "itoa";
-            packageId = "itoa";
+            packageId = "itoa 0.4.7";
             usesDefaultFeatures = false;
           }
           {
@@ -5530,7 +6866,32 @@ rec {
           }
           {
             name = "itoa";
-            packageId = "itoa";
+            packageId = "itoa 0.4.7";
+          }
+          {
+            name = "ryu";
+            packageId = "ryu";
+          }
+          {
+            name = "serde";
+            packageId = "serde";
+          }
+        ];
+
+      };
+      "serde_yaml" = rec {
+        crateName = "serde_yaml";
+        version = "0.8.26";
+        edition = "2021";
+        sha256 = "06y7gxy312mink8nsnmci9cw0ykpgsdcxmayg0snmdbnnwrp92jp";
+        authors = [
+          "David Tolnay <dtolnay@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "indexmap";
+            packageId = "indexmap";
+            features = [ "std" ];
           }
           {
             name = "ryu";
@@ -5540,6 +6901,10 @@ rec {
             name = "serde";
             packageId = "serde";
           }
+          {
+            name = "yaml-rust";
+            packageId = "yaml-rust";
+          }
         ];
 
       };
@@ -5836,13 +7201,17 @@ rec {
           }
           {
             name = "uuid";
-            packageId = "uuid";
+            packageId = "uuid 0.8.2";
             features = [ "v4" "serde" ];
           }
           {
             name = "yscloud-config-model";
             packageId = "yscloud-config-model";
           }
+          {
+            name = "yscloud-runtime";
+            packageId = "yscloud-runtime";
+          }
         ];
 
       };
@@ -6019,7 +7388,7 @@ rec {
           }
           {
             name = "syn";
-            packageId = "syn";
+            packageId = "syn 1.0.67";
             usesDefaultFeatures = false;
             features = [ "derive" "parsing" "printing" ];
           }
@@ -6066,7 +7435,7 @@ rec {
           }
           {
             name = "syn";
-            packageId = "syn";
+            packageId = "syn 1.0.67";
             usesDefaultFeatures = false;
             features = [ "full" "parsing" "printing" "clone-impls" ];
           }
@@ -6095,7 +7464,7 @@ rec {
         ];
 
       };
-      "syn" = rec {
+      "syn 1.0.67" = rec {
         crateName = "syn";
         version = "1.0.67";
         edition = "2018";
@@ -6128,6 +7497,72 @@ rec {
         };
         resolvedDefaultFeatures = [ "clone-impls" "default" "derive" "extra-traits" "full" "parsing" "printing" "proc-macro" "quote" "visit" "visit-mut" ];
       };
+      "syn 2.0.119" = rec {
+        crateName = "syn";
+        version = "2.0.119";
+        edition = "2021";
+        sha256 = "15vjy620l91a3q4n4f4gzhnflmdr6pnm38v2m6cpk86i8av32a47";
+        authors = [
+          "David Tolnay <dtolnay@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "proc-macro2";
+            packageId = "proc-macro2";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "quote";
+            packageId = "quote";
+            optional = true;
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "unicode-ident";
+            packageId = "unicode-ident";
+          }
+        ];
+        features = {
+          "default" = [ "derive" "parsing" "printing" "clone-impls" "proc-macro" ];
+          "printing" = [ "dep:quote" ];
+          "proc-macro" = [ "proc-macro2/proc-macro" "quote?/proc-macro" ];
+          "test" = [ "syn-test-suite/all-features" ];
+        };
+        resolvedDefaultFeatures = [ "clone-impls" "default" "derive" "extra-traits" "full" "parsing" "printing" "proc-macro" "visit" "visit-mut" ];
+      };
+      "syn 3.0.9" = rec {
+        crateName = "syn";
+        version = "3.0.9";
+        edition = "2021";
+        sha256 = "0fw28lhl90kls24q2h2sp39yjb0lsvz5cwh9fd3f3w3v9kp8v36p";
+        authors = [
+          "David Tolnay <dtolnay@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "proc-macro2";
+            packageId = "proc-macro2";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "quote";
+            packageId = "quote";
+            optional = true;
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "unicode-ident";
+            packageId = "unicode-ident";
+          }
+        ];
+        features = {
+          "default" = [ "derive" "parsing" "printing" "clone-impls" "proc-macro" ];
+          "printing" = [ "dep:quote" ];
+          "proc-macro" = [ "proc-macro2/proc-macro" "quote?/proc-macro" ];
+          "test" = [ "syn-test-suite/all-features" ];
+        };
+        resolvedDefaultFeatures = [ "clone-impls" "derive" "parsing" "printing" "proc-macro" ];
+      };
       "synstructure" = rec {
         crateName = "synstructure";
         version = "0.12.4";
@@ -6149,7 +7584,7 @@ rec {
           }
           {
             name = "syn";
-            packageId = "syn";
+            packageId = "syn 1.0.67";
             usesDefaultFeatures = false;
             features = [ "derive" "parsing" "printing" "clone-impls" "visit" "extra-traits" ];
           }
@@ -6202,7 +7637,7 @@ rec {
           }
           {
             name = "redox_syscall";
-            packageId = "redox_syscall";
+            packageId = "redox_syscall 0.2.5";
             target = { target, features }: (target."os" == "redox");
           }
           {
@@ -6287,7 +7722,7 @@ rec {
           }
           {
             name = "syn";
-            packageId = "syn";
+            packageId = "syn 1.0.67";
           }
         ];
 
@@ -6391,7 +7826,7 @@ rec {
           }
           {
             name = "time-macros";
-            packageId = "time-macros";
+            packageId = "time-macros 0.1.1";
           }
           {
             name = "winapi";
@@ -6419,7 +7854,90 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "deprecated" "libc" "std" "stdweb" "winapi" ];
       };
-      "time-macros" = rec {
+      "time 0.3.55" = rec {
+        crateName = "time";
+        version = "0.3.55";
+        edition = "2024";
+        sha256 = "0d6iyws47z50zlksf5m3cflxvjrcgfhjglhn112gmpahxjappf6d";
+        authors = [
+          "Jacob Pratt <open-source@jhpratt.dev>"
+          "Time contributors"
+        ];
+        dependencies = [
+          {
+            name = "deranged";
+            packageId = "deranged";
+          }
+          {
+            name = "num-conv";
+            packageId = "num-conv";
+          }
+          {
+            name = "powerfmt";
+            packageId = "powerfmt";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "serde_core";
+            packageId = "serde_core";
+            optional = true;
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "time-core";
+            packageId = "time-core";
+          }
+          {
+            name = "time-macros";
+            packageId = "time-macros 0.2.32";
+            optional = true;
+          }
+        ];
+        devDependencies = [
+          {
+            name = "num-conv";
+            packageId = "num-conv";
+          }
+          {
+            name = "time-macros";
+            packageId = "time-macros 0.2.32";
+          }
+        ];
+        features = {
+          "alloc" = [ "serde_core?/alloc" ];
+          "default" = [ "std" ];
+          "formatting" = [ "std" "time-macros?/formatting" ];
+          "large-dates" = [ "time-core/large-dates" "time-macros?/large-dates" ];
+          "local-offset" = [ "std" "dep:libc" "dep:num_threads" ];
+          "macros" = [ "dep:time-macros" ];
+          "parsing" = [ "time-macros?/parsing" ];
+          "quickcheck" = [ "dep:quickcheck" "alloc" "deranged/quickcheck" ];
+          "rand" = [ "rand08" "rand09" "rand010" ];
+          "rand010" = [ "dep:rand010" "deranged/rand010" ];
+          "rand08" = [ "dep:rand08" "deranged/rand08" ];
+          "rand09" = [ "dep:rand09" "deranged/rand09" ];
+          "serde" = [ "dep:serde_core" "time-macros?/serde" "deranged/serde" ];
+          "serde-human-readable" = [ "serde" "formatting" "parsing" ];
+          "serde-well-known" = [ "serde" "formatting" "parsing" ];
+          "std" = [ "alloc" ];
+          "wasm-bindgen" = [ "dep:js-sys" ];
+        };
+        resolvedDefaultFeatures = [ "alloc" "default" "macros" "parsing" "std" ];
+      };
+      "time-core" = rec {
+        crateName = "time-core";
+        version = "0.1.9";
+        edition = "2024";
+        sha256 = "028ix0ax7ixp1h1k5zsqwgw85w6y1q32irslma7ci6ddd5kr074y";
+        libName = "time_core";
+        authors = [
+          "Jacob Pratt <open-source@jhpratt.dev>"
+          "Time contributors"
+        ];
+        features = {
+        };
+      };
+      "time-macros 0.1.1" = rec {
         crateName = "time-macros";
         version = "0.1.1";
         edition = "2018";
@@ -6439,6 +7957,31 @@ rec {
         ];
 
       };
+      "time-macros 0.2.32" = rec {
+        crateName = "time-macros";
+        version = "0.2.32";
+        edition = "2024";
+        sha256 = "11gdd3b81mj8i0h114qfjjzm8j2rz2mhr9byr0ksjbldli196s3y";
+        libName = "time_macros";
+        procMacro = true;
+        authors = [
+          "Jacob Pratt <open-source@jhpratt.dev>"
+          "Time contributors"
+        ];
+        dependencies = [
+          {
+            name = "num-conv";
+            packageId = "num-conv";
+          }
+          {
+            name = "time-core";
+            packageId = "time-core";
+          }
+        ];
+        features = {
+        };
+        resolvedDefaultFeatures = [ "parsing" ];
+      };
       "time-macros-impl" = rec {
         crateName = "time-macros-impl";
         version = "0.1.1";
@@ -6468,7 +8011,7 @@ rec {
           }
           {
             name = "syn";
-            packageId = "syn";
+            packageId = "syn 1.0.67";
             usesDefaultFeatures = false;
             features = [ "proc-macro" "parsing" "printing" "derive" ];
           }
@@ -6683,7 +8226,7 @@ rec {
           }
           {
             name = "parking_lot";
-            packageId = "parking_lot";
+            packageId = "parking_lot 0.11.1";
             optional = true;
           }
           {
@@ -6740,202 +8283,875 @@ rec {
         sha256 = "0ni60vnrf32r3wfhlahmnds1phx5d1xfbmyq9j0mz8kkzh5s0kg4";
         procMacro = true;
         authors = [
-          "Tokio Contributors <team@tokio.rs>"
+          "Tokio Contributors <team@tokio.rs>"
+        ];
+        dependencies = [
+          {
+            name = "proc-macro2";
+            packageId = "proc-macro2";
+          }
+          {
+            name = "quote";
+            packageId = "quote";
+          }
+          {
+            name = "syn";
+            packageId = "syn 1.0.67";
+            features = [ "full" ];
+          }
+        ];
+
+      };
+      "tokio-macros 1.1.0" = rec {
+        crateName = "tokio-macros";
+        version = "1.1.0";
+        edition = "2018";
+        sha256 = "0mys5zm2gcdgc0iq8nqipzn703q26x1bpw59m04shikgacdb3xya";
+        procMacro = true;
+        authors = [
+          "Tokio Contributors <team@tokio.rs>"
+        ];
+        dependencies = [
+          {
+            name = "proc-macro2";
+            packageId = "proc-macro2";
+          }
+          {
+            name = "quote";
+            packageId = "quote";
+          }
+          {
+            name = "syn";
+            packageId = "syn 1.0.67";
+            features = [ "full" ];
+          }
+        ];
+
+      };
+      "tokio-tls" = rec {
+        crateName = "tokio-tls";
+        version = "0.3.1";
+        edition = "2018";
+        sha256 = "0hv375949532p32d0w6bm2f6d3r0d00mcdzqjjqlzcmkszyg8w4s";
+        authors = [
+          "Tokio Contributors <team@tokio.rs>"
+        ];
+        dependencies = [
+          {
+            name = "native-tls";
+            packageId = "native-tls";
+          }
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+          }
+        ];
+        devDependencies = [
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+            features = [ "macros" "stream" "rt-core" "io-util" "net" ];
+          }
+        ];
+
+      };
+      "tokio-util 0.2.0" = rec {
+        crateName = "tokio-util";
+        version = "0.2.0";
+        edition = "2018";
+        sha256 = "0c39s4y0kvzkyarn1f9s8khqyajiqn7m4cjsa208f87ch88sa7ap";
+        authors = [
+          "Tokio Contributors <team@tokio.rs>"
+        ];
+        dependencies = [
+          {
+            name = "bytes";
+            packageId = "bytes 0.5.6";
+          }
+          {
+            name = "futures-core";
+            packageId = "futures-core";
+          }
+          {
+            name = "futures-sink";
+            packageId = "futures-sink";
+          }
+          {
+            name = "log";
+            packageId = "log";
+          }
+          {
+            name = "pin-project-lite";
+            packageId = "pin-project-lite 0.1.12";
+          }
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+          }
+        ];
+        devDependencies = [
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+            features = [ "full" ];
+          }
+        ];
+        features = {
+          "full" = [ "codec" "udp" ];
+          "udp" = [ "tokio/udp" ];
+        };
+        resolvedDefaultFeatures = [ "codec" ];
+      };
+      "tokio-util 0.3.1" = rec {
+        crateName = "tokio-util";
+        version = "0.3.1";
+        edition = "2018";
+        sha256 = "16b48dl6sbc9x944jgjvsd65ab1w2c2qcziddbrbwv1b3y4l50my";
+        authors = [
+          "Tokio Contributors <team@tokio.rs>"
+        ];
+        dependencies = [
+          {
+            name = "bytes";
+            packageId = "bytes 0.5.6";
+          }
+          {
+            name = "futures-core";
+            packageId = "futures-core";
+          }
+          {
+            name = "futures-sink";
+            packageId = "futures-sink";
+          }
+          {
+            name = "log";
+            packageId = "log";
+          }
+          {
+            name = "pin-project-lite";
+            packageId = "pin-project-lite 0.1.12";
+          }
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+          }
+        ];
+        devDependencies = [
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+            features = [ "full" ];
+          }
+        ];
+        features = {
+          "codec" = [ "tokio/stream" ];
+          "compat" = [ "futures-io" ];
+          "full" = [ "codec" "udp" "compat" ];
+          "udp" = [ "tokio/udp" ];
+        };
+        resolvedDefaultFeatures = [ "codec" "default" ];
+      };
+      "toml" = rec {
+        crateName = "toml";
+        version = "0.5.8";
+        edition = "2018";
+        sha256 = "1apcmjrrjw429pjw7mqlmdwwd67g8305vwqy4kw3swr612bl44d3";
+        authors = [
+          "Alex Crichton <alex@alexcrichton.com>"
+        ];
+        dependencies = [
+          {
+            name = "serde";
+            packageId = "serde";
+          }
+        ];
+        features = {
+          "preserve_order" = [ "indexmap" ];
+        };
+        resolvedDefaultFeatures = [ "default" ];
+      };
+      "tonic" = rec {
+        crateName = "tonic";
+        version = "0.3.1";
+        edition = "2018";
+        sha256 = "0z9k8cp8dhra4prv97xw4vgpv71afzg80w36cc293kwy8gkxd9bl";
+        authors = [
+          "Lucio Franco <luciofranco14@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "async-stream";
+            packageId = "async-stream";
+          }
+          {
+            name = "async-trait";
+            packageId = "async-trait";
+            optional = true;
+          }
+          {
+            name = "base64";
+            packageId = "base64 0.12.3";
+          }
+          {
+            name = "bytes";
+            packageId = "bytes 0.5.6";
+          }
+          {
+            name = "futures-core";
+            packageId = "futures-core";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "futures-util";
+            packageId = "futures-util";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "http";
+            packageId = "http";
+          }
+          {
+            name = "http-body";
+            packageId = "http-body";
+          }
+          {
+            name = "hyper";
+            packageId = "hyper";
+            optional = true;
+            features = [ "stream" ];
+          }
+          {
+            name = "percent-encoding";
+            packageId = "percent-encoding";
+          }
+          {
+            name = "pin-project";
+            packageId = "pin-project 0.4.30";
+          }
+          {
+            name = "prost";
+            packageId = "prost";
+            rename = "prost1";
+            optional = true;
+          }
+          {
+            name = "prost-derive";
+            packageId = "prost-derive";
+            optional = true;
+          }
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+            optional = true;
+            features = [ "tcp" ];
+          }
+          {
+            name = "tokio-util";
+            packageId = "tokio-util 0.3.1";
+            features = [ "codec" ];
+          }
+          {
+            name = "tower";
+            packageId = "tower";
+            optional = true;
+          }
+          {
+            name = "tower-balance";
+            packageId = "tower-balance";
+            optional = true;
+          }
+          {
+            name = "tower-load";
+            packageId = "tower-load";
+            optional = true;
+          }
+          {
+            name = "tower-make";
+            packageId = "tower-make";
+            features = [ "connect" ];
+          }
+          {
+            name = "tower-service";
+            packageId = "tower-service";
+          }
+          {
+            name = "tracing";
+            packageId = "tracing";
+          }
+          {
+            name = "tracing-futures";
+            packageId = "tracing-futures";
+            optional = true;
+          }
+        ];
+        devDependencies = [
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+            features = [ "rt-core" "macros" ];
+          }
+        ];
+        features = {
+          "codegen" = [ "async-trait" ];
+          "default" = [ "transport" "codegen" "prost" ];
+          "prost" = [ "prost1" "prost-derive" ];
+          "tls" = [ "transport" "tokio-rustls" ];
+          "tls-roots" = [ "tls" "rustls-native-certs" ];
+          "transport" = [ "hyper" "tokio" "tower" "tower-balance" "tower-load" "tracing-futures" ];
+        };
+        resolvedDefaultFeatures = [ "async-trait" "codegen" "default" "hyper" "prost" "prost-derive" "prost1" "tokio" "tower" "tower-balance" "tower-load" "tracing-futures" "transport" ];
+      };
+      "tonic-build" = rec {
+        crateName = "tonic-build";
+        version = "0.3.1";
+        edition = "2018";
+        sha256 = "0crnbzg0r9habl4h4fjf3j58xg4b3c1c8x5yc84q5k1sizshr5qr";
+        authors = [
+          "Lucio Franco <luciofranco14@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "proc-macro2";
+            packageId = "proc-macro2";
+          }
+          {
+            name = "prost-build";
+            packageId = "prost-build";
+            optional = true;
+          }
+          {
+            name = "quote";
+            packageId = "quote";
+          }
+          {
+            name = "syn";
+            packageId = "syn 1.0.67";
+          }
+        ];
+        features = {
+          "default" = [ "transport" "rustfmt" "prost" ];
+          "prost" = [ "prost-build" ];
+        };
+        resolvedDefaultFeatures = [ "default" "prost" "prost-build" "rustfmt" "transport" ];
+      };
+      "tower" = rec {
+        crateName = "tower";
+        version = "0.3.1";
+        edition = "2018";
+        sha256 = "0qpykkjgf2ijpcqr6z374dj5dxdl1anvl2p8zhl7l2q9gh0njcgx";
+        authors = [
+          "Tower Maintainers <team@tower-rs.com>"
+        ];
+        dependencies = [
+          {
+            name = "futures-core";
+            packageId = "futures-core";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "tower-buffer";
+            packageId = "tower-buffer";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "tower-discover";
+            packageId = "tower-discover";
+          }
+          {
+            name = "tower-layer";
+            packageId = "tower-layer";
+          }
+          {
+            name = "tower-limit";
+            packageId = "tower-limit";
+          }
+          {
+            name = "tower-load-shed";
+            packageId = "tower-load-shed";
+          }
+          {
+            name = "tower-retry";
+            packageId = "tower-retry";
+          }
+          {
+            name = "tower-service";
+            packageId = "tower-service";
+          }
+          {
+            name = "tower-timeout";
+            packageId = "tower-timeout";
+          }
+          {
+            name = "tower-util";
+            packageId = "tower-util";
+            features = [ "call-all" ];
+          }
+        ];
+        features = {
+          "default" = [ "full" "log" ];
+          "log" = [ "tower-buffer/log" ];
+        };
+        resolvedDefaultFeatures = [ "default" "full" "log" ];
+      };
+      "tower-balance" = rec {
+        crateName = "tower-balance";
+        version = "0.3.0";
+        edition = "2018";
+        sha256 = "0k2x4yy7dxmyc3f3422604fnygj35j5gkz0yhm4281dp2dv2g4m7";
+        authors = [
+          "Tower Maintainers <team@tower-rs.com>"
+        ];
+        dependencies = [
+          {
+            name = "futures-core";
+            packageId = "futures-core";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "futures-util";
+            packageId = "futures-util";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "indexmap";
+            packageId = "indexmap";
+          }
+          {
+            name = "pin-project";
+            packageId = "pin-project 0.4.30";
+          }
+          {
+            name = "rand";
+            packageId = "rand 0.7.3";
+            features = [ "small_rng" ];
+          }
+          {
+            name = "slab";
+            packageId = "slab";
+          }
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+            features = [ "sync" "time" ];
+          }
+          {
+            name = "tower-discover";
+            packageId = "tower-discover";
+          }
+          {
+            name = "tower-layer";
+            packageId = "tower-layer";
+          }
+          {
+            name = "tower-load";
+            packageId = "tower-load";
+          }
+          {
+            name = "tower-make";
+            packageId = "tower-make";
+          }
+          {
+            name = "tower-ready-cache";
+            packageId = "tower-ready-cache";
+          }
+          {
+            name = "tower-service";
+            packageId = "tower-service";
+          }
+          {
+            name = "tracing";
+            packageId = "tracing";
+          }
+        ];
+        features = {
+          "default" = [ "log" ];
+          "log" = [ "tracing/log" ];
+        };
+        resolvedDefaultFeatures = [ "default" "log" ];
+      };
+      "tower-buffer" = rec {
+        crateName = "tower-buffer";
+        version = "0.3.0";
+        edition = "2018";
+        sha256 = "0sj40370p9mh0llcgbvk6ddwzmig3kay9q36kf5lqijxlv17v264";
+        authors = [
+          "Tower Maintainers <team@tower-rs.com>"
+        ];
+        dependencies = [
+          {
+            name = "futures-core";
+            packageId = "futures-core";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "pin-project";
+            packageId = "pin-project 0.4.30";
+          }
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+            features = [ "rt-core" "sync" ];
+          }
+          {
+            name = "tower-layer";
+            packageId = "tower-layer";
+          }
+          {
+            name = "tower-service";
+            packageId = "tower-service";
+          }
+          {
+            name = "tracing";
+            packageId = "tracing";
+          }
+        ];
+        devDependencies = [
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+            features = [ "macros" ];
+          }
+        ];
+        features = {
+          "default" = [ "log" ];
+          "log" = [ "tracing/log" ];
+        };
+        resolvedDefaultFeatures = [ "log" ];
+      };
+      "tower-discover" = rec {
+        crateName = "tower-discover";
+        version = "0.3.0";
+        edition = "2018";
+        sha256 = "0aiwpxjsnhz1560w9whxpy6d0cxv6s0z5pwmqsf2ckf5qc050sqg";
+        authors = [
+          "Tower Maintainers <team@tower-rs.com>"
+        ];
+        dependencies = [
+          {
+            name = "futures-core";
+            packageId = "futures-core";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "pin-project";
+            packageId = "pin-project 0.4.30";
+          }
+          {
+            name = "tower-service";
+            packageId = "tower-service";
+          }
+        ];
+
+      };
+      "tower-layer" = rec {
+        crateName = "tower-layer";
+        version = "0.3.3";
+        edition = "2018";
+        sha256 = "03kq92fdzxin51w8iqix06dcfgydyvx7yr6izjq0p626v9n2l70j";
+        libName = "tower_layer";
+        authors = [
+          "Tower Maintainers <team@tower-rs.com>"
+        ];
+
+      };
+      "tower-limit" = rec {
+        crateName = "tower-limit";
+        version = "0.3.1";
+        edition = "2018";
+        sha256 = "010l6v8vidpsxsq3mws421scsm54q5x53m50zazqmmmybl609hwj";
+        authors = [
+          "Tower Maintainers <team@tower-rs.com>"
+        ];
+        dependencies = [
+          {
+            name = "futures-core";
+            packageId = "futures-core";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "pin-project";
+            packageId = "pin-project 0.4.30";
+          }
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+            features = [ "time" ];
+          }
+          {
+            name = "tower-layer";
+            packageId = "tower-layer";
+          }
+          {
+            name = "tower-load";
+            packageId = "tower-load";
+          }
+          {
+            name = "tower-service";
+            packageId = "tower-service";
+          }
+        ];
+        devDependencies = [
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+            features = [ "macros" "test-util" ];
+          }
+        ];
+
+      };
+      "tower-load" = rec {
+        crateName = "tower-load";
+        version = "0.3.0";
+        edition = "2018";
+        sha256 = "0yq1sb2r2rdfsxfhg9k8sxcfvy2hdiyglzkdjsvr4x6hmz1rziwc";
+        authors = [
+          "Tower Maintainers <team@tower-rs.com>"
         ];
         dependencies = [
           {
-            name = "proc-macro2";
-            packageId = "proc-macro2";
+            name = "futures-core";
+            packageId = "futures-core";
+            usesDefaultFeatures = false;
           }
           {
-            name = "quote";
-            packageId = "quote";
+            name = "log";
+            packageId = "log";
           }
           {
-            name = "syn";
-            packageId = "syn";
-            features = [ "full" ];
+            name = "pin-project";
+            packageId = "pin-project 0.4.30";
+          }
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+            features = [ "time" ];
+          }
+          {
+            name = "tower-discover";
+            packageId = "tower-discover";
+          }
+          {
+            name = "tower-service";
+            packageId = "tower-service";
+          }
+        ];
+        devDependencies = [
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+            features = [ "macros" "test-util" ];
           }
         ];
 
       };
-      "tokio-macros 1.1.0" = rec {
-        crateName = "tokio-macros";
-        version = "1.1.0";
+      "tower-load-shed" = rec {
+        crateName = "tower-load-shed";
+        version = "0.3.0";
         edition = "2018";
-        sha256 = "0mys5zm2gcdgc0iq8nqipzn703q26x1bpw59m04shikgacdb3xya";
-        procMacro = true;
+        sha256 = "0kmfy08nq1hjj444rdw9qrwkxbhda4i6jjzbbwqxqwq1j0iiw0lz";
         authors = [
-          "Tokio Contributors <team@tokio.rs>"
+          "Tower Maintainers <team@tower-rs.com>"
         ];
         dependencies = [
           {
-            name = "proc-macro2";
-            packageId = "proc-macro2";
+            name = "futures-core";
+            packageId = "futures-core";
+            usesDefaultFeatures = false;
           }
           {
-            name = "quote";
-            packageId = "quote";
+            name = "pin-project";
+            packageId = "pin-project 0.4.30";
           }
           {
-            name = "syn";
-            packageId = "syn";
-            features = [ "full" ];
+            name = "tower-layer";
+            packageId = "tower-layer";
+          }
+          {
+            name = "tower-service";
+            packageId = "tower-service";
           }
         ];
 
       };
-      "tokio-tls" = rec {
-        crateName = "tokio-tls";
-        version = "0.3.1";
+      "tower-make" = rec {
+        crateName = "tower-make";
+        version = "0.3.0";
         edition = "2018";
-        sha256 = "0hv375949532p32d0w6bm2f6d3r0d00mcdzqjjqlzcmkszyg8w4s";
+        sha256 = "0rf9rnjiwffd6k12qkhhilj6l584ckvx9zvp92zn80sach6kfl6f";
         authors = [
-          "Tokio Contributors <team@tokio.rs>"
+          "Tower Maintainers <team@tower-rs.com>"
         ];
         dependencies = [
-          {
-            name = "native-tls";
-            packageId = "native-tls";
-          }
           {
             name = "tokio";
             packageId = "tokio 0.2.20";
+            optional = true;
           }
-        ];
-        devDependencies = [
           {
-            name = "tokio";
-            packageId = "tokio 0.2.20";
-            features = [ "macros" "stream" "rt-core" "io-util" "net" ];
+            name = "tower-service";
+            packageId = "tower-service";
           }
         ];
-
+        features = {
+          "connect" = [ "tokio" ];
+        };
+        resolvedDefaultFeatures = [ "connect" "tokio" ];
       };
-      "tokio-util 0.2.0" = rec {
-        crateName = "tokio-util";
-        version = "0.2.0";
+      "tower-ready-cache" = rec {
+        crateName = "tower-ready-cache";
+        version = "0.3.1";
         edition = "2018";
-        sha256 = "0c39s4y0kvzkyarn1f9s8khqyajiqn7m4cjsa208f87ch88sa7ap";
+        sha256 = "07qyb5mv4xjhvwjxhkmivhahrb8wnf0cfcn85rz2d0al1ribdasf";
         authors = [
-          "Tokio Contributors <team@tokio.rs>"
+          "Tower Maintainers <team@tower-rs.com>"
         ];
         dependencies = [
-          {
-            name = "bytes";
-            packageId = "bytes 0.5.6";
-          }
           {
             name = "futures-core";
             packageId = "futures-core";
+            usesDefaultFeatures = false;
           }
           {
-            name = "futures-sink";
-            packageId = "futures-sink";
+            name = "futures-util";
+            packageId = "futures-util";
+            usesDefaultFeatures = false;
+            features = [ "alloc" ];
           }
           {
-            name = "log";
-            packageId = "log";
+            name = "indexmap";
+            packageId = "indexmap";
           }
           {
-            name = "pin-project-lite";
-            packageId = "pin-project-lite 0.1.12";
+            name = "log";
+            packageId = "log";
           }
           {
             name = "tokio";
             packageId = "tokio 0.2.20";
+            features = [ "sync" ];
           }
-        ];
-        devDependencies = [
           {
-            name = "tokio";
-            packageId = "tokio 0.2.20";
-            features = [ "full" ];
+            name = "tower-service";
+            packageId = "tower-service";
           }
         ];
-        features = {
-          "full" = [ "codec" "udp" ];
-          "udp" = [ "tokio/udp" ];
-        };
-        resolvedDefaultFeatures = [ "codec" ];
+
       };
-      "tokio-util 0.3.1" = rec {
-        crateName = "tokio-util";
-        version = "0.3.1";
+      "tower-retry" = rec {
+        crateName = "tower-retry";
+        version = "0.3.0";
         edition = "2018";
-        sha256 = "16b48dl6sbc9x944jgjvsd65ab1w2c2qcziddbrbwv1b3y4l50my";
+        sha256 = "0lm9nv4qcvk44nr443xkkpcnaklfzq4b6clj9myrby52m9b7jwp6";
         authors = [
-          "Tokio Contributors <team@tokio.rs>"
+          "Tower Maintainers <team@tower-rs.com>"
         ];
         dependencies = [
-          {
-            name = "bytes";
-            packageId = "bytes 0.5.6";
-          }
           {
             name = "futures-core";
             packageId = "futures-core";
+            usesDefaultFeatures = false;
           }
           {
-            name = "futures-sink";
-            packageId = "futures-sink";
+            name = "pin-project";
+            packageId = "pin-project 0.4.30";
           }
           {
-            name = "log";
-            packageId = "log";
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+            features = [ "time" ];
           }
           {
-            name = "pin-project-lite";
-            packageId = "pin-project-lite 0.1.12";
+            name = "tower-layer";
+            packageId = "tower-layer";
           }
           {
-            name = "tokio";
-            packageId = "tokio 0.2.20";
+            name = "tower-service";
+            packageId = "tower-service";
           }
         ];
         devDependencies = [
           {
             name = "tokio";
             packageId = "tokio 0.2.20";
-            features = [ "full" ];
+            features = [ "macros" "test-util" ];
           }
         ];
-        features = {
-          "codec" = [ "tokio/stream" ];
-          "compat" = [ "futures-io" ];
-          "full" = [ "codec" "udp" "compat" ];
-          "udp" = [ "tokio/udp" ];
-        };
-        resolvedDefaultFeatures = [ "codec" "default" ];
+
       };
-      "toml" = rec {
-        crateName = "toml";
-        version = "0.5.8";
+      "tower-service" = rec {
+        crateName = "tower-service";
+        version = "0.3.1";
         edition = "2018";
-        sha256 = "1apcmjrrjw429pjw7mqlmdwwd67g8305vwqy4kw3swr612bl44d3";
+        sha256 = "1iih764s3f6vlkspfmr72fkrs2lw1v3wiqmc6bd5zq1hdlfzs39n";
         authors = [
-          "Alex Crichton <alex@alexcrichton.com>"
+          "Tower Maintainers <team@tower-rs.com>"
+        ];
+
+      };
+      "tower-timeout" = rec {
+        crateName = "tower-timeout";
+        version = "0.3.0";
+        edition = "2018";
+        sha256 = "07mkwjr9hqdr4w84h2ansanl0ba8iihc1bpa4f497gjpncj8jyqj";
+        authors = [
+          "Tower Maintainers <team@tower-rs.com>"
         ];
         dependencies = [
           {
-            name = "serde";
-            packageId = "serde";
+            name = "pin-project";
+            packageId = "pin-project 0.4.30";
+          }
+          {
+            name = "tokio";
+            packageId = "tokio 0.2.20";
+            features = [ "time" ];
+          }
+          {
+            name = "tower-layer";
+            packageId = "tower-layer";
+          }
+          {
+            name = "tower-service";
+            packageId = "tower-service";
           }
         ];
-        features = {
-          "preserve_order" = [ "indexmap" ];
-        };
-        resolvedDefaultFeatures = [ "default" ];
+
       };
-      "tower-service" = rec {
-        crateName = "tower-service";
+      "tower-util" = rec {
+        crateName = "tower-util";
         version = "0.3.1";
         edition = "2018";
-        sha256 = "1iih764s3f6vlkspfmr72fkrs2lw1v3wiqmc6bd5zq1hdlfzs39n";
+        sha256 = "0x4np2s7h891spvxaarcyainj12a7gvnh7jif9y80cvdh8ckq2fi";
         authors = [
           "Tower Maintainers <team@tower-rs.com>"
         ];
-
+        dependencies = [
+          {
+            name = "futures-core";
+            packageId = "futures-core";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "futures-util";
+            packageId = "futures-util";
+            optional = true;
+            usesDefaultFeatures = false;
+            features = [ "alloc" ];
+          }
+          {
+            name = "pin-project";
+            packageId = "pin-project 0.4.30";
+          }
+          {
+            name = "tower-service";
+            packageId = "tower-service";
+          }
+        ];
+        features = {
+          "call-all" = [ "futures-util" ];
+          "default" = [ "call-all" ];
+        };
+        resolvedDefaultFeatures = [ "call-all" "default" "futures-util" ];
       };
       "tracing" = rec {
         crateName = "tracing";
@@ -7007,7 +9223,7 @@ rec {
           }
           {
             name = "syn";
-            packageId = "syn";
+            packageId = "syn 1.0.67";
             usesDefaultFeatures = false;
             features = [ "full" "parsing" "printing" "visit" "visit-mut" "clone-impls" "extra-traits" "proc-macro" ];
           }
@@ -7048,7 +9264,7 @@ rec {
         dependencies = [
           {
             name = "pin-project";
-            packageId = "pin-project";
+            packageId = "pin-project 1.0.6";
             optional = true;
           }
           {
@@ -7064,7 +9280,7 @@ rec {
           "std" = [ "tracing/std" ];
           "std-future" = [ "pin-project" ];
         };
-        resolvedDefaultFeatures = [ "pin-project" "std-future" ];
+        resolvedDefaultFeatures = [ "default" "pin-project" "std" "std-future" ];
       };
       "tracing-log" = rec {
         crateName = "tracing-log";
@@ -7149,7 +9365,7 @@ rec {
           }
           {
             name = "regex";
-            packageId = "regex 1.4.5";
+            packageId = "regex 1.13.1";
             optional = true;
             usesDefaultFeatures = false;
             features = [ "std" ];
@@ -7206,7 +9422,7 @@ rec {
         devDependencies = [
           {
             name = "regex";
-            packageId = "regex 1.4.5";
+            packageId = "regex 1.13.1";
             usesDefaultFeatures = false;
             features = [ "std" ];
           }
@@ -7492,6 +9708,17 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" ];
       };
+      "unicode-ident" = rec {
+        crateName = "unicode-ident";
+        version = "1.0.27";
+        edition = "2021";
+        sha256 = "0qv54hz0sxmspjyr162z3435pnyylzjjhx3j4k1s359pqgb59ix2";
+        libName = "unicode_ident";
+        authors = [
+          "David Tolnay <dtolnay@gmail.com>"
+        ];
+
+      };
       "unicode-normalization" = rec {
         crateName = "unicode-normalization";
         version = "0.1.17";
@@ -7565,9 +9792,9 @@ rec {
       };
       "url" = rec {
         crateName = "url";
-        version = "2.2.1";
+        version = "2.3.0";
         edition = "2018";
-        sha256 = "12qzdzgcvmc5l1fk4psldiipx80423mikb6g4a9qwb322d0rdkcw";
+        sha256 = "1lq8yldj33f8il1p8j27g2k29605aznqq1dmbjr2az119xd1kzi2";
         authors = [
           "The rust-url developers"
         ];
@@ -7580,16 +9807,14 @@ rec {
             name = "idna";
             packageId = "idna";
           }
-          {
-            name = "matches";
-            packageId = "matches";
-          }
           {
             name = "percent-encoding";
             packageId = "percent-encoding";
           }
         ];
-
+        features = {
+        };
+        resolvedDefaultFeatures = [ "default" ];
       };
       "users" = rec {
         crateName = "users";
@@ -7627,7 +9852,7 @@ rec {
         ];
 
       };
-      "uuid" = rec {
+      "uuid 0.8.2" = rec {
         crateName = "uuid";
         version = "0.8.2";
         edition = "2018";
@@ -7662,6 +9887,60 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "getrandom" "serde" "std" "v4" ];
       };
+      "uuid 1.28.0" = rec {
+        crateName = "uuid";
+        version = "1.28.0";
+        edition = "2021";
+        sha256 = "01mnlhz09hwdjaxwzjd6bql3fjb5zl9n8dzaxn7dvdxyhiiiihbw";
+        authors = [
+          "Ashley Mannix<ashleymannix@live.com.au>"
+          "Dylan DPC<dylan.dpc@gmail.com>"
+          "Hunar Roop Kahlon<hunar.roop@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "js-sys";
+            packageId = "js-sys";
+            optional = true;
+            usesDefaultFeatures = false;
+            target = { target, features }: ((target."arch" == "wasm32") && ((target."os" == "unknown") || (target."os" == "none")) && (builtins.elem "atomics" targetFeatures));
+          }
+          {
+            name = "wasm-bindgen";
+            packageId = "wasm-bindgen";
+            optional = true;
+            usesDefaultFeatures = false;
+            target = { target, features }: ((target."arch" == "wasm32") && ((target."os" == "unknown") || (target."os" == "none")));
+          }
+        ];
+        devDependencies = [
+          {
+            name = "wasm-bindgen";
+            packageId = "wasm-bindgen";
+            target = {target, features}: ((target."arch" == "wasm32") && ((target."os" == "unknown") || (target."os" == "none")));
+          }
+        ];
+        features = {
+          "borsh" = [ "dep:borsh" "dep:borsh-derive" ];
+          "default" = [ "std" ];
+          "fast-rng" = [ "rng" "dep:rand" ];
+          "js" = [ "dep:wasm-bindgen" "dep:js-sys" ];
+          "md5" = [ "dep:md-5" ];
+          "rng" = [ "dep:getrandom" ];
+          "rng-getrandom" = [ "rng" "dep:getrandom" "uuid-rng-internal-lib" "uuid-rng-internal-lib/getrandom" ];
+          "rng-rand" = [ "rng" "dep:rand" "uuid-rng-internal-lib" "uuid-rng-internal-lib/rand" ];
+          "serde" = [ "dep:serde_core" ];
+          "sha1" = [ "dep:sha1_smol" ];
+          "std" = [ "wasm-bindgen?/std" "js-sys?/std" ];
+          "v1" = [ "atomic" ];
+          "v3" = [ "md5" ];
+          "v4" = [ "rng" ];
+          "v5" = [ "sha1" ];
+          "v6" = [ "atomic" ];
+          "v7" = [ "rng" ];
+        };
+        resolvedDefaultFeatures = [ "default" "std" ];
+      };
       "vcpkg" = rec {
         crateName = "vcpkg";
         version = "0.2.11";
@@ -7674,9 +9953,9 @@ rec {
       };
       "version_check" = rec {
         crateName = "version_check";
-        version = "0.9.3";
+        version = "0.9.5";
         edition = "2015";
-        sha256 = "1zmkcgj2m0pq0l4wnhrp1wl1lygf7x2h5p7pvjwc4719lnlxrv2z";
+        sha256 = "0nhhi4i5x89gm911azqbn7avs9mdacw2i3vcz3cnmz3mv4rqz4hb";
         authors = [
           "Sergio Benitez <sb@sergio.bz>"
         ];
@@ -7730,11 +10009,32 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "std" ];
       };
+      "wasip2" = rec {
+        crateName = "wasip2";
+        version = "1.0.4+wasi-0.2.12";
+        edition = "2021";
+        sha256 = "11wl7lqwq4pbmlmzr6n7bwz0hzy1z6sxc4554bkmrr86w4vznzmn";
+        authors = [
+        ];
+        dependencies = [
+          {
+            name = "wit-bindgen";
+            packageId = "wit-bindgen";
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "bitflags" = [ "wit-bindgen/bitflags" ];
+          "default" = [ "std" "bitflags" ];
+          "rustc-dep-of-std" = [ "core" "alloc" "wit-bindgen/rustc-dep-of-std" ];
+        };
+      };
       "wasm-bindgen" = rec {
         crateName = "wasm-bindgen";
-        version = "0.2.73";
-        edition = "2018";
-        sha256 = "1n93yq72c7bd2464gg48405yzgvwakwc0cqzz647864xcm4ha943";
+        version = "0.2.118";
+        edition = "2021";
+        sha256 = "129s5r14fx4v4xrzpx2c6l860nkxpl48j50y7kl6j16bpah3iy8b";
+        libName = "wasm_bindgen";
         authors = [
           "The wasm-bindgen Developers"
         ];
@@ -7743,6 +10043,11 @@ rec {
             name = "cfg-if";
             packageId = "cfg-if 1.0.0";
           }
+          {
+            name = "once_cell";
+            packageId = "once_cell";
+            usesDefaultFeatures = false;
+          }
           {
             name = "serde";
             packageId = "serde";
@@ -7757,60 +10062,31 @@ rec {
             name = "wasm-bindgen-macro";
             packageId = "wasm-bindgen-macro";
           }
-        ];
-        features = {
-          "default" = [ "spans" "std" ];
-          "enable-interning" = [ "std" ];
-          "serde-serialize" = [ "serde" "serde_json" "std" ];
-          "spans" = [ "wasm-bindgen-macro/spans" ];
-          "strict-macro" = [ "wasm-bindgen-macro/strict-macro" ];
-          "xxx_debug_only_print_generated_code" = [ "wasm-bindgen-macro/xxx_debug_only_print_generated_code" ];
-        };
-        resolvedDefaultFeatures = [ "default" "serde" "serde-serialize" "serde_json" "spans" "std" ];
-      };
-      "wasm-bindgen-backend" = rec {
-        crateName = "wasm-bindgen-backend";
-        version = "0.2.73";
-        edition = "2018";
-        sha256 = "1bkxxkmxfy182aav9qqkcq7mz48yxgmx61kdr8ak55fa24j64w5f";
-        authors = [
-          "The wasm-bindgen Developers"
-        ];
-        dependencies = [
-          {
-            name = "bumpalo";
-            packageId = "bumpalo";
-          }
-          {
-            name = "lazy_static";
-            packageId = "lazy_static";
-          }
-          {
-            name = "log";
-            packageId = "log";
-          }
-          {
-            name = "proc-macro2";
-            packageId = "proc-macro2";
-          }
           {
-            name = "quote";
-            packageId = "quote";
+            name = "wasm-bindgen-shared";
+            packageId = "wasm-bindgen-shared";
           }
+        ];
+        buildDependencies = [
           {
-            name = "syn";
-            packageId = "syn";
-            features = [ "full" ];
+            name = "rustversion";
+            packageId = "rustversion";
+            rename = "rustversion-compat";
           }
+        ];
+        devDependencies = [
           {
-            name = "wasm-bindgen-shared";
-            packageId = "wasm-bindgen-shared";
+            name = "once_cell";
+            packageId = "once_cell";
           }
         ];
         features = {
-          "extra-traits" = [ "syn/extra-traits" ];
+          "default" = [ "std" ];
+          "enable-interning" = [ "std" ];
+          "serde-serialize" = [ "serde" "serde_json" "std" ];
+          "strict-macro" = [ "wasm-bindgen-macro/strict-macro" ];
         };
-        resolvedDefaultFeatures = [ "spans" ];
+        resolvedDefaultFeatures = [ "default" "serde" "serde-serialize" "serde_json" "std" ];
       };
       "wasm-bindgen-futures" = rec {
         crateName = "wasm-bindgen-futures";
@@ -7846,9 +10122,10 @@ rec {
       };
       "wasm-bindgen-macro" = rec {
         crateName = "wasm-bindgen-macro";
-        version = "0.2.73";
-        edition = "2018";
-        sha256 = "0vqgbnbnh6zs339v6g9w3j9idi8m3s1dws8rnkypn5rz8j8lswry";
+        version = "0.2.118";
+        edition = "2021";
+        sha256 = "1v98r8vs17cj8918qsg0xx4nlg4nxk1g0jd4nwnyrh1687w29zzf";
+        libName = "wasm_bindgen_macro";
         procMacro = true;
         authors = [
           "The wasm-bindgen Developers"
@@ -7864,20 +10141,23 @@ rec {
           }
         ];
         features = {
-          "spans" = [ "wasm-bindgen-macro-support/spans" ];
           "strict-macro" = [ "wasm-bindgen-macro-support/strict-macro" ];
         };
-        resolvedDefaultFeatures = [ "spans" ];
       };
       "wasm-bindgen-macro-support" = rec {
         crateName = "wasm-bindgen-macro-support";
-        version = "0.2.73";
-        edition = "2018";
-        sha256 = "0b0vcb8pvg03cmq9sg5b94a0p05bp1rc6m6dvc7qp9n813zkjdym";
+        version = "0.2.118";
+        edition = "2021";
+        sha256 = "0169jr0q469hfx5zqxfyywf2h2f4aj17vn4zly02nfwqmxghc24x";
+        libName = "wasm_bindgen_macro_support";
         authors = [
           "The wasm-bindgen Developers"
         ];
         dependencies = [
+          {
+            name = "bumpalo";
+            packageId = "bumpalo";
+          }
           {
             name = "proc-macro2";
             packageId = "proc-macro2";
@@ -7888,12 +10168,8 @@ rec {
           }
           {
             name = "syn";
-            packageId = "syn";
-            features = [ "visit" "full" ];
-          }
-          {
-            name = "wasm-bindgen-backend";
-            packageId = "wasm-bindgen-backend";
+            packageId = "syn 2.0.119";
+            features = [ "visit" "visit-mut" "full" "extra-traits" ];
           }
           {
             name = "wasm-bindgen-shared";
@@ -7902,18 +10178,23 @@ rec {
         ];
         features = {
           "extra-traits" = [ "syn/extra-traits" ];
-          "spans" = [ "wasm-bindgen-backend/spans" ];
         };
-        resolvedDefaultFeatures = [ "spans" ];
       };
       "wasm-bindgen-shared" = rec {
         crateName = "wasm-bindgen-shared";
-        version = "0.2.73";
-        edition = "2018";
-        sha256 = "12a4kq5wvy3znkqlqn6ib25ips1k9apxjpknpca3s8xacsp479fr";
+        version = "0.2.118";
+        edition = "2021";
+        sha256 = "0ag1vvdzi4334jlzilsy14y3nyzwddf1ndn62fyhf6bg62g4vl2z";
+        libName = "wasm_bindgen_shared";
         authors = [
           "The wasm-bindgen Developers"
         ];
+        dependencies = [
+          {
+            name = "unicode-ident";
+            packageId = "unicode-ident";
+          }
+        ];
 
       };
       "web-sys" = rec {
@@ -8375,12 +10656,30 @@ rec {
           }
           {
             name = "uuid";
-            packageId = "uuid";
+            packageId = "uuid 0.8.2";
             features = [ "v4" "serde" ];
           }
         ];
 
       };
+      "which" = rec {
+        crateName = "which";
+        version = "3.1.1";
+        edition = "2015";
+        sha256 = "094pw9pi48szshn9ln69z2kg7syq1jp80h5ps1qncbsaw4d0f4fh";
+        authors = [
+          "Harry Fei <tiziyuanfang@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "libc";
+            packageId = "libc";
+          }
+        ];
+        features = {
+          "default" = [ "failure" ];
+        };
+      };
       "widestring" = rec {
         crateName = "widestring";
         version = "0.4.3";
@@ -8478,6 +10777,16 @@ rec {
           "Peter Atashian <retep998@gmail.com>"
         ];
 
+      };
+      "windows-link" = rec {
+        crateName = "windows-link";
+        version = "0.2.1";
+        edition = "2021";
+        sha256 = "1rag186yfr3xx7piv5rg8b6im2dwcf8zldiflvb22xbzwli5507h";
+        libName = "windows_link";
+        authors = [
+        ];
+
       };
       "winreg 0.6.2" = rec {
         crateName = "winreg";
@@ -8519,6 +10828,25 @@ rec {
           "transactions" = [ "winapi/ktmw32" ];
         };
       };
+      "wit-bindgen" = rec {
+        crateName = "wit-bindgen";
+        version = "0.57.1";
+        edition = "2024";
+        sha256 = "0vjk2jb593ri9k1aq4iqs2si9mrw5q46wxnn78im7hm7hx799gqy";
+        libName = "wit_bindgen";
+        authors = [
+          "Alex Crichton <alex@alexcrichton.com>"
+        ];
+        features = {
+          "async-spawn" = [ "async" "dep:futures" "std" ];
+          "default" = [ "macros" "realloc" "async" "std" "bitflags" "macro-string" ];
+          "futures-stream" = [ "async" "dep:futures" ];
+          "inter-task-wakeup" = [ "async" ];
+          "macro-string" = [ "wit-bindgen-rust-macro?/macro-string" ];
+          "macros" = [ "dep:wit-bindgen-rust-macro" ];
+          "rustc-dep-of-std" = [ "dep:core" "dep:alloc" ];
+        };
+      };
       "ws2_32-sys" = rec {
         crateName = "ws2_32-sys";
         version = "0.2.1";
@@ -8556,6 +10884,22 @@ rec {
         };
         resolvedDefaultFeatures = [ "alloc" ];
       };
+      "yaml-rust" = rec {
+        crateName = "yaml-rust";
+        version = "0.4.5";
+        edition = "2018";
+        sha256 = "118wbqrr4n6wgk5rjjnlrdlahawlxc1bdsx146mwk8f79in97han";
+        authors = [
+          "Yuheng Chen <yuhengchen@sensetime.com>"
+        ];
+        dependencies = [
+          {
+            name = "linked-hash-map";
+            packageId = "linked-hash-map";
+          }
+        ];
+
+      };
       "yscloud-config-model" = rec {
         crateName = "yscloud-config-model";
         version = "0.1.0";
@@ -8565,6 +10909,16 @@ rec {
           "Stacey Ell <stacey.ell@gmail.com>"
         ];
         dependencies = [
+          {
+            name = "ksuid";
+            packageId = "ksuid";
+          }
+          {
+            name = "schemars";
+            packageId = "schemars";
+            optional = true;
+            features = [ "uuid" ];
+          }
           {
             name = "semver";
             packageId = "semver 0.11.0";
@@ -8581,11 +10935,14 @@ rec {
           }
           {
             name = "uuid";
-            packageId = "uuid";
+            packageId = "uuid 0.8.2";
             features = [ "v4" "serde" ];
           }
         ];
-
+        features = {
+          "schema" = [ "schemars" ];
+        };
+        resolvedDefaultFeatures = [ "schema" "schemars" ];
       };
       "yscloud-linker" = rec {
         crateName = "yscloud-linker";
@@ -8616,6 +10973,11 @@ rec {
             name = "futures";
             packageId = "futures";
           }
+          {
+            name = "jsonschema";
+            packageId = "jsonschema";
+            usesDefaultFeatures = false;
+          }
           {
             name = "libc";
             packageId = "libc";
@@ -8628,10 +10990,18 @@ rec {
             name = "nix";
             packageId = "nix";
           }
+          {
+            name = "openssl";
+            packageId = "openssl";
+          }
           {
             name = "owned_fd";
             packageId = "owned_fd";
           }
+          {
+            name = "prost";
+            packageId = "prost";
+          }
           {
             name = "rand";
             packageId = "rand 0.8.3";
@@ -8659,6 +11029,10 @@ rec {
             name = "serde_json";
             packageId = "serde_json";
           }
+          {
+            name = "serde_yaml";
+            packageId = "serde_yaml";
+          }
           {
             name = "sha2";
             packageId = "sha2";
@@ -8684,6 +11058,14 @@ rec {
             packageId = "tokio 0.2.20";
             features = [ "full" ];
           }
+          {
+            name = "toml";
+            packageId = "toml";
+          }
+          {
+            name = "tonic";
+            packageId = "tonic";
+          }
           {
             name = "tracing";
             packageId = "tracing";
@@ -8698,12 +11080,167 @@ rec {
           }
           {
             name = "uuid";
-            packageId = "uuid";
+            packageId = "uuid 0.8.2";
             features = [ "v4" "serde" ];
           }
           {
             name = "yscloud-config-model";
             packageId = "yscloud-config-model";
+            features = [ "schema" ];
+          }
+        ];
+        buildDependencies = [
+          {
+            name = "tonic-build";
+            packageId = "tonic-build";
+          }
+        ];
+
+      };
+      "yscloud-runtime" = rec {
+        crateName = "yscloud-runtime";
+        version = "0.1.0";
+        edition = "2018";
+        src = lib.cleanSourceWith { filter = sourceFilter;  src = ./yscloud-runtime; };
+        authors = [
+          "Stacey Ell <stacey.ell@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "nix";
+            packageId = "nix";
+          }
+          {
+            name = "serde";
+            packageId = "serde";
+          }
+          {
+            name = "serde_json";
+            packageId = "serde_json";
+          }
+          {
+            name = "uuid";
+            packageId = "uuid 0.8.2";
+          }
+          {
+            name = "yscloud-config-model";
+            packageId = "yscloud-config-model";
+          }
+        ];
+        devDependencies = [
+          {
+            name = "tempfile";
+            packageId = "tempfile";
+          }
+        ];
+        features = {
+          "async" = [ "tokio" ];
+        };
+        resolvedDefaultFeatures = [ "default" ];
+      };
+      "yscloud-testkit" = rec {
+        crateName = "yscloud-testkit";
+        version = "0.1.0";
+        edition = "2018";
+        src = lib.cleanSourceWith { filter = sourceFilter;  src = ./yscloud-testkit; };
+        authors = [
+          "Stacey Ell <stacey.ell@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "nix";
+            packageId = "nix";
+          }
+          {
+            name = "owned_fd";
+            packageId = "owned_fd";
+          }
+          {
+            name = "semver";
+            packageId = "semver 0.11.0";
+          }
+          {
+            name = "serde_json";
+            packageId = "serde_json";
+          }
+          {
+            name = "tempfile";
+            packageId = "tempfile";
+          }
+          {
+            name = "uuid";
+            packageId = "uuid 0.8.2";
+            features = [ "v4" ];
+          }
+          {
+            name = "yscloud-config-model";
+            packageId = "yscloud-config-model";
+          }
+        ];
+
+      };
+      "zerocopy" = rec {
+        crateName = "zerocopy";
+        version = "0.8.63";
+        edition = "2021";
+        sha256 = "1n1fm40mq4adw83hvn56j6rhnph0lldccx11jq01l6863f7izzp5";
+        authors = [
+        ];
+        dependencies = [
+          {
+            name = "zerocopy-derive";
+            packageId = "zerocopy-derive";
+            optional = true;
+          }
+          {
+            name = "zerocopy-derive";
+            packageId = "zerocopy-derive";
+            target = { target, features }: ();
+          }
+        ];
+        devDependencies = [
+          {
+            name = "zerocopy-derive";
+            packageId = "zerocopy-derive";
+          }
+        ];
+        features = {
+          "__internal_use_only_features_that_work_on_stable" = [ "alloc" "derive" "simd" "std" ];
+          "derive" = [ "zerocopy-derive" ];
+          "simd-nightly" = [ "simd" ];
+          "std" = [ "alloc" ];
+        };
+        resolvedDefaultFeatures = [ "simd" ];
+      };
+      "zerocopy-derive" = rec {
+        crateName = "zerocopy-derive";
+        version = "0.8.63";
+        edition = "2021";
+        sha256 = "0zqc4g3yf378dkbsr68h1jjx3n0d4k4zvnxf83bv8gr9hfnd6fl6";
+        libName = "zerocopy_derive";
+        procMacro = true;
+        authors = [
+        ];
+        dependencies = [
+          {
+            name = "proc-macro2";
+            packageId = "proc-macro2";
+          }
+          {
+            name = "quote";
+            packageId = "quote";
+          }
+          {
+            name = "syn";
+            packageId = "syn 2.0.119";
+            features = [ "full" ];
+          }
+        ];
+        devDependencies = [
+          {
+            name = "syn";
+            packageId = "syn 2.0.119";
+            features = [ "visit" ];
           }
         ];
 
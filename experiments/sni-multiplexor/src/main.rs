@@ -1,8 +1,5 @@
 use std::collections::BTreeMap;
-use std::fs::File;
-use std::net::TcpListener as StdTcpListener;
-use std::os::unix::io::{FromRawFd, RawFd};
-use std::os::unix::net::UnixListener as StdUnixListener;
+use std::os::unix::io::RawFd;
 use std::sync::Arc;
 use std::pin::Pin;
 
@@ -13,7 +10,8 @@ use serde::{Deserialize, Serialize};
 use tokio::net::TcpListener;
 use tokio::net::UnixListener;
 use tokio::sync::{mpsc, Mutex};
-use yscloud_config_model::{AppConfiguration, FileDescriptorRemote, SocketFlag};
+use yscloud_config_model::SocketFlag;
+use yscloud_runtime::{ConfigUpdates, Listener, Runtime};
 use tracing::{event, Level};
 use tracing_subscriber::filter::LevelFilter as TracingLevelFilter;
 use tracing_subscriber::FmtSubscriber;
@@ -45,6 +43,9 @@ use self::sni2::Dialer;
 const CARGO_PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
 const CARGO_PKG_NAME: &str = env!("CARGO_PKG_NAME");
 
+const SNI_DIRECTOR_SERVICE: &str = "org.yshi.sni_multiplexor.https";
+const MANAGEMENT_SERVICE: &str = "org.yshi.sni_multiplexor.v1.SniMultiplexor";
+
 #[tokio::main]
 async fn main() -> Result<(), failure::Error> {
     let mut my_subscriber_builder = FmtSubscriber::builder();
@@ -79,16 +80,15 @@ async fn main() -> Result<(), failure::Error> {
         .value_of("config-fd")
         .expect("only runnable as yscloud program for now");
 
-    let config_file = unsafe { File::from_raw_fd(config_fd.parse::<RawFd>().unwrap()) };
-    let config: AppConfiguration = serde_json::from_reader(config_file).unwrap();
+    let config_fd = config_fd.parse::<RawFd>()?;
+    let mut runtime = unsafe { Runtime::from_config_fd(config_fd)? };
 
     #[derive(Deserialize, Serialize)]
     struct DebugLevel {
         verbosity: Option<u64>,
     }
 
-    let extras_str = serde_json::to_string(&config.extras).unwrap();
-    let debug_level: DebugLevel = serde_json::from_str(&extras_str).unwrap();
+    let debug_level: DebugLevel = runtime.extras()?;
 
     let verbosity = debug_level.verbosity.unwrap_or_else(|| {
         matches.occurrences_of("v")
@@ -110,40 +110,28 @@ async fn main() -> Result<(), failure::Error> {
         print_test_logging();
     }
 
-    let mut sni_director_sock = None;
-    let mut sni_director_sock_haproxy_proxy_header_version = None;
-    let mut management_sock = None;
-    for file in &config.files {
-        if file.service_name == "org.yshi.sni_multiplexor.https" {
-            // 128 taken from rust stdlib
-            ::nix::sys::socket::listen(file.file_num, 128).unwrap();
-
-            sni_director_sock = Some(unsafe { StdTcpListener::from_raw_fd(file.file_num) });
-            if let FileDescriptorRemote::Socket(ref si) = file.remote {
-                for flag in &si.flags {
-                    if *flag == SocketFlag::BehindHaproxy {
-                        sni_director_sock_haproxy_proxy_header_version =
-                            Some(HaproxyProxyHeaderVersion::Version1);
-                    }
-                }
-            }
+    let trace_id = runtime.trace_id();
 
-            break;
-        }
+    let mut sni_director_sock_haproxy_proxy_header_version = None;
+    if runtime
+        .socket_flags(SNI_DIRECTOR_SERVICE)?
+        .contains(&SocketFlag::BehindHaproxy)
+    {
+        sni_director_sock_haproxy_proxy_header_version = Some(HaproxyProxyHeaderVersion::Version1);
     }
-    for file in &config.files {
-        if file.service_name == "org.yshi.sni_multiplexor.v1.SniMultiplexor" {
-            // 128 taken from rust stdlib
-            ::nix::sys::socket::listen(file.file_num, 128).unwrap();
 
-            management_sock = Some(unsafe { StdUnixListener::from_raw_fd(file.file_num) });
-            break;
+    let sni_director_sock = match runtime.listener(SNI_DIRECTOR_SERVICE)? {
+        Listener::Tcp(l) => l,
+        Listener::Unix(..) => {
+            failure::bail!("{} must be bound to a TCP address", SNI_DIRECTOR_SERVICE)
         }
-    }
-    let sni_director_sock = sni_director_sock.expect("sni_director_sock");
-    let management_sock = management_sock.expect("management_sock");
+    };
+    let management_sock = match runtime.listener(MANAGEMENT_SERVICE)? {
+        Listener::Unix(l) => l,
+        Listener::Tcp(..) => failure::bail!("{} must be a unix socket", MANAGEMENT_SERVICE),
+    };
 
-    let resolver_init: ConfigResolverInit = serde_json::from_str(&extras_str).unwrap();
+    let resolver_init: ConfigResolverInit = runtime.extras()?;
 
     let mut backends = BTreeMap::new();
     for (k, v) in resolver_init.hostnames.into_iter() {
@@ -153,6 +141,18 @@ async fn main() -> Result<(), failure::Error> {
     let resolver = Arc::new(Mutex::new(BackendManager {
         backends: Arc::new(backends),
     }));
+
+    match runtime.config_updates() {
+        Ok(updates) => {
+            let resolver = Arc::clone(&resolver);
+            std::thread::spawn(move || apply_config_updates(updates, resolver));
+        }
+        Err(yscloud_runtime::Error::NoControlFd) => {
+            event!(Level::INFO, "live configuration is not enabled");
+        }
+        Err(err) => return Err(err.into()),
+    }
+
     let mgmt_resolver = Arc::clone(&resolver);
     let data_resolver = resolver;
 
@@ -260,6 +260,7 @@ async fn main() -> Result<(), failure::Error> {
                     socket,
                     ClientCtx {
                         proxy_header_version: sni_director_sock_haproxy_proxy_header_version,
+                        trace_id,
                     },
                 )
                 .await;
@@ -300,3 +301,47 @@ fn print_test_logging() {
     event!(Level::WARN, "logger initialized - warn check");
     event!(Level::ERROR, "logger initialized - error check");
 }
+
+/// Replaces the hostnames with those from each configuration update,
+/// including any backends added over the management socket since.  A changed
+/// `upstream_dns` still needs a restart.
+fn apply_config_updates(mut updates: ConfigUpdates, resolver: Arc<Mutex<BackendManager>>) {
+    loop {
+        let config = match updates.recv() {
+            Ok(Some(config)) => config,
+            Ok(None) => {
+                event!(Level::INFO, "control socket closed, no more configuration updates");
+                return;
+            }
+            Err(yscloud_runtime::Error::Io(err)) => {
+                event!(Level::WARN, "receiving configuration updates failed: {}", err);
+                return;
+            }
+            Err(err) => {
+                event!(Level::WARN, "ignoring configuration update: {}", err);
+                continue;
+            }
+        };
+
+        let resolver_init: ConfigResolverInit = match serde_json::from_value(config.extras) {
+            Ok(resolver_init) => resolver_init,
+            Err(err) => {
+                event!(Level::WARN, "ignoring configuration update: {}", err);
+                continue;
+            }
+        };
+
+        let mut backends = BTreeMap::new();
+        for (k, v) in resolver_init.hostnames.into_iter() {
+            backends.insert(k, v.into());
+        }
+        let hostname_count = backends.len();
+
+        futures::executor::block_on(resolver.lock()).backends = Arc::new(backends);
+        event!(
+            Level::INFO,
+            "configuration updated, serving {} hostnames",
+            hostname_count
+        );
+    }
+}
@@ -3,6 +3,7 @@ use std::net::{SocketAddr, SocketAddrV4, SocketAddrV6};
 use std::path::PathBuf;
 
 use failure::{Error, Fail};
+use ksuid::Ksuid;
 use serde::{Deserialize, Serialize};
 
 pub mod config;
@@ -29,6 +30,8 @@ pub enum HaproxyProxyHeaderVersion {
 #[derive(Debug)]
 pub struct ClientCtx {
     pub proxy_header_version: Option<HaproxyProxyHeaderVersion>,
+    // the deployment's, logged with each session.
+    pub trace_id: Option<Ksuid>,
 }
 
 pub struct BackendCtx {
@@ -20,7 +20,7 @@ use ksuid::Ksuid;
 use socket_traits::{AsyncWriteClose, DynamicSocket, Socket};
 use tls::decode_client_hello;
 use tokio::sync::Mutex;
-use tracing::{event, Level};
+use tracing::{event, field, span, Instrument, Level};
 use trust_dns_resolver::TokioAsyncResolver;
 use failure::format_err;
 
@@ -34,6 +34,9 @@ use crate::error::tls::{ALERT_INTERNAL_ERROR, ALERT_UNRECOGNIZED_NAME};
 
 // --
 
+// PP2_TYPE_UNIQUE_ID, which carries the session id to backends.
+const PP2_TYPE_UNIQUE_ID: u8 = 0x05;
+
 #[derive(Debug)]
 pub enum ReadTimeoutPhase {
     HaproxyHeader,
@@ -356,11 +359,21 @@ pub async fn sni_connect_and_copy(
     ));
     drop(sessman_locked);
 
+    let span = span!(
+        Level::INFO,
+        "session",
+        session_id = %session_id.fmt_base62(),
+        trace_id = field::Empty,
+    );
+    if let Some(trace_id) = client_ctx.trace_id {
+        span.record("trace_id", &field::display(trace_id.fmt_base62()));
+    }
+
     let res = sni_connect_and_copy_helper(
         dialer,
         sessman.clone(), session_id, backend_man,
         client_addr, client, client_ctx, canceler,
-    ).await;
+    ).instrument(span).await;
 
     if let Err(ref err) = res {
         event!(Level::WARN, "error for {}: {}", session_id.to_base62(), err);
@@ -460,9 +473,19 @@ pub async fn sni_connect_and_copy_helper(
 
         if client_ctx.proxy_header_version.is_some() && bset.haproxy_header_allow_passthrough {
             let header = haproxy_passthrough_header.as_ref().unwrap();
-            write_haproxy_header_from_parsed(&mut backend_write_buf, haproxy_v, &header.parsed);
+            write_haproxy_header_from_parsed(
+                &mut backend_write_buf,
+                haproxy_v,
+                &header.parsed,
+                &session_id,
+            );
         } else {
-            write_haproxy_header_from_socketaddr(&mut backend_write_buf, haproxy_v, &client_addr);
+            write_haproxy_header_from_socketaddr(
+                &mut backend_write_buf,
+                haproxy_v,
+                &client_addr,
+                &session_id,
+            );
         }
 
         backend_write_buf.extend_from_slice(&tls_handshake[..])
@@ -572,14 +595,22 @@ fn write_haproxy_header_from_parsed(
     dst: &mut BytesMut,
     v: HaproxyProxyHeaderVersion,
     h: &ppp::model::Header,
+    session_id: &Ksuid,
 ) {
-    use ppp::model::Version;
+    use ppp::model::{Header, Version};
 
-    let mut new_h = h.clone();
-    new_h.version = match v {
+    let version = match v {
         HaproxyProxyHeaderVersion::Version1 => Version::One,
         HaproxyProxyHeaderVersion::Version2 => Version::Two,
     };
+    // our session id replaces whatever unique id an earlier proxy sent.
+    let mut tlvs: Vec<_> = h
+        .tlvs()
+        .filter(|tlv| tlv.value_type() != PP2_TYPE_UNIQUE_ID)
+        .cloned()
+        .collect();
+    tlvs.extend(session_id_tlv(v, session_id));
+    let new_h = Header::new(version, h.command, h.protocol, tlvs, h.addresses.clone());
 
     match v {
         HaproxyProxyHeaderVersion::Version1 => {
@@ -593,10 +624,22 @@ fn write_haproxy_header_from_parsed(
     }
 }
 
+// version 1 headers have no room for it.
+fn session_id_tlv(v: HaproxyProxyHeaderVersion, session_id: &Ksuid) -> Option<ppp::model::Tlv> {
+    match v {
+        HaproxyProxyHeaderVersion::Version1 => None,
+        HaproxyProxyHeaderVersion::Version2 => Some(ppp::model::Tlv::new(
+            PP2_TYPE_UNIQUE_ID,
+            session_id.to_base62().into_bytes(),
+        )),
+    }
+}
+
 fn write_haproxy_header_from_socketaddr(
     dst: &mut BytesMut,
     v: HaproxyProxyHeaderVersion,
     ap: &SocketAddrPair,
+    session_id: &Ksuid,
 ) {
     use ppp::model::{Command, Header, Protocol, Version};
 
@@ -609,7 +652,7 @@ fn write_haproxy_header_from_socketaddr(
         version,
         Command::Proxy,
         Protocol::Stream,
-        Vec::new(),
+        session_id_tlv(v, session_id).into_iter().collect(),
         ap.clone().into(),
     );
 
@@ -624,3 +667,37 @@ fn write_haproxy_header_from_socketaddr(
         }
     }
 }
+
+#[test]
+fn haproxy_v2_header_carries_session_id() {
+    let pair = SocketAddrPair::from_pair(
+        "127.0.0.1:443".parse().unwrap(),
+        "192.0.2.1:50000".parse().unwrap(),
+    )
+    .unwrap();
+    let session_id = Ksuid::generate();
+
+    let mut buf = BytesMut::new();
+    write_haproxy_header_from_socketaddr(
+        &mut buf,
+        HaproxyProxyHeaderVersion::Version2,
+        &pair,
+        &session_id,
+    );
+    let (_, header) = parse_v2_header(&buf[..]).unwrap();
+    let unique_id = header
+        .tlvs()
+        .find(|tlv| tlv.value_type() == PP2_TYPE_UNIQUE_ID)
+        .unwrap();
+    assert_eq!(unique_id.value(), session_id.to_base62().as_bytes());
+
+    let mut passed = BytesMut::new();
+    write_haproxy_header_from_parsed(
+        &mut passed,
+        HaproxyProxyHeaderVersion::Version2,
+        &header,
+        &Ksuid::generate(),
+    );
+    let (_, header) = parse_v2_header(&passed[..]).unwrap();
+    assert_eq!(header.tlvs().count(), 1);
+}
@@ -158,3 +158,16 @@ pub fn socketpair_raw() -> io::Result<(OwnedFd, OwnedFd)> {
 
     unsafe { Ok((OwnedFd::from_raw_fd(left), OwnedFd::from_raw_fd(right))) }
 }
+
+/// Like `socketpair_raw`, but message-oriented.
+pub fn seqpacket_pair_raw() -> io::Result<(OwnedFd, OwnedFd)> {
+    let (left, right) = socketpair(
+        AddressFamily::Unix,
+        SockType::SeqPacket,
+        None,
+        SockFlag::SOCK_CLOEXEC,
+    )
+    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+    unsafe { Ok((OwnedFd::from_raw_fd(left), OwnedFd::from_raw_fd(right))) }
+}
@@ -40,6 +40,11 @@ impl OwnedFd {
     pub fn into_file(self) -> File {
         unsafe { FromRawFd::from_raw_fd(self.into_raw_fd()) }
     }
+
+    pub fn try_clone(&self) -> Result<OwnedFd, nix::Error> {
+        let raw_fd = nix::unistd::dup(self.raw_fd)?;
+        Ok(OwnedFd { raw_fd })
+    }
 }
 
 impl FromRawFd for OwnedFd {
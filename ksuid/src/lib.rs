@@ -273,7 +273,7 @@ impl<'de> serde::Deserialize<'de> for Ksuid {
     where
         D: serde::Deserializer<'de>,
     {
-        deserializer.deserialize_i32(KsuidVisitor)
+        deserializer.deserialize_str(KsuidVisitor)
     }
 }
 
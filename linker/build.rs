@@ -0,0 +1,9 @@
+fn main() {
+    tonic_build::configure()
+        .build_client(false)
+        .compile(
+            &["../pb/certificate_issuer/certificate_issuer.proto"],
+            &["../pb/certificate_issuer"],
+        )
+        .unwrap();
+}
@@ -0,0 +1,343 @@
+//! Configuration files for third-party daemons, which take their sockets
+//! with `FdProtocol::ListenFds` but can't read an `AppConfiguration`.  The
+//! linker renders a component's `Adapter` template into its working
+//! directory before starting it.
+//!
+//! `{{name}}` is replaced with:
+//!
+//! * `deployment_name`, `package_id`, `instance_id`, `version`, `trace_id`
+//!   and `workdir`;
+//! * `fd.<service>`, the descriptor the service is passed as;
+//! * `host.<service>` and `port.<service>` for TCP sockets, or
+//!   `path.<service>` for UNIX domain sockets, as bound by the linker;
+//! * `extras.<key>`, which may be a dotted path into the extras.  Strings
+//!   are inserted as they are, anything else as JSON.
+//!
+//! Anything else between `{{` and `}}` is an error.
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Component, Path};
+
+use failure::Fail;
+use nix::sys::socket::{getsockname, SockAddr};
+use nix::unistd::{chown, Gid, Uid};
+use serde_json::Value;
+use users::{get_group_by_name, get_user_by_name};
+use yscloud_config_model::{
+    Adapter, AppConfiguration, DeployedApplicationManifest, FdProtocol, Sandbox,
+};
+
+use crate::platform::app_configuration;
+use crate::AppPreforkConfiguration;
+
+#[derive(Debug, Fail)]
+pub struct InvalidAdapter {
+    package_id: String,
+    reason: String,
+}
+
+impl fmt::Display for InvalidAdapter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid adapter for {}: {}",
+            self.package_id, self.reason
+        )
+    }
+}
+
+/// Checks that `component`'s adapter, if it has one, can be rendered.
+pub fn check(component: &DeployedApplicationManifest) -> Result<(), InvalidAdapter> {
+    let adapter = match component.adapter {
+        Some(ref adapter) => adapter,
+        None => return Ok(()),
+    };
+    let invalid = |reason: String| InvalidAdapter {
+        package_id: component.package_id.clone(),
+        reason,
+    };
+
+    if component.fd_protocol != FdProtocol::ListenFds {
+        return Err(invalid("adapters need fd_protocol listen_fds".into()));
+    }
+    // the component owns its working directory, so the linker won't go
+    // through any subdirectories it might have replaced with a symlink.
+    let mut components = Path::new(&adapter.path).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => (),
+        _ => return Err(invalid(format!("{:?} is not a file name", adapter.path))),
+    }
+    for name in placeholders(&adapter.template).map_err(invalid)? {
+        if !is_placeholder(name) {
+            return Err(invalid(format!("unknown placeholder {{{{{}}}}}", name)));
+        }
+    }
+    Ok(())
+}
+
+/// Renders `adapter` for the instance `c` into `workdir`.  The file is only
+/// readable by the component's user, since extras may hold secrets.
+pub fn write(
+    adapter: &Adapter,
+    sandbox: &Sandbox,
+    c: &AppPreforkConfiguration,
+    workdir: &Path,
+) -> io::Result<()> {
+    let data = render(&adapter.template, c, workdir)
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+    fs::create_dir_all(workdir)?;
+    let path = workdir.join(&adapter.path);
+    // nor does it follow whatever was left at `path`.
+    let _ = fs::remove_file(&path);
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&path)?;
+    file.write_all(data.as_bytes())?;
+
+    if let Sandbox::UnixUserConfinement(ref user, ref group) = *sandbox {
+        let uid = get_user_by_name(user)
+            .map(|u| Uid::from_raw(u.uid()))
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::Other, format!("unknown user {}", user))
+            })?;
+        let gid = get_group_by_name(group)
+            .map(|g| Gid::from_raw(g.gid()))
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::Other, format!("unknown group {}", group))
+            })?;
+        chown(&path, Some(uid), Some(gid))
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+    }
+    Ok(())
+}
+
+/// `template` with its placeholders filled in for the instance `c`.
+pub fn render(
+    template: &str,
+    c: &AppPreforkConfiguration,
+    workdir: &Path,
+) -> Result<String, String> {
+    let config = app_configuration(c);
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| "unterminated placeholder".to_string())?;
+        let name = rest[start + 2..start + end].trim();
+        out.push_str(&lookup(name, c, &config, workdir)?);
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn placeholders(template: &str) -> Result<Vec<&str>, String> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| "unterminated placeholder".to_string())?;
+        names.push(rest[start + 2..start + end].trim());
+        rest = &rest[start + end + 2..];
+    }
+    Ok(names)
+}
+
+/// Whether `name` is a placeholder at all.  Which services and extras exist
+/// is only known once the deployment is linked.
+fn is_placeholder(name: &str) -> bool {
+    match name {
+        "deployment_name" | "package_id" | "instance_id" | "version" | "trace_id" | "workdir" => {
+            true
+        }
+        _ => match name.find('.') {
+            Some(dot) => {
+                let (kind, key) = (&name[..dot], &name[dot + 1..]);
+                !key.is_empty() && ["fd", "host", "port", "path", "extras"].contains(&kind)
+            }
+            None => false,
+        },
+    }
+}
+
+fn lookup(
+    name: &str,
+    c: &AppPreforkConfiguration,
+    config: &AppConfiguration,
+    workdir: &Path,
+) -> Result<String, String> {
+    let unknown = || format!("unknown placeholder {{{{{}}}}}", name);
+    match name {
+        "deployment_name" => return Ok(config.deployment_name.clone()),
+        "package_id" => return Ok(config.package_id.clone()),
+        "instance_id" => return Ok(config.instance_id.to_string()),
+        "version" => return Ok(config.version.clone()),
+        "trace_id" => return Ok(c.trace_id.to_base62()),
+        "workdir" => return Ok(workdir.display().to_string()),
+        _ => (),
+    }
+
+    let dot = name.find('.').ok_or_else(unknown)?;
+    let (kind, key) = (&name[..dot], &name[dot + 1..]);
+    if kind == "extras" {
+        let mut pointer = String::new();
+        for segment in key.split('.') {
+            pointer.push('/');
+            pointer.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+        }
+        return match config.extras.pointer(&pointer) {
+            Some(Value::String(s)) => Ok(s.clone()),
+            Some(value) => Ok(value.to_string()),
+            None => Err(format!("no extras value {:?}", key)),
+        };
+    }
+
+    let index = config
+        .files
+        .iter()
+        .position(|f| f.service_name == key)
+        .ok_or_else(|| format!("{} has no service {:?}", config.package_id, key))?;
+    if kind == "fd" {
+        return Ok(config.files[index].file_num.to_string());
+    }
+
+    let addr = getsockname(c.files[index].file.as_raw_fd())
+        .map_err(|err| format!("can't get the address of {:?}: {}", key, err))?;
+    match (kind, addr) {
+        ("host", SockAddr::Inet(addr)) => Ok(addr.to_std().ip().to_string()),
+        ("port", SockAddr::Inet(addr)) => Ok(addr.port().to_string()),
+        ("path", SockAddr::Unix(ref addr)) if addr.path().is_some() => {
+            Ok(addr.path().unwrap().display().to_string())
+        }
+        ("host", _) | ("port", _) | ("path", _) => {
+            Err(format!("{:?} has no {} to fill in", key, kind))
+        }
+        _ => Err(unknown()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::os::unix::io::{FromRawFd, IntoRawFd};
+    use std::path::Path;
+
+    use owned_fd::OwnedFd;
+    use uuid::Uuid;
+    use yscloud_config_model::{
+        Adapter, DeployedApplicationManifest, FdProtocol, FileDescriptorRemote, ImageType, Ksuid,
+        Protocol, Sandbox, ServiceFileDirection, SocketInfo, SocketMode,
+    };
+
+    use super::{check, render};
+    use crate::platform::Executable;
+    use crate::{AppPreforkConfiguration, ServiceFileDescriptor};
+
+    #[test]
+    fn test_render() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let c = AppPreforkConfiguration {
+            deployment_name: "irc".into(),
+            trace_id: Ksuid::generate(),
+            package_id: "org.inspircd".into(),
+            artifact: Executable::open("/bin/sh").unwrap(),
+            instance_id: Uuid::new_v4(),
+            version: "3.0.0".into(),
+            files: vec![ServiceFileDescriptor {
+                file: unsafe { OwnedFd::from_raw_fd(listener.into_raw_fd()) },
+                direction: ServiceFileDirection::ServingListening,
+                service_name: "org.inspircd.irc".into(),
+                remote: FileDescriptorRemote::Socket(SocketInfo {
+                    mode: SocketMode::Listening,
+                    protocol: Protocol::Stream,
+                    flags: vec![],
+                }),
+                trace_id: None,
+            }],
+            extras: serde_json::json!({
+                "server": {"name": "irc.example", "max_clients": 64},
+                "paths": {"/etc/motd": "motd.txt", "~": "home"}
+            }),
+            container_mounts: vec![],
+            control: None,
+            fd_protocol: FdProtocol::ListenFds,
+            arguments: vec![],
+            environment: vec![],
+        };
+
+        let template = r#"<server name="{{extras.server.name}}" max="{{ extras.server.max_clients }}">
+<bind fd="{{fd.org.inspircd.irc}}" address="{{host.org.inspircd.irc}}:{{port.org.inspircd.irc}}">
+<pid file="{{workdir}}/inspircd.pid">"#;
+        let rendered = render(template, &c, Path::new("/srv/irc/org.inspircd")).unwrap();
+        assert_eq!(
+            rendered,
+            format!(
+                r#"<server name="irc.example" max="64">
+<bind fd="3" address="127.0.0.1:{}">
+<pid file="/srv/irc/org.inspircd/inspircd.pid">"#,
+                port
+            )
+        );
+
+        assert!(render("{{path.org.inspircd.irc}}", &c, Path::new("/")).is_err());
+        assert!(render("{{fd.org.inspircd.ssl}}", &c, Path::new("/")).is_err());
+        assert!(render("{{extras.missing}}", &c, Path::new("/")).is_err());
+        assert_eq!(
+            render(
+                "{{extras.paths./etc/motd}} {{extras.paths.~}}",
+                &c,
+                Path::new("/")
+            )
+            .unwrap(),
+            "motd.txt home"
+        );
+        assert!(render("{{fd.org.inspircd.irc", &c, Path::new("/")).is_err());
+
+        let adapter = Adapter {
+            path: "../inspircd.conf".into(),
+            template: template.into(),
+        };
+        assert!(check(&component(adapter.clone())).is_err());
+        let adapter = Adapter {
+            path: "inspircd.conf".into(),
+            ..adapter
+        };
+        check(&component(adapter.clone())).unwrap();
+        let adapter = Adapter {
+            template: "{{listen_fds}}".into(),
+            ..adapter
+        };
+        assert!(check(&component(adapter)).is_err());
+    }
+
+    fn component(adapter: Adapter) -> DeployedApplicationManifest {
+        DeployedApplicationManifest {
+            package_id: "org.inspircd".into(),
+            version: "3.0.0".parse().unwrap(),
+            provided_local_services: vec![],
+            provided_remote_services: vec!["org.inspircd.irc".into()],
+            required_local_services: vec![],
+            required_remote_services: vec![],
+            sandbox: Sandbox::Unconfined,
+            extras: serde_json::json!({}),
+            image_type: ImageType::Executable,
+            live_config: false,
+            fd_protocol: FdProtocol::ListenFds,
+            process: Default::default(),
+            activation: Default::default(),
+            adapter: Some(adapter),
+            workdir: Default::default(),
+            artifacts: Default::default(),
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! The audit log: one JSON line per privileged action the linker takes,
+//! with its outcome.  It is kept apart from tracing, which is for
+//! debugging, so reviewing what a linker did doesn't need verbose logs.
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use digest::Digest;
+use nix::unistd::{getpid, getuid};
+use serde::Serialize;
+use sha2::Sha256;
+use tracing::{event, Level};
+use yscloud_config_model::PublicServiceBinder;
+
+use crate::util::hexify;
+
+pub const AUDIT_LOG_FILE: &str = "audit.jsonl";
+
+static LOG: OnceLock<Mutex<AuditLog>> = OnceLock::new();
+
+struct AuditLog {
+    file: File,
+    command: &'static str,
+    manifest_sha256: Option<String>,
+    override_reason: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action<'a> {
+    BindPublicService {
+        package_id: &'a str,
+        service_name: &'a str,
+        binder: &'a PublicServiceBinder,
+    },
+    /// Running a component as another user, which takes effect when it is
+    /// started.
+    Confine {
+        package_id: &'a str,
+        user: &'a str,
+        group: &'a str,
+    },
+    /// Loading a component from a path instead of a signed artifact.
+    ArtifactOverride { package_id: &'a str, path: &'a str },
+    MountSquashfs {
+        image: &'a Path,
+        workdir: &'a Path,
+        persist: &'a Path,
+    },
+    /// Backing a component's working directory with a size-limited image.
+    MountWorkdirImage { image: &'a Path, workdir: &'a Path },
+    /// Removing the working directories of a deployment that is gone.
+    RemoveWorkdir { path: &'a Path },
+}
+
+#[derive(Serialize)]
+struct Entry<'a> {
+    timestamp_ms: u128,
+    pid: i32,
+    uid: u32,
+    command: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    manifest_sha256: Option<&'a str>,
+    #[serde(flatten)]
+    action: &'a Action<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+    result: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Opens the audit log at `path` for appending, for the rest of the
+/// process.  `manifest_sha256` and `override_reason` go with every action
+/// they concern.
+pub fn open(
+    path: &Path,
+    command: &'static str,
+    manifest_sha256: Option<String>,
+    override_reason: Option<String>,
+) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .mode(0o600)
+        .open(path)?;
+    let log = AuditLog {
+        file,
+        command,
+        manifest_sha256,
+        override_reason,
+    };
+    LOG.set(Mutex::new(log))
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "audit log already open"))
+}
+
+/// The hash the audit log identifies a manifest file by.
+pub fn manifest_sha256(path: &Path) -> io::Result<String> {
+    let mut data = Vec::new();
+    File::open(path)?.read_to_end(&mut data)?;
+    let mut scratch = [0; 64];
+    let hash = hexify(&mut scratch[..], &Sha256::digest(&data)).unwrap();
+    Ok(hash.to_string())
+}
+
+/// Appends `action` and how it went.  Nothing is recorded if no audit log
+/// was opened.
+pub fn record<T, E: fmt::Display>(action: Action, result: &Result<T, E>) {
+    let mut log = match LOG.get() {
+        Some(log) => log.lock().unwrap(),
+        None => return,
+    };
+
+    let reason = match action {
+        Action::ArtifactOverride { .. } => log.override_reason.as_deref(),
+        _ => None,
+    };
+    let entry = Entry {
+        timestamp_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis(),
+        pid: getpid().as_raw(),
+        uid: getuid().as_raw(),
+        command: log.command,
+        manifest_sha256: log.manifest_sha256.as_deref(),
+        action: &action,
+        reason,
+        result: if result.is_ok() { "ok" } else { "error" },
+        error: result.as_ref().err().map(|e| e.to_string()),
+    };
+
+    let mut line = serde_json::to_vec(&entry).unwrap();
+    line.push(b'\n');
+    // a single write, so lines from concurrent linkers don't interleave.
+    let written = log
+        .file
+        .write_all(&line)
+        .and_then(|()| log.file.sync_data());
+    if let Err(err) = written {
+        event!(Level::ERROR, "writing the audit log failed: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io;
+
+    use tempfile::tempdir;
+
+    use super::{manifest_sha256, open, record, Action, AUDIT_LOG_FILE};
+
+    #[test]
+    fn test_record() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(AUDIT_LOG_FILE);
+        fs::write(dir.path().join("manifest.json"), b"{}").unwrap();
+        let hash = manifest_sha256(&dir.path().join("manifest.json")).unwrap();
+        assert_eq!(
+            hash,
+            "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+        );
+
+        open(&path, "run", Some(hash), Some("hotfix".into())).unwrap();
+        let ok: Result<(), io::Error> = Ok(());
+        record(
+            Action::ArtifactOverride {
+                package_id: "org.yshi.frontend",
+                path: "/tmp/frontend",
+            },
+            &ok,
+        );
+        record(
+            Action::Confine {
+                package_id: "org.yshi.frontend",
+                user: "nobody",
+                group: "nogroup",
+            },
+            &Err::<(), _>("unknown user nobody"),
+        );
+
+        let lines: Vec<serde_json::Value> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["action"], "artifact_override");
+        assert_eq!(lines[0]["reason"], "hotfix");
+        assert_eq!(lines[0]["result"], "ok");
+        assert_eq!(lines[0]["manifest_sha256"], lines[1]["manifest_sha256"]);
+        assert_eq!(lines[1]["action"], "confine");
+        assert_eq!(lines[1]["reason"], serde_json::Value::Null);
+        assert_eq!(lines[1]["error"], "unknown user nobody");
+    }
+}
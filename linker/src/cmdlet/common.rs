@@ -1,4 +1,9 @@
-use clap::Arg;
+use std::path::Path;
+
+use clap::{Arg, ArgMatches};
+
+use crate::audit;
+use crate::document::{Format, FORMAT_NAMES};
 
 pub fn registry() -> Arg<'static, 'static> {
     Arg::with_name("registry")
@@ -36,4 +41,54 @@ pub fn artifact_override() -> Arg<'static, 'static> {
         .help("Override a Package ID with some other path")
         .multiple(true)
         .takes_value(true)
+        .requires("override-reason")
+}
+
+pub fn override_reason() -> Arg<'static, 'static> {
+    Arg::with_name("override-reason")
+        .long("override-reason")
+        .value_name("TEXT")
+        .help("Why artifacts are overridden, recorded in the audit log")
+        .takes_value(true)
+}
+
+pub fn audit_log() -> Arg<'static, 'static> {
+    Arg::with_name("audit-log")
+        .long("audit-log")
+        .value_name("PATH")
+        .help("Append a record of privileged actions to PATH (default: <approot>/audit.jsonl)")
+        .takes_value(true)
+        .validator_os(|_| Ok(()))
+}
+
+/// Opens the audit log named by `audit_log()`, or the one in `approot`.
+/// Without an approot, `audit_log()` must be required.
+pub fn open_audit_log(
+    matches: &ArgMatches,
+    approot: Option<&Path>,
+    command: &'static str,
+    manifest: Option<&Path>,
+) {
+    let path = match matches.value_of_os("audit-log") {
+        Some(path) => Path::new(path).to_owned(),
+        None => approot.unwrap().join(audit::AUDIT_LOG_FILE),
+    };
+    let manifest_sha256 = manifest.map(|m| audit::manifest_sha256(m).unwrap());
+    let override_reason = matches.value_of("override-reason").map(|r| r.to_string());
+    audit::open(&path, command, manifest_sha256, override_reason).unwrap();
+}
+
+pub fn input_format() -> Arg<'static, 'static> {
+    Arg::with_name("input-format")
+        .long("format")
+        // what it was first called.
+        .alias("input-format")
+        .value_name("FORMAT")
+        .help("Read input documents in this format instead of guessing from the file extension")
+        .possible_values(FORMAT_NAMES)
+        .takes_value(true)
+}
+
+pub fn get_input_format(matches: &ArgMatches) -> Option<Format> {
+    matches.value_of("input-format").map(|f| f.parse().unwrap())
 }
@@ -1,22 +1,30 @@
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::fmt;
 use std::fs::File;
-use std::io;
-use std::path::PathBuf;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 
 use clap::{App, Arg, SubCommand};
 use failure::{Fail, Fallible};
+use semver::VersionReq;
 use serde_json::{json, Value};
+use tempfile::NamedTempFile;
 use tokio::runtime;
 use tracing::{event, Level};
 use yscloud_config_model::{
-    ApplicationDeploymentTemplate, ArtifactHashSet, DeployedApplicationManifest,
+    ApplicationDeploymentRequirement, ApplicationDeploymentTemplate, ArtifactHashSet, DeployedApplicationManifest,
     DeployedPublicService, DeploymentManifest, RegistryEntry, Sandbox, ServiceId,
-    ImageType,
+    ImageType, LockedService, ReleaseLock, INTRINSIC_PACKAGE_ID,
+    migrate::CURRENT_FORMAT_VERSION,
 };
 
 use super::common;
+use crate::adapter;
+use crate::intrinsic;
+use crate::process;
 use crate::registry::{FileRegistry, Registry, RegistryShared};
+use crate::schema;
+use crate::template;
 use crate::CARGO_PKG_VERSION;
 
 pub const SUBCOMMAND_NAME: &str = "create-release";
@@ -26,6 +34,7 @@ pub fn get_subcommand() -> App<'static, 'static> {
         .version(CARGO_PKG_VERSION)
         .about("press a release")
         .arg(common::registry())
+        .arg(common::input_format())
         .arg(
             Arg::with_name("deployment-template")
                 .long("deployment-template")
@@ -42,6 +51,48 @@ pub fn get_subcommand() -> App<'static, 'static> {
                 .required(true)
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("overlay")
+                .long("overlay")
+                .value_name("FILE")
+                .help("A template overlay to merge over the deployment template, in order")
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true)
+                .validator_os(|_| Ok(())),
+        )
+        .arg(
+            Arg::with_name("vars")
+                .long("vars")
+                .value_name("FILE")
+                .help("A JSON object of values for ${VAR} references in the template")
+                .takes_value(true)
+                .validator_os(|_| Ok(())),
+        )
+        .arg(
+            Arg::with_name("lock-file")
+                .long("lock-file")
+                .value_name("FILE")
+                .help("The lock file to prefer versions from and to record resolved versions in")
+                .takes_value(true)
+                .validator_os(|_| Ok(())),
+        )
+        .arg(
+            Arg::with_name("locked")
+                .long("locked")
+                .help("Fail instead of changing the lock file")
+                .requires("lock-file"),
+        )
+        .arg(
+            Arg::with_name("update")
+                .long("update")
+                .value_name("PACKAGE_ID")
+                .help("Re-resolve this package instead of using the locked version")
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true)
+                .requires("lock-file"),
+        )
 }
 
 pub fn main(matches: &clap::ArgMatches) {
@@ -52,53 +103,301 @@ pub fn main(matches: &clap::ArgMatches) {
 
     let deployment_tpl_path = PathBuf::from(matches.value_of_os("deployment-template").unwrap());
 
-    let mut rdr = File::open(&deployment_tpl_path).unwrap();
-    let ad: ApplicationDeploymentTemplate = serde_json::from_reader(&mut rdr).unwrap();
+    let overlay_paths: Vec<PathBuf> = matches
+        .values_of_os("overlay")
+        .map(|v| v.map(PathBuf::from).collect())
+        .unwrap_or_default();
+    let overlay_paths: Vec<&Path> = overlay_paths.iter().map(|p| p.as_path()).collect();
+    event!(Level::TRACE, "got overlays: {:?}", overlay_paths);
+
+    let input_format = common::get_input_format(matches);
+
+    let vars = match matches.value_of_os("vars") {
+        Some(vars_path) => match template::load_vars(Path::new(vars_path), input_format) {
+            Ok(vars) => vars,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                std::process::exit(1);
+            }
+        },
+        None => Default::default(),
+    };
+
+    let loaded = template::load_template(&deployment_tpl_path, &overlay_paths, &vars, input_format);
+    let ad = match loaded {
+        Ok(ad) => ad,
+        Err(err) => {
+            event!(Level::ERROR, "loading deployment template failed: {}", err);
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let lock_path = matches.value_of_os("lock-file").map(PathBuf::from);
+    let locked = matches.is_present("locked");
+    let update: BTreeSet<String> = matches
+        .values_of_lossy("update")
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+
+    let previous_lock: Option<ReleaseLock> = lock_path.as_ref().and_then(|p| {
+        if !p.exists() {
+            event!(Level::INFO, "lock file {} does not exist yet", p.display());
+            return None;
+        }
+        let rdr = File::open(p).unwrap();
+        Some(serde_json::from_reader(rdr).unwrap())
+    });
 
     let registry = RegistryShared::shared(FileRegistry::new(&registry_path));
 
     let mut rt = runtime::Builder::new().basic_scheduler().build().unwrap();
 
-    let resolved = rt.block_on(resolve(&registry, &ad)).unwrap();
+    let result = rt
+        .block_on(resolve_with_lock(&registry, &ad, previous_lock.as_ref(), &update))
+        .and_then(|(resolved, lock)| {
+            if let Some(ref lock_path) = lock_path {
+                if previous_lock.as_ref() != Some(&lock) {
+                    if locked {
+                        return Err(LockOutdated::new(previous_lock.as_ref(), &lock).into());
+                    }
+                    write_lock(lock_path, &lock)?;
+                }
+            }
+            Ok(resolved)
+        });
+
+    let resolved = match result {
+        Ok(resolved) => resolved,
+        Err(err) => {
+            event!(Level::ERROR, "create-release failed: {}", err);
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+    };
 
     let stdout = io::stdout();
     serde_json::to_writer(stdout.lock(), &resolved).unwrap();
 }
 
+fn write_lock(path: &Path, lock: &ReleaseLock) -> Fallible<()> {
+    let parent = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+
+    let mut staged = NamedTempFile::new_in(parent)?;
+    serde_json::to_writer_pretty(staged.as_file_mut(), lock)?;
+    staged.write_all(b"\n")?;
+    staged.as_file().sync_all()?;
+    staged.persist(path).map_err(|e| e.error)?;
+
+    event!(Level::INFO, "wrote lock file {}", path.display());
+    Ok(())
+}
+
 #[derive(Debug, Fail)]
 struct MissingServiceName {
     service_name: String,
+    // package_id
+    required_by: Option<String>,
 }
 
 impl fmt::Display for MissingServiceName {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Missing service name: {}", self.service_name)
+        write!(f, "Missing service name: {}", self.service_name)?;
+        if let Some(ref required_by) = self.required_by {
+            write!(f, " (required by {})", required_by)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Fail)]
+struct VersionConflict {
+    package_id: String,
+    // service name -> requirement
+    demands: BTreeMap<String, VersionReq>,
+}
+
+impl fmt::Display for VersionConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "no version of {} satisfies every service it implements:",
+            self.package_id
+        )?;
+        for (service_name, ver_req) in &self.demands {
+            write!(f, "\n    {} requires {}", service_name, ver_req)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Fail)]
+struct UnsettledSelection {
+    package_ids: Vec<String>,
+}
+
+impl fmt::Display for UnsettledSelection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "picking versions of {} goes round in circles",
+            self.package_ids.join(", ")
+        )
+    }
+}
+
+#[derive(Debug, Fail)]
+struct UnknownUpdate {
+    package_ids: Vec<String>,
+}
+
+impl fmt::Display for UnknownUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "--update names packages not in the release: {}",
+            self.package_ids.join(", ")
+        )
+    }
+}
+
+#[derive(Debug, Fail)]
+struct LockOutdated {
+    changes: Vec<String>,
+}
+
+impl LockOutdated {
+    fn new(previous: Option<&ReleaseLock>, current: &ReleaseLock) -> LockOutdated {
+        let empty = ReleaseLock::default();
+        let previous = previous.unwrap_or(&empty);
+
+        let mut changes = Vec::new();
+        if previous.deployment_name != current.deployment_name {
+            changes.push(format!(
+                "deployment name {:?} -> {:?}",
+                previous.deployment_name, current.deployment_name
+            ));
+        }
+
+        let service_names: BTreeSet<&String> = previous
+            .services
+            .keys()
+            .chain(current.services.keys())
+            .collect();
+
+        for service_name in service_names {
+            match (
+                previous.services.get(service_name),
+                current.services.get(service_name),
+            ) {
+                (Some(old), Some(new)) if old != new => {
+                    changes.push(format!(
+                        "{}: {} v{} -> {} v{}",
+                        service_name, old.package_id, old.version, new.package_id, new.version
+                    ));
+                    if old.package_id == new.package_id && old.version == new.version {
+                        changes.push(format!("{}: artifact hashes changed", service_name));
+                    }
+                }
+                (Some(old), None) => {
+                    changes.push(format!(
+                        "{}: {} v{} removed",
+                        service_name, old.package_id, old.version
+                    ));
+                }
+                (None, Some(new)) => {
+                    changes.push(format!(
+                        "{}: {} v{} added",
+                        service_name, new.package_id, new.version
+                    ));
+                }
+                _ => (),
+            }
+        }
+
+        LockOutdated { changes }
+    }
+}
+
+impl fmt::Display for LockOutdated {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "lock file needs to be updated but --locked was passed")?;
+        for change in &self.changes {
+            write!(f, "\n    {}", change)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Fail)]
+struct LockHashMismatch {
+    package_id: String,
+    version: semver::Version,
+    host_triple: String,
+    locked_sha256: String,
+    registry_sha256: Option<String>,
+}
+
+impl fmt::Display for LockHashMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} v{} for {} is locked to sha256 {} but the registry has {}",
+            self.package_id,
+            self.version,
+            self.host_triple,
+            self.locked_sha256,
+            self.registry_sha256.as_deref().unwrap_or("nothing"),
+        )
     }
 }
 
+#[cfg(test)]
 async fn resolve(
     reg: &RegistryShared,
     template: &ApplicationDeploymentTemplate,
 ) -> Fallible<DeploymentManifest> {
+    let (out, _lock) = resolve_with_lock(reg, template, None, &Default::default()).await?;
+    Ok(out)
+}
+
+/// Resolves `template` against the registry.  Services recorded in `previous`
+/// keep their locked version as long as it still satisfies the template,
+/// unless their package is named in `update`, which must only name packages
+/// of the release.
+///
+/// Several services may be implemented by the same package, in which case a
+/// single component is emitted at a version satisfying all of their
+/// requirements.  Picking a version can pull in new required services, or
+/// drop those of the version it replaces, so selection is repeated until no
+/// package's pick changes.
+async fn resolve_with_lock(
+    reg: &RegistryShared,
+    template: &ApplicationDeploymentTemplate,
+    previous: Option<&ReleaseLock>,
+    update: &BTreeSet<String>,
+) -> Fallible<(DeploymentManifest, ReleaseLock)> {
+    let previous = previous.filter(|l| l.deployment_name == template.deployment_name);
+
     let mut out = DeploymentManifest {
+        format_version: CURRENT_FORMAT_VERSION,
         deployment_name: template.deployment_name.clone(),
         public_services: Vec::new(),
         components: Vec::new(),
         path_overrides: Default::default(),
     };
 
-    let mut unresolved_local_services: VecDeque<String> = Default::default();
-    let mut resolved_local_services = BTreeMap::new();
+    let mut selected: BTreeMap<String, RegistryEntry> = BTreeMap::new();
+    // every selection so far, so one that goes round in circles ends.
+    let mut seen_selections: BTreeSet<Vec<(String, semver::Version)>> = BTreeSet::new();
 
     for ps in &template.public_services {
-        let impl_req = template
-            .service_implementations
-            .get(&ps.service_name)
-            .ok_or_else(|| MissingServiceName {
-                service_name: ps.service_name.clone(),
-            })?;
+        let impl_req = lookup_service(template, &ps.service_name, None)?;
 
-        unresolved_local_services.push_back(ps.service_name.clone());
         out.public_services.push(DeployedPublicService {
             service_id: ServiceId {
                 package_id: impl_req.package_id.clone(),
@@ -108,56 +407,181 @@ async fn resolve(
         });
     }
 
-    while let Some(ps) = unresolved_local_services.pop_front() {
-        if resolved_local_services.get(&ps).is_some() {
-            continue;
+    loop {
+        // what each package must satisfy follows from the current picks
+        // alone, not from those they replaced.
+        // (service name, package that requires it)
+        let mut unresolved_local_services: VecDeque<(String, Option<String>)> = template
+            .public_services
+            .iter()
+            .map(|ps| (ps.service_name.clone(), None))
+            .collect();
+        let mut seen_services = BTreeSet::new();
+        // package_id -> service name -> requirement
+        let mut demands: BTreeMap<String, BTreeMap<String, VersionReq>> = BTreeMap::new();
+        while let Some((ps, required_by)) = unresolved_local_services.pop_front() {
+            if !seen_services.insert(ps.clone()) || is_intrinsic(template, &ps) {
+                continue;
+            }
+
+            let impl_req = lookup_service(template, &ps, required_by)?;
+            if let Some(found) = selected.get(&impl_req.package_id) {
+                for rls in &found.manifest.required_local_services {
+                    unresolved_local_services
+                        .push_back((rls.clone(), Some(impl_req.package_id.clone())));
+                }
+            }
+            demands
+                .entry(impl_req.package_id.clone())
+                .or_insert_with(Default::default)
+                .insert(ps, impl_req.version_req.clone());
         }
+        selected.retain(|package_id, _| demands.contains_key(package_id));
 
-        let impl_req =
-            template
-                .service_implementations
-                .get(&ps)
-                .ok_or_else(|| MissingServiceName {
-                    service_name: ps.clone(),
-                })?;
+        // a package may only be demanded by a pick that is replaced in this
+        // pass, so failing to select it only counts once nothing changes.
+        let mut changed = false;
+        let mut failed = None;
+        for (package_id, package_demands) in &demands {
+            let found =
+                match select_package(reg, package_id, package_demands, previous, update).await {
+                    Ok(found) => found,
+                    Err(err) => {
+                        failed = failed.or(Some(err));
+                        continue;
+                    }
+                };
+            let unchanged = selected
+                .get(package_id)
+                .map(|e| e.version == found.version)
+                .unwrap_or(false);
+            if !unchanged {
+                selected.insert(package_id.clone(), found);
+                changed = true;
+            }
+        }
+        if !changed {
+            if let Some(err) = failed {
+                return Err(err);
+            }
+            break;
+        }
 
-        let found: RegistryEntry = reg
-            .find_best_entry_for_version(&impl_req.package_id, &impl_req.version_req)
-            .await?;
+        let selection = selected
+            .iter()
+            .map(|(package_id, e)| (package_id.clone(), e.version.clone()))
+            .collect();
+        if !seen_selections.insert(selection) {
+            return Err(UnsettledSelection {
+                package_ids: selected.keys().cloned().collect(),
+            }
+            .into());
+        }
+    }
 
-        for rls in &found.manifest.required_local_services {
-            unresolved_local_services.push_back(rls.clone());
+    let unknown: Vec<String> = update
+        .iter()
+        .filter(|package_id| !selected.contains_key(*package_id))
+        .cloned()
+        .collect();
+    if !unknown.is_empty() {
+        return Err(UnknownUpdate {
+            package_ids: unknown,
         }
+        .into());
+    }
 
-        let mut required_local_services = Vec::new();
+    // walk the final selection again: a package whose version changed during
+    // selection may no longer require everything its earlier pick did.
+    let mut lock = ReleaseLock {
+        deployment_name: template.deployment_name.clone(),
+        services: Default::default(),
+    };
+    let mut used_packages = BTreeSet::new();
+    let mut pending: VecDeque<String> = template
+        .public_services
+        .iter()
+        .map(|ps| ps.service_name.clone())
+        .collect();
 
+    while let Some(ps) = pending.pop_front() {
+        // intrinsics aren't pinned, so they stay out of the lock.
+        if lock.services.contains_key(&ps) || is_intrinsic(template, &ps) {
+            continue;
+        }
+
+        let impl_req = lookup_service(template, &ps, None)?;
+        let found = &selected[&impl_req.package_id];
+        pending.extend(found.manifest.required_local_services.iter().cloned());
+
+        lock.services.insert(
+            ps,
+            LockedService {
+                package_id: impl_req.package_id.clone(),
+                version: found.version.clone(),
+                sha256s: found
+                    .sha256s
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+            },
+        );
+        used_packages.insert(impl_req.package_id.clone());
+    }
+
+    for package_id in used_packages {
+        let found = selected.remove(&package_id).unwrap();
+
+        let mut required_local_services = Vec::new();
         for rls in &found.manifest.required_local_services {
-            let service_impl =
-                template
-                    .service_implementations
-                    .get(rls)
-                    .ok_or_else(|| MissingServiceName {
-                        service_name: ps.clone(),
-                    })?;
+            let provider = if is_intrinsic(template, rls) {
+                INTRINSIC_PACKAGE_ID
+            } else {
+                lookup_service(template, rls, Some(package_id.clone()))?
+                    .package_id
+                    .as_str()
+            };
 
             required_local_services.push(ServiceId {
-                package_id: service_impl.package_id.clone(),
+                package_id: provider.to_string(),
                 service_name: rls.clone(),
             });
         }
 
         let extras: Value = template
             .configuration
-            .get(&impl_req.package_id)
+            .get(&package_id)
             .map(|x| x.clone())
             .unwrap_or_else(|| json!({}));
+        schema::validate_extras(&package_id, found.manifest.extras_schema.as_ref(), &extras)?;
 
         let sandbox = template
             .sandbox
-            .get(&impl_req.package_id)
+            .get(&package_id)
             .cloned()
             .unwrap_or(Sandbox::Unconfined);
 
+        let process = template
+            .process
+            .get(&package_id)
+            .cloned()
+            .unwrap_or_default();
+        process::check(&package_id, &process)?;
+
+        let activation = template
+            .activation
+            .get(&package_id)
+            .cloned()
+            .unwrap_or_default();
+
+        let adapter = template.adapter.get(&package_id).cloned();
+
+        let workdir = template
+            .workdir
+            .get(&package_id)
+            .cloned()
+            .unwrap_or_default();
+
         let mut artifacts = BTreeMap::new();
         for (trip, sha256) in &found.sha256s {
             artifacts.insert(
@@ -169,42 +593,131 @@ async fn resolve(
             );
         }
 
-        resolved_local_services.insert(
-            ps,
-            DeployedApplicationManifest {
-                package_id: impl_req.package_id.clone(),
-                version: found.version,
-                provided_local_services: found.manifest.provided_local_services,
-                provided_remote_services: found.manifest.provided_remote_services,
-                required_local_services,
-                required_remote_services: found.manifest.required_remote_services,
-                image_type: ImageType::Executable,
-                sandbox,
-                extras,
-                artifacts,
-            },
-        );
+        let component = DeployedApplicationManifest {
+            package_id,
+            version: found.version,
+            provided_local_services: found.manifest.provided_local_services,
+            provided_remote_services: found.manifest.provided_remote_services,
+            required_local_services,
+            required_remote_services: found.manifest.required_remote_services,
+            image_type: ImageType::Executable,
+            live_config: found.manifest.live_config,
+            fd_protocol: found.manifest.fd_protocol,
+            process,
+            activation,
+            adapter,
+            workdir,
+            sandbox,
+            extras,
+            artifacts,
+        };
+        adapter::check(&component)?;
+        out.components.push(component);
     }
 
-    out.components
-        .extend(resolved_local_services.into_iter().map(|(_, v)| v));
+    Ok((out, lock))
+}
 
-    Ok(out)
+/// Whether `service_name` is left to the linker.  A template can still pin
+/// its own implementation of an intrinsic.
+fn is_intrinsic(template: &ApplicationDeploymentTemplate, service_name: &str) -> bool {
+    !template.service_implementations.contains_key(service_name)
+        && (template.intrinsic_services.contains(service_name)
+            || intrinsic::is_builtin(service_name))
+}
+
+fn lookup_service<'a>(
+    template: &'a ApplicationDeploymentTemplate,
+    service_name: &str,
+    required_by: Option<String>,
+) -> Result<&'a ApplicationDeploymentRequirement, MissingServiceName> {
+    template
+        .service_implementations
+        .get(service_name)
+        .ok_or_else(|| MissingServiceName {
+            service_name: service_name.to_string(),
+            required_by,
+        })
+}
+
+/// Picks the version of `package_id` to deploy, given what every service it
+/// implements demands of it.
+async fn select_package(
+    reg: &RegistryShared,
+    package_id: &str,
+    demands: &BTreeMap<String, VersionReq>,
+    previous: Option<&ReleaseLock>,
+    update: &BTreeSet<String>,
+) -> Fallible<RegistryEntry> {
+    let locked = previous
+        .and_then(|l| {
+            demands
+                .keys()
+                .filter_map(|service_name| l.services.get(service_name))
+                .find(|l| l.package_id == package_id)
+        })
+        .filter(|l| !update.contains(&l.package_id))
+        .filter(|l| demands.values().all(|r| r.matches(&l.version)));
+
+    if let Some(locked) = locked {
+        event!(
+            Level::DEBUG,
+            "using locked {} v{}",
+            locked.package_id,
+            locked.version,
+        );
+        let found = reg
+            .find_exact_entry(&locked.package_id, &locked.version)
+            .await?;
+        for (host_triple, locked_sha256) in &locked.sha256s {
+            let registry_sha256 = found.sha256s.get(host_triple);
+            if registry_sha256 != Some(locked_sha256) {
+                return Err(LockHashMismatch {
+                    package_id: locked.package_id.clone(),
+                    version: locked.version.clone(),
+                    host_triple: host_triple.clone(),
+                    locked_sha256: locked_sha256.clone(),
+                    registry_sha256: registry_sha256.cloned(),
+                }
+                .into());
+            }
+        }
+        return Ok(found);
+    }
+
+    let ver_reqs: Vec<VersionReq> = demands.values().cloned().collect();
+    match reg.find_best_entry_for_versions(package_id, &ver_reqs).await {
+        Ok(found) => Ok(found),
+        Err(err) if 1 < demands.len() => {
+            // if each requirement can be met on its own, they conflict.
+            for ver_req in &ver_reqs {
+                reg.find_best_entry_for_versions(package_id, std::slice::from_ref(ver_req))
+                    .await?;
+            }
+            event!(Level::DEBUG, "combined requirements failed: {}", err);
+            Err(VersionConflict {
+                package_id: package_id.to_string(),
+                demands: demands.clone(),
+            }
+            .into())
+        }
+        Err(err) => Err(err),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     #[test]
     fn test_staticserver_simple() {
-        use std::collections::BTreeMap;
+        use std::collections::{BTreeMap, BTreeSet};
 
         use semver::{Version, VersionReq};
         use tokio::runtime::Runtime;
 
         use yscloud_config_model::{
-            permissions, ApplicationDeploymentRequirement, ApplicationDeploymentTemplate,
-            ApplicationManifest, DeploymentManifest, NativePortBinder, PublicService,
-            PublicServiceBinder, RegistryEntry, Sandbox, SocketFlag,
+            migrate::CURRENT_FORMAT_VERSION, permissions, ApplicationDeploymentRequirement, ApplicationDeploymentTemplate,
+            ApplicationManifest, DeploymentManifest, FdProtocol, NativePortBinder, ProcessSettings,
+            PublicService, PublicServiceBinder, RegistryEntry, Sandbox, SocketFlag,
         };
 
         use super::resolve;
@@ -216,6 +729,9 @@ mod tests {
             required_remote_services: Vec::new(),
             required_local_services: Vec::new(),
             permissions: vec![permissions::UNCONSTRAINED],
+            extras_schema: None,
+            live_config: false,
+            fd_protocol: FdProtocol::ConfigFd,
         };
 
         let staticserver_manifest = ApplicationManifest {
@@ -224,6 +740,9 @@ mod tests {
             required_remote_services: Vec::new(),
             required_local_services: vec!["org.yshi.log_target.v1.LogTarget".to_string()],
             permissions: vec![permissions::UNCONSTRAINED],
+            extras_schema: None,
+            live_config: false,
+            fd_protocol: FdProtocol::ConfigFd,
         };
 
         let mut registry = MemRegistry::default();
@@ -325,6 +844,7 @@ mod tests {
         }"#).unwrap();
 
         let template = ApplicationDeploymentTemplate {
+            format_version: CURRENT_FORMAT_VERSION,
             deployment_name: "example-deployment".into(),
             public_services: vec![PublicService {
                 service_name: "org.yshi.staticserver.http".into(),
@@ -383,10 +903,25 @@ mod tests {
 
                 map
             },
+            process: {
+                let mut map = BTreeMap::<String, ProcessSettings>::new();
+
+                let mut settings = ProcessSettings::default();
+                settings.arguments.push("--quiet".into());
+                settings.environment.insert("TZ".into(), "UTC".into());
+                map.insert("org.yshi.staticserver".into(), settings);
+
+                map
+            },
+            activation: BTreeMap::new(),
+            adapter: BTreeMap::new(),
+            workdir: BTreeMap::new(),
+            intrinsic_services: BTreeSet::new(),
         };
 
         let dm_expect: DeploymentManifest = serde_json::from_str(
             r#"{
+          "format_version": 1,
           "deployment_name": "example-deployment",
           "public_services": [
             {
@@ -448,6 +983,10 @@ mod tests {
               },
               "extras": {
                 "allowed_hostnames": ["foobar-0436e87111796739188f.nydus.yshi.org"]
+              },
+              "process": {
+                "arguments": ["--quiet"],
+                "environment": {"TZ": "UTC"}
               }
             }
           ]
@@ -460,4 +999,393 @@ mod tests {
         println!("dm = {:#?}", dm);
         assert_eq!(dm_expect, dm);
     }
+
+    #[test]
+    fn test_lock_prefers_locked_versions() {
+        use std::collections::{BTreeMap, BTreeSet};
+
+        use semver::{Version, VersionReq};
+        use tokio::runtime::Runtime;
+
+        use yscloud_config_model::{
+            migrate::CURRENT_FORMAT_VERSION, permissions, ApplicationDeploymentRequirement, ApplicationDeploymentTemplate,
+            ApplicationManifest, FdProtocol, NativePortBinder, PublicService, PublicServiceBinder,
+            RegistryEntry,
+        };
+
+        use super::resolve_with_lock;
+        use crate::registry::{MemRegistry, RegistryShared};
+
+        let manifest = ApplicationManifest {
+            provided_remote_services: vec!["org.yshi.staticserver.http".to_string()],
+            provided_local_services: Vec::new(),
+            required_remote_services: Vec::new(),
+            required_local_services: Vec::new(),
+            permissions: vec![permissions::UNCONSTRAINED],
+            extras_schema: None,
+            live_config: false,
+            fd_protocol: FdProtocol::ConfigFd,
+        };
+
+        let entry = |version: &str, sha256: &str| RegistryEntry {
+            version: Version::parse(version).unwrap(),
+            sha256s: vec![("x86_64-unknown-linux-gnu".to_string(), sha256.to_string())]
+                .into_iter()
+                .collect(),
+            manifest: manifest.clone(),
+        };
+
+        let template = ApplicationDeploymentTemplate {
+            format_version: CURRENT_FORMAT_VERSION,
+            deployment_name: "example-deployment".into(),
+            public_services: vec![PublicService {
+                service_name: "org.yshi.staticserver.http".into(),
+                binder: PublicServiceBinder::NativePortBinder(NativePortBinder {
+                    bind_address: "::".into(),
+                    port: 8080,
+                    start_listen: true,
+                    flags: Vec::new(),
+                }),
+            }],
+            service_implementations: vec![(
+                "org.yshi.staticserver.http".to_string(),
+                ApplicationDeploymentRequirement {
+                    package_id: "org.yshi.staticserver".into(),
+                    version_req: VersionReq::parse("^1.0").unwrap(),
+                },
+            )]
+            .into_iter()
+            .collect(),
+            configuration: BTreeMap::new(),
+            sandbox: BTreeMap::new(),
+            process: BTreeMap::new(),
+            activation: BTreeMap::new(),
+            adapter: BTreeMap::new(),
+            workdir: BTreeMap::new(),
+            intrinsic_services: BTreeSet::new(),
+        };
+
+        let mut rt = Runtime::new().unwrap();
+        let no_updates = BTreeSet::new();
+
+        let mut registry = MemRegistry::default();
+        registry.add_package("org.yshi.staticserver", entry("1.0.0", "aaaa"));
+        let registry = RegistryShared::shared(registry);
+        let (_, lock) = rt
+            .block_on(resolve_with_lock(&registry, &template, None, &no_updates))
+            .unwrap();
+
+        let mut registry = MemRegistry::default();
+        registry.add_package("org.yshi.staticserver", entry("1.0.0", "aaaa"));
+        registry.add_package("org.yshi.staticserver", entry("1.1.0", "bbbb"));
+        let registry = RegistryShared::shared(registry);
+
+        let (dm, relock) = rt
+            .block_on(resolve_with_lock(&registry, &template, Some(&lock), &no_updates))
+            .unwrap();
+        assert_eq!(relock, lock);
+        assert_eq!(dm.components[0].version, Version::parse("1.0.0").unwrap());
+
+        let mut update = BTreeSet::new();
+        update.insert("org.yshi.staticserver".to_string());
+        let (dm, relock) = rt
+            .block_on(resolve_with_lock(&registry, &template, Some(&lock), &update))
+            .unwrap();
+        assert_ne!(relock, lock);
+        assert_eq!(dm.components[0].version, Version::parse("1.1.0").unwrap());
+
+        update.insert("org.yshi.staticsever".to_string());
+        let err = rt
+            .block_on(resolve_with_lock(&registry, &template, Some(&lock), &update))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "--update names packages not in the release: org.yshi.staticsever"
+        );
+
+        let mut registry = MemRegistry::default();
+        registry.add_package("org.yshi.staticserver", entry("1.0.0", "cccc"));
+        let registry = RegistryShared::shared(registry);
+        assert!(rt
+            .block_on(resolve_with_lock(&registry, &template, Some(&lock), &no_updates))
+            .is_err());
+    }
+
+    #[test]
+    fn test_requirements_unified_per_package() {
+        use std::collections::{BTreeMap, BTreeSet};
+
+        use semver::{Version, VersionReq};
+        use tokio::runtime::Runtime;
+
+        use yscloud_config_model::{
+            migrate::CURRENT_FORMAT_VERSION, permissions, ApplicationDeploymentRequirement, ApplicationDeploymentTemplate,
+            ApplicationManifest, FdProtocol, PublicService, PublicServiceBinder, RegistryEntry,
+            ServiceId, UnixDomainBinder, INTRINSIC_PACKAGE_ID,
+        };
+
+        use super::resolve;
+        use crate::registry::{MemRegistry, RegistryShared};
+
+        let logger_manifest = ApplicationManifest {
+            provided_remote_services: Vec::new(),
+            provided_local_services: vec![
+                "org.yshi.log_target.v1.LogTarget".to_string(),
+                "org.yshi.log_query.v1.LogQuery".to_string(),
+            ],
+            required_remote_services: Vec::new(),
+            required_local_services: Vec::new(),
+            permissions: vec![permissions::UNCONSTRAINED],
+            extras_schema: None,
+            live_config: false,
+            fd_protocol: FdProtocol::ConfigFd,
+        };
+        let frontend_manifest = ApplicationManifest {
+            provided_remote_services: vec!["org.yshi.frontend.http".to_string()],
+            provided_local_services: Vec::new(),
+            required_remote_services: Vec::new(),
+            required_local_services: vec![
+                "org.yshi.log_target.v1.LogTarget".to_string(),
+                "org.yshi.log_query.v1.LogQuery".to_string(),
+            ],
+            permissions: vec![permissions::UNCONSTRAINED],
+            extras_schema: None,
+            live_config: false,
+            fd_protocol: FdProtocol::ConfigFd,
+        };
+
+        let mut registry = MemRegistry::default();
+        registry.add_package(
+            "org.yshi.frontend",
+            RegistryEntry {
+                version: Version::parse("1.0.0").unwrap(),
+                sha256s: Default::default(),
+                manifest: frontend_manifest,
+            },
+        );
+        for version in &["1.0.0", "1.4.0", "2.0.0"] {
+            registry.add_package(
+                "org.yshi.file-logger",
+                RegistryEntry {
+                    version: Version::parse(version).unwrap(),
+                    sha256s: Default::default(),
+                    manifest: logger_manifest.clone(),
+                },
+            );
+        }
+        let registry = RegistryShared::shared(registry);
+
+        let requirement = |package_id: &str, version_req: &str| ApplicationDeploymentRequirement {
+            package_id: package_id.into(),
+            version_req: VersionReq::parse(version_req).unwrap(),
+        };
+
+        let mut template = ApplicationDeploymentTemplate {
+            format_version: CURRENT_FORMAT_VERSION,
+            deployment_name: "example-deployment".into(),
+            public_services: vec![PublicService {
+                service_name: "org.yshi.frontend.http".into(),
+                binder: PublicServiceBinder::UnixDomainBinder(UnixDomainBinder {
+                    path: "/tmp/org.yshi.frontend.http".into(),
+                    start_listen: true,
+                    flags: Vec::new(),
+                }),
+            }],
+            service_implementations: BTreeMap::new(),
+            configuration: BTreeMap::new(),
+            sandbox: BTreeMap::new(),
+            process: BTreeMap::new(),
+            activation: BTreeMap::new(),
+            adapter: BTreeMap::new(),
+            workdir: BTreeMap::new(),
+            intrinsic_services: BTreeSet::new(),
+        };
+        template.service_implementations.insert(
+            "org.yshi.frontend.http".into(),
+            requirement("org.yshi.frontend", "^1.0"),
+        );
+        template.service_implementations.insert(
+            "org.yshi.log_target.v1.LogTarget".into(),
+            requirement("org.yshi.file-logger", ">=1.0"),
+        );
+        template.service_implementations.insert(
+            "org.yshi.log_query.v1.LogQuery".into(),
+            requirement("org.yshi.file-logger", "^1.0"),
+        );
+
+        let mut rt = Runtime::new().unwrap();
+        let dm = rt.block_on(resolve(&registry, &template)).unwrap();
+        assert_eq!(dm.components.len(), 2);
+        assert_eq!(dm.components[0].package_id, "org.yshi.file-logger");
+        assert_eq!(dm.components[0].version, Version::parse("1.4.0").unwrap());
+
+        template.service_implementations.insert(
+            "org.yshi.log_target.v1.LogTarget".into(),
+            requirement("org.yshi.file-logger", "^2.0"),
+        );
+        let err = rt.block_on(resolve(&registry, &template)).unwrap_err();
+        let msg = format!("{}", err);
+        assert!(msg.contains("org.yshi.log_target.v1.LogTarget requires >=2.0.0, <3.0.0"), "{}", msg);
+        assert!(msg.contains("org.yshi.log_query.v1.LogQuery requires >=1.0.0, <2.0.0"), "{}", msg);
+
+        template
+            .service_implementations
+            .remove("org.yshi.log_query.v1.LogQuery");
+        let err = rt.block_on(resolve(&registry, &template)).unwrap_err();
+        assert_eq!(
+            format!("{}", err),
+            "Missing service name: org.yshi.log_query.v1.LogQuery (required by org.yshi.frontend)"
+        );
+
+        template
+            .intrinsic_services
+            .insert("org.yshi.log_query.v1.LogQuery".into());
+        let dm = rt.block_on(resolve(&registry, &template)).unwrap();
+        assert_eq!(dm.components[1].package_id, "org.yshi.frontend");
+        assert_eq!(
+            dm.components[1].required_local_services,
+            vec![
+                ServiceId {
+                    package_id: "org.yshi.file-logger".into(),
+                    service_name: "org.yshi.log_target.v1.LogTarget".into(),
+                },
+                ServiceId {
+                    package_id: INTRINSIC_PACKAGE_ID.into(),
+                    service_name: "org.yshi.log_query.v1.LogQuery".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_superseded_requirements_dropped() {
+        use std::collections::{BTreeMap, BTreeSet};
+
+        use semver::{Version, VersionReq};
+        use tokio::runtime::Runtime;
+
+        use yscloud_config_model::{
+            migrate::CURRENT_FORMAT_VERSION, permissions, ApplicationDeploymentRequirement,
+            ApplicationDeploymentTemplate, ApplicationManifest, FdProtocol, PublicService,
+            PublicServiceBinder, RegistryEntry, UnixDomainBinder,
+        };
+
+        use super::resolve;
+        use crate::registry::{MemRegistry, RegistryShared};
+
+        let manifest = |provided: &[&str], required: &[&str]| ApplicationManifest {
+            provided_remote_services: Vec::new(),
+            provided_local_services: provided.iter().map(|s| s.to_string()).collect(),
+            required_remote_services: Vec::new(),
+            required_local_services: required.iter().map(|s| s.to_string()).collect(),
+            permissions: vec![permissions::UNCONSTRAINED],
+            extras_schema: None,
+            live_config: false,
+            fd_protocol: FdProtocol::ConfigFd,
+        };
+        let logger = &[
+            "org.yshi.log_target.v1.LogTarget",
+            "org.yshi.log_query.v1.LogQuery",
+        ];
+
+        let mut registry = MemRegistry::default();
+        let mut add = |package_id: &str, version: &str, manifest: ApplicationManifest| {
+            registry.add_package(
+                package_id,
+                RegistryEntry {
+                    version: Version::parse(version).unwrap(),
+                    sha256s: Default::default(),
+                    manifest,
+                },
+            );
+        };
+        add(
+            "org.yshi.frontend",
+            "1.0.0",
+            manifest(
+                &[],
+                &[
+                    "org.yshi.log_target.v1.LogTarget",
+                    "org.yshi.tracing.v1.Tracing",
+                ],
+            ),
+        );
+        // the newest logger needs metrics, which aren't in the registry, but
+        // the tracer holds the logger back to 1.x anyway.
+        add("org.yshi.file-logger", "1.0.0", manifest(logger, &[]));
+        add(
+            "org.yshi.file-logger",
+            "2.0.0",
+            manifest(logger, &["org.yshi.metrics.v1.Metrics"]),
+        );
+        add(
+            "org.yshi.tracer",
+            "1.0.0",
+            manifest(
+                &["org.yshi.tracing.v1.Tracing"],
+                &["org.yshi.log_query.v1.LogQuery"],
+            ),
+        );
+        let registry = RegistryShared::shared(registry);
+
+        let mut template = ApplicationDeploymentTemplate {
+            format_version: CURRENT_FORMAT_VERSION,
+            deployment_name: "example-deployment".into(),
+            public_services: vec![PublicService {
+                service_name: "org.yshi.frontend.http".into(),
+                binder: PublicServiceBinder::UnixDomainBinder(UnixDomainBinder {
+                    path: "/tmp/org.yshi.frontend.http".into(),
+                    start_listen: true,
+                    flags: Vec::new(),
+                }),
+            }],
+            service_implementations: BTreeMap::new(),
+            configuration: BTreeMap::new(),
+            sandbox: BTreeMap::new(),
+            process: BTreeMap::new(),
+            activation: BTreeMap::new(),
+            adapter: BTreeMap::new(),
+            workdir: BTreeMap::new(),
+            intrinsic_services: BTreeSet::new(),
+        };
+        for (service_name, package_id, version_req) in &[
+            ("org.yshi.frontend.http", "org.yshi.frontend", "^1.0"),
+            (
+                "org.yshi.log_target.v1.LogTarget",
+                "org.yshi.file-logger",
+                ">=1.0",
+            ),
+            (
+                "org.yshi.log_query.v1.LogQuery",
+                "org.yshi.file-logger",
+                "^1.0",
+            ),
+            ("org.yshi.tracing.v1.Tracing", "org.yshi.tracer", "^1.0"),
+            ("org.yshi.metrics.v1.Metrics", "org.yshi.metrics", "^1.0"),
+        ] {
+            template.service_implementations.insert(
+                service_name.to_string(),
+                ApplicationDeploymentRequirement {
+                    package_id: package_id.to_string(),
+                    version_req: VersionReq::parse(version_req).unwrap(),
+                },
+            );
+        }
+
+        let mut rt = Runtime::new().unwrap();
+        let dm = rt.block_on(resolve(&registry, &template)).unwrap();
+        let picks: Vec<(&str, String)> = dm
+            .components
+            .iter()
+            .map(|c| (&c.package_id[..], c.version.to_string()))
+            .collect();
+        assert_eq!(
+            picks,
+            vec![
+                ("org.yshi.file-logger", "1.0.0".to_string()),
+                ("org.yshi.frontend", "1.0.0".to_string()),
+                ("org.yshi.tracer", "1.0.0".to_string()),
+            ]
+        );
+    }
 }
@@ -0,0 +1,75 @@
+use std::io;
+use std::path::Path;
+
+use clap::{App, Arg, SubCommand};
+use tracing::{event, Level};
+
+use super::common;
+use crate::manifest_diff::diff_manifests;
+use crate::util::load_deployment_manifest;
+use crate::CARGO_PKG_VERSION;
+
+pub const SUBCOMMAND_NAME: &str = "diff";
+
+pub fn get_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name(SUBCOMMAND_NAME)
+        .version(CARGO_PKG_VERSION)
+        .about("compare two deployment manifests")
+        .arg(
+            Arg::with_name("old")
+                .long("old")
+                .value_name("FILE")
+                .help("The deployment manifest currently rolled out")
+                .required(true)
+                .takes_value(true)
+                .validator_os(|_| Ok(())),
+        )
+        .arg(
+            Arg::with_name("new")
+                .long("new")
+                .value_name("FILE")
+                .help("The deployment manifest to compare against")
+                .required(true)
+                .takes_value(true)
+                .validator_os(|_| Ok(())),
+        )
+        .arg(common::input_format())
+        .arg(
+            Arg::with_name("output-format")
+                .long("output-format")
+                .value_name("FORMAT")
+                .help("The output format")
+                .possible_values(&["text", "json"])
+                .default_value("text")
+                .takes_value(true),
+        )
+}
+
+pub fn main(matches: &clap::ArgMatches) {
+    let old_path = Path::new(matches.value_of_os("old").unwrap()).to_owned();
+    event!(Level::TRACE, "got old: {}", old_path.display());
+
+    let new_path = Path::new(matches.value_of_os("new").unwrap()).to_owned();
+    event!(Level::TRACE, "got new: {}", new_path.display());
+
+    let input_format = common::get_input_format(matches);
+    let diff = diff_manifests(
+        &load_deployment_manifest(&old_path, input_format).unwrap(),
+        &load_deployment_manifest(&new_path, input_format).unwrap(),
+    );
+
+    match matches.value_of("output-format").unwrap() {
+        "json" => {
+            let stdout = io::stdout();
+            serde_json::to_writer(stdout.lock(), &diff).unwrap();
+            println!();
+        }
+        _ => {
+            if diff.is_empty() {
+                println!("no changes");
+            } else {
+                print!("{}", diff);
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use clap::{App, Arg, SubCommand};
+use tracing::{event, Level};
+
+use super::common;
+use crate::start_daemon::Journal;
+use crate::util::load_deployment_manifest;
+use crate::workdir;
+use crate::CARGO_PKG_VERSION;
+
+pub const SUBCOMMAND_NAME: &str = "gc-workdirs";
+
+pub fn get_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name(SUBCOMMAND_NAME)
+        .version(CARGO_PKG_VERSION)
+        .about("remove the working directories of deployments that no longer exist")
+        .after_help(
+            "Run with --dry-run first and check the list: whatever it names is removed for good.",
+        )
+        .arg(common::approot())
+        .arg(common::input_format())
+        .arg(
+            Arg::with_name("keep")
+                .long("keep")
+                .value_name("FILE")
+                .help("a deployment manifest whose working directories must be kept")
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true)
+                .validator_os(|_| Ok(())),
+        )
+        .arg(
+            Arg::with_name("dry-run")
+                .long("dry-run")
+                .help("list what would be removed without removing anything"),
+        )
+        .arg(common::audit_log())
+}
+
+pub fn main(matches: &clap::ArgMatches) {
+    let approot = matches.value_of_os("approot").unwrap();
+    let approot = Path::new(approot).to_owned();
+    event!(Level::TRACE, "got approot: {}", approot.display());
+
+    let dry_run = matches.is_present("dry-run");
+
+    // deployments `start-daemon` is running count as existing, too.
+    let journal = Journal::load(&Journal::path(&approot)).unwrap();
+    let mut live: BTreeSet<String> = journal.deployments.keys().cloned().collect();
+    let input_format = common::get_input_format(matches);
+    if let Some(keep) = matches.values_of_os("keep") {
+        for manifest_path in keep {
+            event!(Level::TRACE, "got manifest to keep: {:?}", manifest_path);
+            let dm = load_deployment_manifest(manifest_path, input_format).unwrap();
+            live.insert(dm.deployment_name);
+        }
+    }
+
+    let plan = workdir::plan_gc(&approot, &live).unwrap();
+    if plan.is_empty() {
+        event!(Level::INFO, "nothing to remove");
+        return;
+    }
+
+    for path in &plan {
+        println!("{}", path.display());
+    }
+
+    if dry_run {
+        event!(Level::INFO, "dry run - not removing anything");
+        return;
+    }
+
+    common::open_audit_log(matches, Some(&approot), SUBCOMMAND_NAME, None);
+    for path in &plan {
+        workdir::remove_deployment(path).unwrap();
+    }
+}
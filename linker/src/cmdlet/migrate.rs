@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use clap::{App, Arg, SubCommand};
+use failure::{format_err, Fallible};
+use tracing::{event, Level};
+use yscloud_config_model::migrate::{upgrade_document, DocumentKind, CURRENT_FORMAT_VERSION};
+
+use super::common;
+use crate::document::{self, Format};
+use crate::CARGO_PKG_VERSION;
+
+pub const SUBCOMMAND_NAME: &str = "migrate";
+
+pub fn get_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name(SUBCOMMAND_NAME)
+        .version(CARGO_PKG_VERSION)
+        .about("rewrite deployment manifests or templates in the newest format")
+        .arg(common::input_format())
+        .arg(
+            Arg::with_name("kind")
+                .long("kind")
+                .value_name("KIND")
+                .help("The kind of document being migrated")
+                .possible_values(&["deployment-manifest", "deployment-template"])
+                .required(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("check")
+                .long("check")
+                .help("Don't write anything, exit non-zero if a file is out of date"),
+        )
+        .arg(
+            Arg::with_name("files")
+                .value_name("FILE")
+                .help("The files to migrate in place")
+                .required(true)
+                .multiple(true)
+                .validator_os(|_| Ok(())),
+        )
+}
+
+pub fn main(matches: &clap::ArgMatches) {
+    let kind = match matches.value_of("kind").unwrap() {
+        "deployment-manifest" => DocumentKind::DeploymentManifest,
+        "deployment-template" => DocumentKind::DeploymentTemplate,
+        _ => unreachable!(),
+    };
+    let check = matches.is_present("check");
+    let input_format = common::get_input_format(matches);
+
+    let mut outdated = false;
+    for path in matches.values_of_os("files").unwrap() {
+        let path = Path::new(path);
+        match migrate_file(kind, path, input_format, check) {
+            Ok(Some(from_version)) => {
+                outdated = true;
+                if check {
+                    println!("{}: format {} is out of date", path.display(), from_version);
+                } else {
+                    println!(
+                        "{}: migrated format {} -> {}",
+                        path.display(),
+                        from_version,
+                        CURRENT_FORMAT_VERSION
+                    );
+                }
+            }
+            Ok(None) => event!(Level::INFO, "{} is up to date", path.display()),
+            Err(err) => {
+                event!(Level::ERROR, "migrating {} failed: {}", path.display(), err);
+                eprintln!("error: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if check && outdated {
+        std::process::exit(1);
+    }
+}
+
+/// Returns the format version the file was upgraded from, if it was outdated.
+/// Files are written back in the format they were read in.
+fn migrate_file(
+    kind: DocumentKind,
+    path: &Path,
+    format: Option<Format>,
+    check: bool,
+) -> Fallible<Option<u32>> {
+    let format = format.unwrap_or_else(|| Format::from_path(path));
+    let mut doc = document::read_value(path, Some(format))?;
+
+    let from_version = upgrade_document(kind, &mut doc)
+        .map_err(|err| format_err!("{}: {}", path.display(), err))?;
+    if from_version == CURRENT_FORMAT_VERSION {
+        return Ok(None);
+    }
+    if check {
+        return Ok(Some(from_version));
+    }
+
+    document::write_value(path, format, &doc)?;
+    Ok(Some(from_version))
+}
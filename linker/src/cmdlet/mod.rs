@@ -2,12 +2,22 @@ pub mod artifact_metastamp;
 
 pub mod create_release;
 
+pub mod diff;
+
+pub mod gc_workdirs;
+
 pub mod run;
 
 pub mod start_daemon;
 
+pub mod migrate;
+
+pub mod print_schema;
+
 pub mod publish_artifact;
 
+pub mod registry;
+
 pub mod unstable_setup_container;
 
 mod common;
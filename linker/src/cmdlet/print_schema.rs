@@ -0,0 +1,40 @@
+use std::io;
+
+use clap::{App, Arg, SubCommand};
+use yscloud_config_model::schema;
+
+use crate::CARGO_PKG_VERSION;
+
+pub const SUBCOMMAND_NAME: &str = "print-schema";
+
+pub fn get_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name(SUBCOMMAND_NAME)
+        .version(CARGO_PKG_VERSION)
+        .about("print the JSON Schema for one of the linker's documents")
+        .arg(
+            Arg::with_name("document")
+                .help("The kind of document")
+                .possible_values(&[
+                    "deployment-manifest",
+                    "deployment-template",
+                    "application-manifest",
+                    "release-lock",
+                ])
+                .required(true)
+                .takes_value(true),
+        )
+}
+
+pub fn main(matches: &clap::ArgMatches) {
+    let root = match matches.value_of("document").unwrap() {
+        "deployment-manifest" => schema::deployment_manifest(),
+        "deployment-template" => schema::deployment_template(),
+        "application-manifest" => schema::application_manifest(),
+        "release-lock" => schema::release_lock(),
+        _ => unreachable!(),
+    };
+
+    let stdout = io::stdout();
+    serde_json::to_writer_pretty(stdout.lock(), &root).unwrap();
+    println!();
+}
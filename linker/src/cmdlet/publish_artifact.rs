@@ -3,8 +3,10 @@ use std::path::Path;
 use clap::{App, Arg, SubCommand};
 use semver::Version;
 use tracing::{event, Level};
+use yscloud_config_model::ApplicationManifest;
 
 use super::common;
+use crate::document;
 use crate::publish_artifact::{start, Config};
 use crate::CARGO_PKG_VERSION;
 
@@ -22,6 +24,7 @@ pub fn get_subcommand() -> App<'static, 'static> {
         .version(CARGO_PKG_VERSION)
         .about("publish an artifact to a registry")
         .arg(common::registry())
+        .arg(common::input_format())
         .arg(
             Arg::with_name("package-id")
                 .long("package-id")
@@ -56,6 +59,15 @@ pub fn get_subcommand() -> App<'static, 'static> {
                 .required(true)
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("manifest")
+                .long("manifest")
+                .value_name("FILE")
+                .help("the application manifest of the artifact")
+                .required(true)
+                .takes_value(true)
+                .validator_os(|_| Ok(())),
+        )
 }
 
 pub fn main(matches: &clap::ArgMatches) {
@@ -77,11 +89,31 @@ pub fn main(matches: &clap::ArgMatches) {
     let host_triple = matches.value_of("host-triple").unwrap().to_string();
     event!(Level::TRACE, "got host-triple: {:?}", host_triple);
 
-    start(Config {
+    let manifest_path = matches.value_of_os("manifest").unwrap();
+    let manifest_path = Path::new(manifest_path).to_owned();
+    event!(Level::TRACE, "got manifest: {}", manifest_path.display());
+
+    let input_format = common::get_input_format(matches);
+    let manifest: ApplicationManifest = match document::read(&manifest_path, input_format) {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let result = start(Config {
         registry,
         package_id,
         version,
         artifact,
         host_triple,
+        manifest,
     });
+
+    if let Err(err) = result {
+        event!(Level::ERROR, "publish failed: {}", err);
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,108 @@
+use std::path::Path;
+
+use clap::{App, AppSettings, Arg, SubCommand};
+use tracing::{event, Level};
+
+use super::common;
+use crate::registry::{FileRegistry, RetentionPolicy};
+use crate::util::load_deployment_manifest;
+use crate::CARGO_PKG_VERSION;
+
+pub const SUBCOMMAND_NAME: &str = "registry";
+
+const GC_SUBCOMMAND_NAME: &str = "gc";
+
+fn count_validator(v: String) -> Result<(), String> {
+    match v.parse::<usize>() {
+        Ok(_) => Ok(()),
+        Err(err) => Err(format!("{}", err)),
+    }
+}
+
+pub fn get_subcommand() -> App<'static, 'static> {
+    SubCommand::with_name(SUBCOMMAND_NAME)
+        .version(CARGO_PKG_VERSION)
+        .about("registry maintenance")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name(GC_SUBCOMMAND_NAME)
+                .version(CARGO_PKG_VERSION)
+                .about("remove old versions and unreferenced artifacts from a registry")
+                .arg(common::registry())
+                .arg(common::input_format())
+                .arg(
+                    Arg::with_name("keep")
+                        .long("keep")
+                        .value_name("N")
+                        .help("the number of newest versions to keep for each package")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(count_validator),
+                )
+                .arg(
+                    Arg::with_name("pinned")
+                        .long("pinned")
+                        .value_name("FILE")
+                        .help("a deployment manifest whose component versions must be kept")
+                        .multiple(true)
+                        .number_of_values(1)
+                        .takes_value(true)
+                        .validator_os(|_| Ok(())),
+                )
+                .arg(
+                    Arg::with_name("dry-run")
+                        .long("dry-run")
+                        .help("list what would be removed without removing anything"),
+                ),
+        )
+}
+
+pub fn main(matches: &clap::ArgMatches) {
+    match matches.subcommand() {
+        (GC_SUBCOMMAND_NAME, Some(args)) => gc_main(args),
+        _ => panic!("bad argument parse"),
+    }
+}
+
+fn gc_main(matches: &clap::ArgMatches) {
+    let registry_path = matches.value_of_os("registry").unwrap();
+    let registry_path = Path::new(registry_path).to_owned();
+    event!(Level::TRACE, "got registry: {}", registry_path.display());
+
+    let keep_versions: usize = matches.value_of("keep").unwrap().parse().unwrap();
+    let dry_run = matches.is_present("dry-run");
+
+    let mut policy = RetentionPolicy::new(keep_versions);
+    let input_format = common::get_input_format(matches);
+    if let Some(pinned) = matches.values_of_os("pinned") {
+        for manifest_path in pinned {
+            event!(Level::TRACE, "got pinned manifest: {:?}", manifest_path);
+            let dm = load_deployment_manifest(manifest_path, input_format).unwrap();
+            policy.pin_deployment(&dm);
+        }
+    }
+
+    let registry = FileRegistry::new(&registry_path);
+    let plan = registry.plan_gc(&policy).unwrap();
+    if plan.is_empty() {
+        event!(Level::INFO, "nothing to remove");
+        return;
+    }
+
+    for (package_id, version) in &plan.remove_versions {
+        println!("version {} v{}", package_id, version);
+    }
+    for sha256 in &plan.remove_blobs {
+        println!("blob {}", sha256);
+    }
+    for path in &plan.remove_leftovers {
+        println!("leftover {}", path.display());
+    }
+
+    if dry_run {
+        event!(Level::INFO, "dry run - not removing anything");
+        return;
+    }
+
+    registry.apply_gc(&plan).unwrap();
+}
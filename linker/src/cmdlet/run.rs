@@ -1,24 +1,33 @@
 use std::collections::HashMap;
 use std::error::Error as StdError;
-use std::fs::File;
 use std::os::unix::io::AsRawFd;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::os::unix::net::UnixListener;
+use std::sync::Arc;
 
 use clap::{App, Arg, SubCommand};
 use tracing::{event, span, Level};
 use uuid::Uuid;
 
-use sockets::socketpair_raw;
+use sockets::{seqpacket_pair_raw, socketpair_raw};
 use owned_fd::OwnedFd;
 use yscloud_config_model::{
-    DeployedApplicationManifest, DeploymentManifest, FileDescriptorRemote, Protocol,
-    PublicServiceBinder, Sandbox, ServiceFileDirection, SideCarServiceInfo, SocketInfo, SocketMode,
-    ServiceId,
+    DeployedApplicationManifest, DeploymentManifest, FdProtocol, FileDescriptorRemote, Ksuid,
+    Protocol, PublicServiceBinder, Sandbox, ServiceFileDirection, SideCarServiceInfo, SocketInfo,
+    SocketMode, ServiceId, Workdir, INTRINSIC_PACKAGE_ID,
 };
 
 use super::common;
+use crate::adapter;
 use crate::artifact::{direct_load_artifact, find_artifact};
+use crate::audit::{self, Action};
+use crate::identity_signer::{self, CertificateAuthority, Identity, IdentitySigner};
+use crate::highwire;
+use crate::intrinsic;
+use crate::platform::ReloadFn;
+use crate::process;
+use crate::util::load_deployment_manifest;
+use crate::workdir;
 use crate::{
     bind_service, AppPreforkConfiguration, ExecExtras, ExecSomething, ServiceFileDescriptor,
 };
@@ -27,11 +36,21 @@ use crate::CARGO_PKG_VERSION;
 
 pub const SUBCOMMAND_NAME: &str = "run";
 
+const DEFAULT_DEV_PORT_OFFSET: &str = "10000";
+
+fn port_offset_validator(v: String) -> Result<(), String> {
+    match v.parse::<u16>() {
+        Ok(_) => Ok(()),
+        Err(err) => Err(format!("{}", err)),
+    }
+}
+
 pub fn get_subcommand() -> App<'static, 'static> {
     SubCommand::with_name(SUBCOMMAND_NAME)
         .version(CARGO_PKG_VERSION)
         .about("link and run a deployment")
-        .arg(common::approot())
+        .arg(common::approot().required_unless("dev"))
+        .arg(common::input_format())
         .arg(
             Arg::with_name("manifest")
                 .long("manifest")
@@ -42,6 +61,49 @@ pub fn get_subcommand() -> App<'static, 'static> {
         )
         .arg(common::artifacts())
         .arg(common::artifact_override())
+        .arg(common::override_reason())
+        .arg(common::audit_log())
+        .arg(
+            Arg::with_name("dev")
+                .long("dev")
+                .help("Local development mode: run without root, ignoring confinement"),
+        )
+        .arg(
+            Arg::with_name("dev-port-offset")
+                .long("dev-port-offset")
+                .value_name("N")
+                .help("Added to the port of every native port binder in development mode")
+                .requires("dev")
+                .default_value_if("dev", None, DEFAULT_DEV_PORT_OFFSET)
+                .takes_value(true)
+                .validator(port_offset_validator),
+        )
+        .arg(
+            Arg::with_name("cargo-target-dir")
+                .long("cargo-target-dir")
+                .value_name("DIR")
+                .help("In development mode, use binaries in DIR as artifact overrides, e.g. target/debug")
+                .requires("dev")
+                .takes_value(true)
+                .validator_os(|_| Ok(())),
+        )
+        .arg(
+            Arg::with_name("pass-env")
+                .long("pass-env")
+                .value_name("NAME")
+                .help("Pass this environment variable on to components, in addition to RUST_LOG, RUST_BACKTRACE and TZ")
+                .multiple(true)
+                .number_of_values(1)
+                .takes_value(true)
+                .validator(pass_env_validator),
+        )
+}
+
+fn pass_env_validator(v: String) -> Result<(), String> {
+    if process::RESERVED_ENVIRONMENT.contains(&&v[..]) {
+        return Err(format!("{} is set by the linker", v));
+    }
+    Ok(())
 }
 
 fn is_url(maybe_url: &str) -> bool {
@@ -49,8 +111,22 @@ fn is_url(maybe_url: &str) -> bool {
 }
 
 pub fn main(matches: &clap::ArgMatches) {
-    let approot = matches.value_of("approot").unwrap();
-    let approot = Path::new(approot).to_owned();
+    let dev = matches.is_present("dev");
+
+    // kept alive until the deployment exits, then removed.
+    let mut dev_approot = None;
+    let approot = match matches.value_of("approot") {
+        Some(approot) => Path::new(approot).to_owned(),
+        None => {
+            let tmp = tempfile::Builder::new()
+                .prefix("yscloud-dev-")
+                .tempdir()
+                .unwrap();
+            let approot = tmp.path().to_owned();
+            dev_approot = Some(tmp);
+            approot
+        }
+    };
     let artifacts = matches.value_of("artifacts").unwrap();
     let manifest_path = matches.value_of("manifest").unwrap();
 
@@ -78,19 +154,179 @@ pub fn main(matches: &clap::ArgMatches) {
         "starting",
     );
 
-    let rdr = File::open(&manifest_path).unwrap();
+    // a temporary approot's audit log goes with it.
+    common::open_audit_log(
+        matches,
+        Some(&approot),
+        SUBCOMMAND_NAME,
+        Some(Path::new(manifest_path)),
+    );
+
+    let input_format = common::get_input_format(matches);
     let mut target_deployment_manifest =
-        serde_json::from_reader::<_, DeploymentManifest>(rdr).unwrap();
+        load_deployment_manifest(&manifest_path, input_format).unwrap();
+
+    // the CA outlives deployments, so certificates stay verifiable across
+    // restarts of the linker.
+    let ca = CertificateAuthority::load_or_create(&approot.join(identity_signer::PACKAGE_ID))
+        .unwrap();
+    let signer = IdentitySigner::start(ca).unwrap();
+
+    let mut intrinsics = intrinsic::Registry::new();
+    intrinsics
+        .register(identity_signer::SERVICE_NAME, Arc::new(signer.clone()))
+        .unwrap();
+    highwire::register(&approot, &mut intrinsics).unwrap();
+    intrinsics.load_binaries(&approot).unwrap();
+    intrinsics.link(&mut target_deployment_manifest).unwrap();
+
+    if dev {
+        event!(
+            Level::WARN,
+            "DEVELOPMENT MODE - sandboxing is disabled and ports are remapped, workdirs in {}",
+            approot.display()
+        );
+
+        let port_offset: u16 = matches
+            .value_of("dev-port-offset")
+            .unwrap()
+            .parse()
+            .unwrap();
+        apply_dev_mode(&mut target_deployment_manifest, port_offset).unwrap();
+
+        if let Some(target_dir) = matches.value_of_os("cargo-target-dir") {
+            let target_dir = Path::new(target_dir);
+            for component in &target_deployment_manifest.components {
+                if overrides.contains_key(&component.package_id) {
+                    continue;
+                }
+                if let Some(path) = find_cargo_binary(target_dir, &component.package_id) {
+                    event!(
+                        Level::WARN,
+                        "development mode - using {} for {}",
+                        path.display(),
+                        component.package_id
+                    );
+                    overrides.insert(
+                        component.package_id.clone(),
+                        path.to_string_lossy().into_owned(),
+                    );
+                }
+            }
+        }
+    }
     target_deployment_manifest.path_overrides = overrides;
 
     if is_url(&artifacts) {
         // download artifacts somewhere
     }
 
-    let reified =
-        reify_service_connections(&target_deployment_manifest, artifacts, &approot).unwrap();
+    let mut pass_env: Vec<String> = process::PASS_ENVIRONMENT
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    if let Some(names) = matches.values_of_lossy("pass-env") {
+        pass_env.extend(names);
+    }
+
+    let trace_id = Ksuid::generate();
+    let span = span!(Level::INFO, "deployment", trace_id = %trace_id.fmt_base62());
+    let _guard = span.enter();
+
+    let mut reified = reify_service_connections(
+        &target_deployment_manifest,
+        trace_id,
+        artifacts,
+        &approot,
+        &pass_env,
+        &intrinsics,
+    )
+    .unwrap();
+    for exec in &mut reified {
+        signer.attach(exec).unwrap();
+    }
+
+    if dev {
+        let watch = target_deployment_manifest
+            .path_overrides
+            .iter()
+            .map(|(package_id, path)| (package_id.clone(), PathBuf::from(path)))
+            .collect();
+        crate::platform::run_reified_dev(reified, watch);
+    } else {
+        // SIGHUP sends changed extras to components with `live_config`.
+        let manifest_path = manifest_path.to_string();
+        let reload: ReloadFn = Box::new(move || {
+            load_deployment_manifest(&manifest_path, input_format).map_err(|err| err.to_string())
+        });
+        crate::platform::run_reified(reified, Some(reload), None);
+    }
+
+    drop(dev_approot);
+}
+
+/// Rewrites a deployment so it can be run by an unprivileged user: every
+/// component runs unconfined, and native ports are moved up by `port_offset`.
+fn apply_dev_mode(dm: &mut DeploymentManifest, port_offset: u16) -> Result<(), String> {
+    for component in &mut dm.components {
+        if component.sandbox != Sandbox::Unconfined {
+            event!(
+                Level::WARN,
+                "development mode - NOT applying {:?} to {}",
+                component.sandbox,
+                component.package_id
+            );
+            component.sandbox = Sandbox::Unconfined;
+        }
+        if let Workdir::Quota { .. } = component.workdir {
+            event!(
+                Level::WARN,
+                "development mode - NOT applying {:?} to {}",
+                component.workdir,
+                component.package_id
+            );
+            component.workdir = Workdir::Persistent;
+        }
+    }
+
+    for ps in &mut dm.public_services {
+        let service_name = &ps.service_id.service_name;
+        if let PublicServiceBinder::NativePortBinder(ref mut np) = ps.binder {
+            let port = np.port.checked_add(port_offset).ok_or_else(|| {
+                format!(
+                    "{}: port {} + offset {} is out of range",
+                    service_name, np.port, port_offset
+                )
+            })?;
+            event!(
+                Level::WARN,
+                "development mode - {} is on port {} instead of {}",
+                service_name,
+                port,
+                np.port
+            );
+            np.port = port;
+        }
+    }
+
+    Ok(())
+}
 
-    crate::platform::run_reified(reified);
+/// Cargo names binaries after the crate, so `org.yshi.sni_multiplexor` is
+/// looked for as `org.yshi.sni_multiplexor`, `sni_multiplexor` and
+/// `sni-multiplexor`.
+fn find_cargo_binary(target_dir: &Path, package_id: &str) -> Option<PathBuf> {
+    let short_name = package_id.rsplit('.').next().unwrap_or(package_id);
+    let candidates = [
+        package_id.to_string(),
+        short_name.to_string(),
+        short_name.replace('_', "-"),
+    ];
+
+    candidates
+        .iter()
+        .map(|name| target_dir.join(name))
+        .find(|path| path.is_file())
 }
 
 fn setup_named_socket_service_connection(
@@ -124,6 +360,7 @@ fn setup_named_socket_service_connection(
         direction: ServiceFileDirection::ServingListening,
         service_name: service_id.service_name.clone(),
         remote: FileDescriptorRemote::SideCarService(sidecar_info),
+        trace_id: None,
     });
 
     Ok(())
@@ -138,6 +375,15 @@ fn setup_preconnected_service_connection(
     remote_cfg: &DeployedApplicationManifest,
 ) -> Result<(), Box<dyn StdError>> {
     let (local_sock, remote_sock) = socketpair_raw()?;
+    let trace_id = Ksuid::generate();
+    event!(
+        Level::DEBUG,
+        trace_id = %trace_id.fmt_base62(),
+        "connecting {} to {} for {}",
+        local_cfg.package_id,
+        remote_cfg.package_id,
+        service_id.service_name
+    );
 
     let local_instance = instances.get_mut(local_instance_id).ok_or_else(|| {
         format!("internal error: unknown instance {:?}", local_instance_id)
@@ -153,6 +399,7 @@ fn setup_preconnected_service_connection(
         direction: ServiceFileDirection::Consuming,
         service_name: service_id.service_name.clone(),
         remote: FileDescriptorRemote::SideCarService(sidecar_info),
+        trace_id: Some(trace_id),
     });
 
     let remote_instance = instances.get_mut(remote_instance_id).ok_or_else(|| {
@@ -169,6 +416,7 @@ fn setup_preconnected_service_connection(
         direction: ServiceFileDirection::ServingConnected,
         service_name: service_id.service_name.clone(),
         remote: FileDescriptorRemote::SideCarService(sidecar_info),
+        trace_id: Some(trace_id),
     });
 
     Ok(())
@@ -176,8 +424,11 @@ fn setup_preconnected_service_connection(
 
 fn reify_service_connections(
     dm: &DeploymentManifest,
+    trace_id: Ksuid,
     artifact_path: &str,
     approot: &Path,
+    pass_env: &[String],
+    intrinsics: &intrinsic::Registry,
 ) -> Result<Vec<crate::ExecSomething>, Box<dyn StdError>> {
     let span = span!(
         Level::INFO,
@@ -191,6 +442,25 @@ fn reify_service_connections(
     let mut instance_by_package = HashMap::<&str, Uuid>::new();
 
     for component in &dm.components {
+        if instance_by_package.contains_key(&*component.package_id) {
+            return Err(format!(
+                "package {:?} appears more than once in the deployment manifest",
+                component.package_id
+            )
+            .into());
+        }
+        process::check(&component.package_id, &component.process)
+            .map_err(|e| e.to_string())?;
+        process::check_activation(component).map_err(|e| e.to_string())?;
+        adapter::check(component).map_err(|e| e.to_string())?;
+        if component.live_config && component.fd_protocol == FdProtocol::ListenFds {
+            return Err(format!(
+                "package {:?} can't take live configuration without --config-fd",
+                component.package_id
+            )
+            .into());
+        }
+
         let artifact = if let Some(path) = dm.path_overrides.get(&component.package_id) {
             event!(
                 Level::WARN,
@@ -198,8 +468,15 @@ fn reify_service_connections(
                 component.package_id,
                 path
             );
-            direct_load_artifact(&path)
-                .map_err(|e| format!("error opening {}: {}", path, e))?
+            let artifact = direct_load_artifact(&path);
+            audit::record(
+                Action::ArtifactOverride {
+                    package_id: &component.package_id,
+                    path,
+                },
+                &artifact,
+            );
+            artifact.map_err(|e| format!("error opening {}: {}", path, e))?
         } else {
             find_artifact(artifact_path, &component.package_id, &component.version)?
         };
@@ -208,9 +485,8 @@ fn reify_service_connections(
 
         let mut builder = ExecExtras::builder();
 
-        let mut workdir = approot.to_owned();
-        workdir.push(&dm.deployment_name);
-        workdir.push(&component.package_id);
+        let workdir = workdir::path(approot, &dm.deployment_name, &component.package_id);
+        workdir::prepare(&workdir, &component.workdir)?;
         builder.set_workdir(&workdir).unwrap();
 
         if let Sandbox::UnixUserConfinement(ref user, ref group) = component.sandbox {
@@ -224,16 +500,34 @@ fn reify_service_connections(
                 user,
                 group
             );
-            builder.set_user(user).unwrap();
-            builder.set_group(group).unwrap();
+            let confined = builder
+                .set_user(user)
+                .and_then(|()| builder.set_group(group));
+            audit::record(
+                Action::Confine {
+                    package_id: &component.package_id,
+                    user,
+                    group,
+                },
+                &confined,
+            );
+            confined.unwrap();
         }
 
+        let (control, component_control) = if component.live_config {
+            let (control, component_control) = seqpacket_pair_raw()?;
+            (Some(control), Some(component_control))
+        } else {
+            (None, None)
+        };
+
         instances.insert(
             instance_id,
             ExecSomething {
                 extras: builder.build(),
                 cfg: AppPreforkConfiguration {
                     deployment_name: dm.deployment_name.clone(),
+                    trace_id,
                     package_id: component.package_id.clone(),
                     artifact,
                     version: format!("{}", component.version),
@@ -241,6 +535,22 @@ fn reify_service_connections(
                     files: Default::default(),
                     extras: component.extras.clone(),
                     container_mounts: Default::default(),
+                    control: component_control,
+                    fd_protocol: component.fd_protocol,
+                    arguments: component.process.arguments.clone(),
+                    environment: process::environment(
+                        &component.process,
+                        pass_env,
+                        std::env::vars(),
+                    ),
+                },
+                control,
+                activation: component.activation.clone(),
+                identity_signer: None,
+                intrinsic: intrinsics.is_binary(&component.package_id),
+                ephemeral_workdir: match component.workdir {
+                    Workdir::Ephemeral => Some(workdir),
+                    _ => None,
                 },
             },
         );
@@ -273,7 +583,16 @@ fn reify_service_connections(
             "binding public service",
         );
 
-        let service_sock = bind_service(&ps.binder)?;
+        let service_sock = bind_service(&ps.binder);
+        audit::record(
+            Action::BindPublicService {
+                package_id: &ps.service_id.package_id,
+                service_name: &ps.service_id.service_name,
+                binder: &ps.binder,
+            },
+            &service_sock,
+        );
+        let service_sock = service_sock?;
         event!(Level::INFO,
             service_name = &ps.service_id.service_name[..],
             bind_target = ?ps.binder,
@@ -294,11 +613,28 @@ fn reify_service_connections(
                     PublicServiceBinder::WebServiceBinder(ref ws) => ws.flags.clone(),
                 },
             }),
+            trace_id: None,
         });
     }
 
     for (local_instance_id, local_cfg) in &instance_components {
         for ls in &local_cfg.required_local_services {
+            if ls.package_id == INTRINSIC_PACKAGE_ID {
+                let file = intrinsics.connect(
+                    &ls.service_name,
+                    Identity {
+                        deployment_name: dm.deployment_name.clone(),
+                        package_id: local_cfg.package_id.clone(),
+                        instance_id: *local_instance_id,
+                    },
+                )?;
+                let local_instance = instances.get_mut(local_instance_id).ok_or_else(|| {
+                    format!("internal error: unknown instance {:?}", local_instance_id)
+                })?;
+                local_instance.cfg.files.push(file);
+                continue;
+            }
+
             let remote_instance_id = instance_by_package
                 .get(&*ls.package_id)
                 .ok_or_else(|| format!("internal error: unknown package {:?}", ls.package_id))?;
@@ -340,5 +676,57 @@ fn reify_service_connections(
         }
     }
 
+    for (instance_id, component) in &instance_components {
+        if let Some(ref adapter) = component.adapter {
+            let workdir = workdir::path(approot, &dm.deployment_name, &component.package_id);
+            adapter::write(adapter, &component.sandbox, &instances[instance_id].cfg, &workdir)?;
+        }
+    }
+
     Ok(instances.into_iter().map(|(_, v)| v).collect())
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use yscloud_config_model::{DeploymentManifest, PublicServiceBinder, Sandbox};
+
+    use super::apply_dev_mode;
+
+    #[test]
+    fn test_apply_dev_mode() {
+        let mut dm: DeploymentManifest = serde_json::from_value(json!({
+            "format_version": 1,
+            "deployment_name": "example-deployment",
+            "public_services": [{
+                "service_id": {
+                    "package_id": "org.yshi.staticserver",
+                    "service_name": "org.yshi.staticserver.http"
+                },
+                "binder": {"native_port_binder": {"bind_address": "::", "port": 80}}
+            }],
+            "components": [{
+                "package_id": "org.yshi.staticserver",
+                "version": "1.0.5",
+                "provided_local_services": [],
+                "provided_remote_services": ["org.yshi.staticserver.http"],
+                "required_remote_services": [],
+                "required_local_services": [],
+                "sandbox": {"unix_user_confinement": ["www-data", "www-data"]},
+                "extras": {}
+            }]
+        }))
+        .unwrap();
+
+        let mut overflowing = dm.clone();
+        assert!(apply_dev_mode(&mut overflowing, 65500).is_err());
+
+        apply_dev_mode(&mut dm, 10000).unwrap();
+        assert_eq!(dm.components[0].sandbox, Sandbox::Unconfined);
+        match dm.public_services[0].binder {
+            PublicServiceBinder::NativePortBinder(ref np) => assert_eq!(np.port, 10080),
+            ref other => panic!("unexpected {:?}", other),
+        }
+    }
+}
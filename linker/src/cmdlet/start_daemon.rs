@@ -5,7 +5,7 @@ use clap::{App, Arg, SubCommand};
 use tracing::{event, Level};
 
 use super::common;
-use crate::start_daemon::{start, Config};
+use crate::start_daemon::{start, Config, MANIFEST_FILE};
 use crate::CARGO_PKG_VERSION;
 
 pub const SUBCOMMAND_NAME: &str = "start-daemon";
@@ -25,7 +25,17 @@ pub fn get_subcommand() -> App<'static, 'static> {
                 .takes_value(true)
                 .validator_os(|_| Ok(())),
         )
+        .arg(
+            Arg::with_name("metrics-socket")
+                .long("metrics-socket")
+                .value_name("PATH")
+                .help("path to bind a unix socket serving Prometheus metrics")
+                .takes_value(true)
+                .validator_os(|_| Ok(())),
+        )
         .arg(common::artifact_override())
+        .arg(common::override_reason())
+        .arg(common::audit_log())
 }
 
 pub fn main(matches: &clap::ArgMatches) {
@@ -44,6 +54,10 @@ pub fn main(matches: &clap::ArgMatches) {
         control_socket.display()
     );
 
+    let metrics_socket = matches
+        .value_of_os("metrics-socket")
+        .map(|p| Path::new(p).to_owned());
+
     let mut overrides: HashMap<String, String> = HashMap::new();
     if let Some(override_args) = matches.values_of_lossy("artifact-override") {
         for arg in override_args {
@@ -60,10 +74,18 @@ pub fn main(matches: &clap::ArgMatches) {
         );
     }
 
+    common::open_audit_log(
+        matches,
+        Some(&approot),
+        SUBCOMMAND_NAME,
+        Some(Path::new(MANIFEST_FILE)),
+    );
+
     start(Config {
         approot,
         artifacts,
         control_socket,
+        metrics_socket,
         overrides,
     });
 }
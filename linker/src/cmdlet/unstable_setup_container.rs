@@ -8,6 +8,7 @@ use clap::{App, Arg, SubCommand};
 use tracing::{event, Level};
 
 use super::common;
+use crate::audit::{self, Action};
 use crate::start_daemon::{start, Config};
 use crate::CARGO_PKG_VERSION;
 
@@ -45,6 +46,11 @@ pub fn get_subcommand() -> App<'static, 'static> {
                 .validator_os(|_| Ok(())),
         )
         .arg(common::artifact_override())
+        .arg(common::override_reason())
+        .arg(
+            common::audit_log()
+                .help("Append a record of privileged actions to PATH (default: <persist>/audit.jsonl)"),
+        )
 }
 
 #[cfg(not(target_os = "linux"))]
@@ -64,14 +70,25 @@ pub fn main(matches: &clap::ArgMatches) {
     let persist = matches.value_of_os("persist").unwrap();
     let persist = Path::new(persist).to_owned();
 
-    mount_nix_squashfs(&workdir, &ContainerConfig {
-        persistence_path: persist,
-        code_archive_path: image,
+    common::open_audit_log(matches, Some(&persist), SUBCOMMAND_NAME, None);
+
+    let mounted = mount_nix_squashfs(&workdir, &ContainerConfig {
+        persistence_path: persist.clone(),
+        code_archive_path: image.clone(),
         ephemeral_storage_kilobytes: 0,
         enable_proc: false,
         enable_dev: false,
         extra_mounts: Vec::new(),
-    }).unwrap();
+    });
+    audit::record(
+        Action::MountSquashfs {
+            image: &image,
+            workdir: &workdir,
+            persist: &persist,
+        },
+        &mounted,
+    );
+    mounted.unwrap();
 
     Command::new("/nix/entrypoint")
         .args(&["--nofork", "--nopid", "--runasroot", "--config", "/persist/inspircd.config"])
@@ -0,0 +1,251 @@
+//! Reads the documents handed to the linker by people: deployment templates,
+//! overlays, variables, deployment manifests and application manifests.
+//!
+//! JSON, TOML and YAML are accepted.  The format is chosen from the file
+//! extension unless one is given explicitly, and anything unrecognised is
+//! read as JSON.  Documents the linker writes itself stay JSON.
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use failure::{Fail, Fallible};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tempfile::NamedTempFile;
+
+pub const FORMAT_NAMES: &[&str] = &["json", "toml", "yaml"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Format {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Format::Toml,
+            Some("yaml") | Some("yml") => Format::Yaml,
+            _ => Format::Json,
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "json" => Ok(Format::Json),
+            "toml" => Ok(Format::Toml),
+            "yaml" => Ok(Format::Yaml),
+            _ => Err(format!("unknown format {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Format::Json => write!(f, "JSON"),
+            Format::Toml => write!(f, "TOML"),
+            Format::Yaml => write!(f, "YAML"),
+        }
+    }
+}
+
+#[derive(Debug, Fail)]
+pub struct DocumentFailure {
+    path: PathBuf,
+    format: Format,
+    // the parser's message, which carries the line and column.
+    message: String,
+}
+
+impl fmt::Display for DocumentFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: invalid {}: {}",
+            self.path.display(),
+            self.format,
+            self.message
+        )
+    }
+}
+
+#[derive(Debug, Fail)]
+pub struct ReadFailure {
+    path: PathBuf,
+    #[cause]
+    cause: std::io::Error,
+}
+
+impl fmt::Display for ReadFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.cause)
+    }
+}
+
+/// Reads and deserializes `path`, in `format` if given or else in the format
+/// implied by its extension.
+pub fn read<T: DeserializeOwned>(path: &Path, format: Option<Format>) -> Fallible<T> {
+    let format = format.unwrap_or_else(|| Format::from_path(path));
+    let text = fs::read_to_string(path).map_err(|cause| ReadFailure {
+        path: path.to_owned(),
+        cause,
+    })?;
+
+    let result = match format {
+        Format::Json => serde_json::from_str(&text).map_err(|e| e.to_string()),
+        Format::Toml => toml::from_str(&text).map_err(|e| e.to_string()),
+        Format::Yaml => serde_yaml::from_str(&text).map_err(|e| e.to_string()),
+    };
+
+    result.map_err(|message| {
+        DocumentFailure {
+            path: path.to_owned(),
+            format,
+            message,
+        }
+        .into()
+    })
+}
+
+pub fn read_value(path: &Path, format: Option<Format>) -> Fallible<Value> {
+    read(path, format)
+}
+
+/// Atomically replaces `path` with `value`, serialized in `format`.
+pub fn write_value(path: &Path, format: Format, value: &Value) -> Fallible<()> {
+    let mut body = match format {
+        Format::Json => serde_json::to_string_pretty(value)?,
+        Format::Toml => toml::to_string_pretty(&to_toml(value)?)?,
+        Format::Yaml => serde_yaml::to_string(value)?,
+    };
+    if !body.ends_with('\n') {
+        body.push('\n');
+    }
+
+    let parent = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+
+    let mut staged = NamedTempFile::new_in(parent)?;
+    staged.write_all(body.as_bytes())?;
+    staged.as_file().sync_all()?;
+    staged.persist(path).map_err(|e| e.error)?;
+
+    Ok(())
+}
+
+/// `value` as TOML, which has no null: null members are left out, as serde
+/// leaves out a `None` field, and a null anywhere else is an error.  Going
+/// through `toml::Value` also puts each table's scalars before its subtables.
+fn to_toml(value: &Value) -> Fallible<toml::Value> {
+    Ok(match *value {
+        Value::Null => return Err(failure::err_msg("TOML can't represent null")),
+        Value::Bool(b) => toml::Value::Boolean(b),
+        Value::Number(ref n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => toml::Value::Integer(i),
+            (None, Some(f)) if !n.is_u64() => toml::Value::Float(f),
+            _ => return Err(failure::err_msg(format!("{} is out of range for TOML", n))),
+        },
+        Value::String(ref s) => toml::Value::String(s.clone()),
+        Value::Array(ref items) => {
+            toml::Value::Array(items.iter().map(to_toml).collect::<Fallible<_>>()?)
+        }
+        Value::Object(ref members) => {
+            let mut table = toml::value::Table::new();
+            for (key, member) in members {
+                if !member.is_null() {
+                    table.insert(key.clone(), to_toml(member)?);
+                }
+            }
+            toml::Value::Table(table)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use super::{read_value, write_value, Format};
+
+    #[test]
+    fn test_read_formats() {
+        let dir = tempdir().unwrap();
+        let expected = json!({
+            "deployment_name": "yshi.org",
+            "configuration": {"org.yshi.sfshost": {"vhosts": ["yshi.org"]}}
+        });
+
+        let toml_path = dir.path().join("template.toml");
+        fs::write(
+            &toml_path,
+            "# production\n\
+             deployment_name = \"yshi.org\"\n\
+             [configuration.\"org.yshi.sfshost\"]\n\
+             vhosts = [\"yshi.org\"]\n",
+        )
+        .unwrap();
+        assert_eq!(read_value(&toml_path, None).unwrap(), expected);
+
+        let yaml_path = dir.path().join("template.yml");
+        fs::write(
+            &yaml_path,
+            "deployment_name: yshi.org\n\
+             configuration:\n  org.yshi.sfshost:\n    vhosts: [yshi.org]\n",
+        )
+        .unwrap();
+        assert_eq!(read_value(&yaml_path, None).unwrap(), expected);
+
+        // an explicit format wins over the extension.
+        let json_path = dir.path().join("template.yaml");
+        fs::write(&json_path, "{\"deployment_name\": \"yshi.org\",}").unwrap();
+        let err = read_value(&json_path, Some(Format::Json)).unwrap_err();
+        assert_eq!(
+            format!("{}", err),
+            format!(
+                "{}: invalid JSON: trailing comma at line 1 column 32",
+                json_path.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_write_toml() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("deployment.toml");
+        let value = json!({
+            "deployment_name": "yshi.org",
+            "components": [{
+                "package_id": "org.yshi.sfshost",
+                "extras": {"vhosts": ["yshi.org"], "tls": {"cert": "yshi.crt"}},
+                "adapter": null,
+                "live_config": true
+            }],
+            "path_overrides": {},
+            "format_version": 1
+        });
+        write_value(&path, Format::Toml, &value).unwrap();
+
+        let mut expected = value;
+        expected["components"][0]
+            .as_object_mut()
+            .unwrap()
+            .remove("adapter");
+        assert_eq!(read_value(&path, None).unwrap(), expected);
+
+        let err = write_value(&path, Format::Toml, &json!({"ports": [80, null]})).unwrap_err();
+        assert_eq!(format!("{}", err), "TOML can't represent null");
+    }
+}
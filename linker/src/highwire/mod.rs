@@ -0,0 +1,170 @@
+//! Highwire: connections to hosted services outside the deployment, made by
+//! the linker on a component's behalf so the component never holds their
+//! credentials.  Components require `org.yshi.highwire.postgres.<cluster>`
+//! and get a socket speaking the PostgreSQL protocol, already authenticated
+//! as whatever `<approot>/highwire.json` grants their package.
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::io::FromRawFd;
+use std::path::Path;
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+use failure::Fallible;
+use futures::future;
+use serde::Deserialize;
+use sockets::socketpair_raw;
+use tokio::runtime::{Handle, Runtime};
+use tracing::{event, span, Instrument, Level};
+use uuid::Uuid;
+use yscloud_config_model::{FileDescriptorRemote, Ksuid, ServiceFileDirection, SideCarServiceInfo};
+
+use crate::document;
+use crate::identity_signer::Identity;
+use crate::intrinsic::{self, InProcessService};
+use crate::{ServiceFileDescriptor, CARGO_PKG_VERSION};
+
+mod postgres;
+
+pub const PACKAGE_ID: &str = "yscloud.highwire";
+
+pub const HIGHWIRE_FILE: &str = "highwire.json";
+
+/// Followed by the cluster name.
+pub const POSTGRES_SERVICE_PREFIX: &str = "org.yshi.highwire.postgres.";
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct HighwireConfig {
+    #[serde(default)]
+    pub postgres: BTreeMap<String, PostgresCluster>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PostgresCluster {
+    /// `host:port`, or the path of a unix socket.
+    pub address: String,
+    /// by package id
+    pub grants: BTreeMap<String, PostgresGrant>,
+}
+
+/// What a package is connected as.
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct PostgresGrant {
+    pub user: String,
+    pub password: String,
+    pub database: String,
+}
+
+/// Registers a service for each cluster in `approot`'s highwire file, if
+/// it has one, served from a runtime thread of their own.
+pub fn register(approot: &Path, intrinsics: &mut intrinsic::Registry) -> Fallible<()> {
+    let path = approot.join(HIGHWIRE_FILE);
+    if !path.exists() {
+        return Ok(());
+    }
+    if fs::metadata(&path)?.permissions().mode() & 0o077 != 0 {
+        event!(
+            Level::WARN,
+            "{} holds credentials but is readable by others",
+            path.display()
+        );
+    }
+    let config: HighwireConfig = document::read(&path, None)?;
+    if config.postgres.is_empty() {
+        return Ok(());
+    }
+
+    let runtime = start_runtime()?;
+    let instance_id = Uuid::new_v4();
+    for (name, cluster) in config.postgres {
+        let service_name = format!("{}{}", POSTGRES_SERVICE_PREFIX, name);
+        let service = PostgresService {
+            service_name: service_name.clone(),
+            cluster: Arc::new(cluster),
+            instance_id,
+            runtime: runtime.clone(),
+        };
+        intrinsics.register(&service_name, Arc::new(service))?;
+    }
+    Ok(())
+}
+
+fn start_runtime() -> io::Result<Handle> {
+    let (handle_tx, handle_rx) = mpsc::channel();
+    thread::Builder::new()
+        .name(PACKAGE_ID.to_string())
+        .spawn(move || {
+            let mut runtime = Runtime::new().unwrap();
+            handle_tx.send(runtime.handle().clone()).unwrap();
+            runtime.block_on(future::pending::<()>());
+        })?;
+    handle_rx
+        .recv()
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
+}
+
+struct PostgresService {
+    service_name: String,
+    cluster: Arc<PostgresCluster>,
+    // stands in for a component instance in the app configuration.
+    instance_id: Uuid,
+    runtime: Handle,
+}
+
+impl InProcessService for PostgresService {
+    /// A connection carrying one PostgreSQL session after another.  The
+    /// consumer's startup message is answered by us; its user and database
+    /// are replaced by those of its grant.
+    fn connect(&self, consumer: Identity) -> io::Result<ServiceFileDescriptor> {
+        let grant = self
+            .cluster
+            .grants
+            .get(&consumer.package_id)
+            .cloned()
+            .ok_or_else(|| {
+                let msg = format!(
+                    "{} has no grant for {}",
+                    consumer.package_id, self.service_name
+                );
+                io::Error::new(io::ErrorKind::PermissionDenied, msg)
+            })?;
+
+        let (ours, theirs) = socketpair_raw()?;
+        let stream = unsafe { std::os::unix::net::UnixStream::from_raw_fd(ours.into_raw_fd()) };
+        stream.set_nonblocking(true)?;
+        let cluster = Arc::clone(&self.cluster);
+        let package_id = consumer.package_id;
+        let trace_id = Ksuid::generate();
+        let span = span!(Level::INFO, "connection", trace_id = %trace_id.fmt_base62());
+        let serve = async move {
+            let stream = match tokio::net::UnixStream::from_std(stream) {
+                Ok(stream) => stream,
+                Err(err) => {
+                    event!(Level::WARN, "highwire for {}: {}", package_id, err);
+                    return;
+                }
+            };
+            if let Err(err) = postgres::serve(stream, &cluster.address, &grant).await {
+                event!(Level::WARN, "highwire for {}: {}", package_id, err);
+            }
+        };
+        self.runtime.spawn(serve.instrument(span));
+
+        Ok(ServiceFileDescriptor {
+            file: theirs,
+            direction: ServiceFileDirection::Consuming,
+            service_name: self.service_name.clone(),
+            remote: FileDescriptorRemote::SideCarService(SideCarServiceInfo {
+                instance_id: self.instance_id,
+                package_id: PACKAGE_ID.to_string(),
+                version: CARGO_PKG_VERSION.parse().unwrap(),
+            }),
+            trace_id: Some(trace_id),
+        })
+    }
+}
@@ -0,0 +1,759 @@
+//! Just enough of the PostgreSQL frontend/backend protocol to authenticate
+//! on a component's behalf: the startup handshake on both sides, and
+//! cleartext, MD5 and SCRAM-SHA-256 authentication upstream.  Everything
+//! after that is relayed as it is.  Upstream connections aren't encrypted,
+//! so over TCP only SCRAM-SHA-256 is used, which never sends the password.
+use std::collections::BTreeMap;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::future::{self, Either};
+use openssl::base64;
+use openssl::hash::{hash, MessageDigest};
+use openssl::memcmp;
+use openssl::pkcs5::pbkdf2_hmac;
+use openssl::pkey::PKey;
+use openssl::rand::rand_bytes;
+use openssl::sign::Signer;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpStream, UnixStream};
+use tracing::{event, Level};
+
+use super::PostgresGrant;
+use crate::util::hexify;
+
+const PROTOCOL_VERSION_3: i32 = 196_608;
+const SSL_REQUEST: i32 = 80_877_103;
+const GSSENC_REQUEST: i32 = 80_877_104;
+const CANCEL_REQUEST: i32 = 80_877_102;
+
+// the server's limit on a startup packet.
+const MAX_STARTUP_LENGTH: usize = 10_000;
+// the server's limit on any other message.
+const MAX_MESSAGE_LENGTH: usize = 1 << 30;
+
+const AUTH_OK: i32 = 0;
+const AUTH_CLEARTEXT: i32 = 3;
+const AUTH_MD5: i32 = 5;
+const AUTH_SASL: i32 = 10;
+const AUTH_SASL_CONTINUE: i32 = 11;
+const AUTH_SASL_FINAL: i32 = 12;
+
+const SCRAM_SHA_256: &str = "SCRAM-SHA-256";
+
+fn protocol_error(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+struct Message {
+    tag: u8,
+    body: Vec<u8>,
+}
+
+impl Message {
+    fn new(tag: u8, body: Vec<u8>) -> Message {
+        Message { tag, body }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(5 + self.body.len());
+        out.push(self.tag);
+        out.extend_from_slice(&(self.body.len() as i32 + 4).to_be_bytes());
+        out.extend_from_slice(&self.body);
+        out
+    }
+
+    /// An `ErrorResponse` ending the session.
+    fn fatal(code: &str, msg: &str) -> Message {
+        let mut body = Vec::new();
+        for (field, value) in &[(b'S', "FATAL"), (b'V', "FATAL"), (b'C', code), (b'M', msg)] {
+            body.push(*field);
+            put_cstr(&mut body, value);
+        }
+        body.push(0);
+        Message::new(b'E', body)
+    }
+
+    fn auth_code(&self) -> io::Result<i32> {
+        if self.body.len() < 4 {
+            return Err(protocol_error("short authentication message"));
+        }
+        Ok(i32::from_be_bytes([
+            self.body[0],
+            self.body[1],
+            self.body[2],
+            self.body[3],
+        ]))
+    }
+}
+
+fn put_cstr(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(s.as_bytes());
+    out.push(0);
+}
+
+/// `None` at a clean end of the stream.
+async fn read_message<R: AsyncRead + Unpin>(r: &mut R) -> io::Result<Option<Message>> {
+    let mut tag = [0; 1];
+    if r.read(&mut tag).await? == 0 {
+        return Ok(None);
+    }
+    let mut length = [0; 4];
+    r.read_exact(&mut length).await?;
+    let length = i32::from_be_bytes(length) as usize;
+    if length < 4 || MAX_MESSAGE_LENGTH < length {
+        return Err(protocol_error("bad message length"));
+    }
+    let mut body = vec![0; length - 4];
+    r.read_exact(&mut body).await?;
+    Ok(Some(Message { tag: tag[0], body }))
+}
+
+/// The first packet of a session, which has no tag.  `None` at a clean end
+/// of the stream.
+async fn read_startup<R: AsyncRead + Unpin>(r: &mut R) -> io::Result<Option<(i32, Vec<u8>)>> {
+    let mut length = [0; 4];
+    match r.read_exact(&mut length).await {
+        Ok(_) => (),
+        Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+    let length = i32::from_be_bytes(length) as usize;
+    if length < 8 || MAX_STARTUP_LENGTH < length {
+        return Err(protocol_error("bad startup packet length"));
+    }
+    let mut body = vec![0; length - 4];
+    r.read_exact(&mut body).await?;
+    let code = i32::from_be_bytes([body[0], body[1], body[2], body[3]]);
+    Ok(Some((code, body.split_off(4))))
+}
+
+fn parse_parameters(body: &[u8]) -> io::Result<BTreeMap<String, String>> {
+    let mut fields = body.split(|b| *b == 0).map(String::from_utf8_lossy);
+    let mut parameters = BTreeMap::new();
+    loop {
+        let key = fields
+            .next()
+            .ok_or_else(|| protocol_error("unterminated startup packet"))?;
+        if key.is_empty() {
+            return Ok(parameters);
+        }
+        let value = fields
+            .next()
+            .ok_or_else(|| protocol_error("unterminated startup packet"))?;
+        parameters.insert(key.into_owned(), value.into_owned());
+    }
+}
+
+fn encode_startup(parameters: &BTreeMap<String, String>) -> Vec<u8> {
+    let mut body = PROTOCOL_VERSION_3.to_be_bytes().to_vec();
+    for (key, value) in parameters {
+        put_cstr(&mut body, key);
+        put_cstr(&mut body, value);
+    }
+    body.push(0);
+    let mut out = (body.len() as i32 + 4).to_be_bytes().to_vec();
+    out.extend_from_slice(&body);
+    out
+}
+
+/// Serves sessions on `client` until it goes away, connecting each to the
+/// server at `address` as `grant`.  The component can't get another
+/// connection, so losing the server only ends the session.
+pub async fn serve(mut client: UnixStream, address: &str, grant: &PostgresGrant) -> io::Result<()> {
+    loop {
+        let parameters = match read_startup(&mut client).await? {
+            None => return Ok(()),
+            // no encryption on a socket only we hold the other end of.
+            Some((SSL_REQUEST, _)) | Some((GSSENC_REQUEST, _)) => {
+                client.write_all(b"N").await?;
+                continue;
+            }
+            // cancelling needs a connection of its own, which the component
+            // can't make.
+            Some((CANCEL_REQUEST, _)) => continue,
+            Some((PROTOCOL_VERSION_3, body)) => parse_parameters(&body)?,
+            Some((_, _)) => {
+                let fatal = Message::fatal("0A000", "unsupported frontend protocol");
+                client.write_all(&fatal.encode()).await?;
+                return Ok(());
+            }
+        };
+
+        let mut upstream = match Upstream::connect(address).await {
+            Ok(upstream) => upstream,
+            Err(err) => {
+                let msg = format!("highwire could not connect upstream: {}", err);
+                client
+                    .write_all(&Message::fatal("08001", &msg).encode())
+                    .await?;
+                continue;
+            }
+        };
+        match authenticate(&mut client, &mut upstream, parameters, grant).await {
+            Ok(true) => (),
+            Ok(false) => continue,
+            // the details are for the linker's log, not the component.
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                event!(Level::WARN, "highwire login to {} failed: {}", address, err);
+                let fatal = Message::fatal("28000", "highwire could not log in upstream");
+                client.write_all(&fatal.encode()).await?;
+                continue;
+            }
+            Err(err) => {
+                event!(Level::WARN, "highwire login to {} failed: {}", address, err);
+                let fatal = Message::fatal("08006", "highwire lost the upstream connection");
+                client.write_all(&fatal.encode()).await?;
+                continue;
+            }
+        }
+        match relay(&mut client, upstream).await {
+            Ok(true) => (),
+            Ok(false) => return Ok(()),
+            Err(err) => {
+                event!(
+                    Level::WARN,
+                    "highwire session with {} ended: {}",
+                    address,
+                    err
+                );
+                let fatal = Message::fatal("08006", "highwire lost the upstream connection");
+                client.write_all(&fatal.encode()).await?;
+            }
+        }
+    }
+}
+
+/// Logs into `upstream` as `grant`, with the client's other startup
+/// parameters.  Returns whether the session can go on; if it can't, the
+/// client has been told why.
+async fn authenticate(
+    client: &mut UnixStream,
+    upstream: &mut Upstream,
+    mut parameters: BTreeMap<String, String>,
+    grant: &PostgresGrant,
+) -> io::Result<bool> {
+    parameters.insert("user".into(), grant.user.clone());
+    parameters.insert("database".into(), grant.database.clone());
+    // no grant extends to replication.
+    parameters.remove("replication");
+    upstream.write_all(&encode_startup(&parameters)).await?;
+
+    let mut scram = None;
+    loop {
+        let msg = match read_message(upstream).await? {
+            Some(msg) => msg,
+            None => return Err(protocol_error("upstream closed during authentication")),
+        };
+        match msg.tag {
+            // the server's errors don't give the credentials away.
+            b'E' => {
+                client.write_all(&msg.encode()).await?;
+                return Ok(false);
+            }
+            b'R' => (),
+            // e.g. NegotiateProtocolVersion, which is the client's business.
+            _ => {
+                client.write_all(&msg.encode()).await?;
+                continue;
+            }
+        }
+
+        let response = match msg.auth_code()? {
+            AUTH_OK => break,
+            AUTH_CLEARTEXT | AUTH_MD5 if !upstream.is_unix() => {
+                return Err(protocol_error(
+                    "upstream asked for a cleartext or MD5 password over TCP",
+                ));
+            }
+            AUTH_CLEARTEXT => {
+                let mut body = Vec::new();
+                put_cstr(&mut body, &grant.password);
+                body
+            }
+            AUTH_MD5 => {
+                let salt = msg
+                    .body
+                    .get(4..8)
+                    .ok_or_else(|| protocol_error("short MD5 salt"))?;
+                let mut body = Vec::new();
+                put_cstr(
+                    &mut body,
+                    &md5_password(&grant.user, &grant.password, salt)?,
+                );
+                body
+            }
+            AUTH_SASL => {
+                let mut mechanisms = msg.body[4..].split(|b| *b == 0);
+                if !mechanisms.any(|m| m == SCRAM_SHA_256.as_bytes()) {
+                    return Err(protocol_error("no supported SASL mechanism offered"));
+                }
+                let client_first = ScramClient::new("", &grant.password)?;
+                let initial = client_first.client_first();
+                let mut body = Vec::new();
+                put_cstr(&mut body, SCRAM_SHA_256);
+                body.extend_from_slice(&(initial.len() as i32).to_be_bytes());
+                body.extend_from_slice(initial.as_bytes());
+                scram = Some(client_first);
+                body
+            }
+            AUTH_SASL_CONTINUE => {
+                let scram = scram
+                    .as_mut()
+                    .ok_or_else(|| protocol_error("unexpected SASL continuation"))?;
+                let server_first = String::from_utf8_lossy(&msg.body[4..]);
+                scram.client_final(&server_first)?.into_bytes()
+            }
+            AUTH_SASL_FINAL => {
+                let scram = scram
+                    .as_ref()
+                    .ok_or_else(|| protocol_error("unexpected SASL outcome"))?;
+                scram.verify_server_final(&String::from_utf8_lossy(&msg.body[4..]))?;
+                continue;
+            }
+            code => {
+                let msg = format!("unsupported authentication method {}", code);
+                return Err(protocol_error(&msg));
+            }
+        };
+        upstream
+            .write_all(&Message::new(b'p', response).encode())
+            .await?;
+    }
+
+    let ok = Message::new(b'R', AUTH_OK.to_be_bytes().to_vec());
+    client.write_all(&ok.encode()).await?;
+    Ok(true)
+}
+
+/// Relays a logged in session until the client ends it with `Terminate`.
+/// Returns whether the client is still there for another one; the server
+/// ending the session is an error.
+async fn relay(client: &mut UnixStream, mut upstream: Upstream) -> io::Result<bool> {
+    let (mut client_rx, mut client_tx) = client.split();
+    let (mut upstream_rx, mut upstream_tx) = tokio::io::split(&mut upstream);
+
+    // messages are read whole on the way up, so `Terminate` is seen.
+    let to_upstream = async {
+        loop {
+            let msg = match read_message(&mut client_rx).await? {
+                Some(msg) => msg,
+                None => return Ok::<_, io::Error>(false),
+            };
+            upstream_tx.write_all(&msg.encode()).await?;
+            if msg.tag == b'X' {
+                return Ok(true);
+            }
+        }
+    };
+    let to_client = tokio::io::copy(&mut upstream_rx, &mut client_tx);
+    futures::pin_mut!(to_upstream, to_client);
+
+    match future::select(to_upstream, to_client).await {
+        Either::Left((Ok(true), to_client)) => {
+            to_client.await?;
+            Ok(true)
+        }
+        Either::Left((result, _)) => result,
+        // the server ended the session, which leaves the client mid-way.
+        Either::Right((result, _)) => result.and_then(|_| {
+            Err(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "upstream ended the session",
+            ))
+        }),
+    }
+}
+
+fn md5_password(user: &str, password: &str, salt: &[u8]) -> io::Result<String> {
+    let hex = |data: &[u8]| -> io::Result<String> {
+        let digest = hash(MessageDigest::md5(), data)?;
+        let mut scratch = [0; 32];
+        Ok(hexify(&mut scratch[..], &digest).unwrap().to_string())
+    };
+    let inner = hex(format!("{}{}", password, user).as_bytes())?;
+    let mut outer = inner.into_bytes();
+    outer.extend_from_slice(salt);
+    Ok(format!("md5{}", hex(&outer)?))
+}
+
+/// The client side of SCRAM-SHA-256 (RFC 5802, RFC 7677), without channel
+/// binding.
+struct ScramClient {
+    password: String,
+    client_first_bare: String,
+    nonce: String,
+    // set once the server's first message is in.
+    auth_message: Option<String>,
+    salted_password: Vec<u8>,
+}
+
+impl ScramClient {
+    fn new(user: &str, password: &str) -> io::Result<ScramClient> {
+        let mut nonce = [0; 18];
+        rand_bytes(&mut nonce)?;
+        Ok(ScramClient::with_nonce(
+            user,
+            password,
+            &base64::encode_block(&nonce),
+        ))
+    }
+
+    fn with_nonce(user: &str, password: &str, nonce: &str) -> ScramClient {
+        ScramClient {
+            password: password.to_string(),
+            client_first_bare: format!("n={},r={}", user, nonce),
+            nonce: nonce.to_string(),
+            auth_message: None,
+            salted_password: Vec::new(),
+        }
+    }
+
+    fn client_first(&self) -> String {
+        format!("n,,{}", self.client_first_bare)
+    }
+
+    fn client_final(&mut self, server_first: &str) -> io::Result<String> {
+        let mut nonce = None;
+        let mut salt = None;
+        let mut iterations = None;
+        for attr in server_first.split(',') {
+            match attr.split_at(attr.find('=').map_or(0, |i| i + 1)) {
+                ("r=", value) => nonce = Some(value),
+                ("s=", value) => salt = Some(base64::decode_block(value)?),
+                ("i=", value) => iterations = value.parse::<usize>().ok(),
+                _ => (),
+            }
+        }
+        let (nonce, salt, iterations) = match (nonce, salt, iterations) {
+            (Some(n), Some(s), Some(i)) if n.starts_with(&self.nonce) => (n, s, i),
+            _ => return Err(protocol_error("bad SCRAM server-first-message")),
+        };
+
+        let mut salted_password = vec![0; 32];
+        pbkdf2_hmac(
+            self.password.as_bytes(),
+            &salt,
+            iterations,
+            MessageDigest::sha256(),
+            &mut salted_password,
+        )?;
+        let without_proof = format!("c=biws,r={}", nonce);
+        let auth_message = format!(
+            "{},{},{}",
+            self.client_first_bare, server_first, without_proof
+        );
+
+        let client_key = hmac(&salted_password, b"Client Key")?;
+        let stored_key = hash(MessageDigest::sha256(), &client_key)?;
+        let signature = hmac(&stored_key, auth_message.as_bytes())?;
+        let proof: Vec<u8> = client_key
+            .iter()
+            .zip(signature.iter())
+            .map(|(k, s)| k ^ s)
+            .collect();
+
+        self.salted_password = salted_password;
+        self.auth_message = Some(auth_message);
+        Ok(format!(
+            "{},p={}",
+            without_proof,
+            base64::encode_block(&proof)
+        ))
+    }
+
+    /// Makes sure the server knew the password too.
+    fn verify_server_final(&self, server_final: &str) -> io::Result<()> {
+        let auth_message = self
+            .auth_message
+            .as_ref()
+            .ok_or_else(|| protocol_error("SCRAM server-final-message out of order"))?;
+        let server_key = hmac(&self.salted_password, b"Server Key")?;
+        let expected = hmac(&server_key, auth_message.as_bytes())?;
+        let got = server_final
+            .strip_prefix("v=")
+            .map(base64::decode_block)
+            .transpose()?
+            .ok_or_else(|| protocol_error("SCRAM authentication failed upstream"))?;
+        if got.len() != expected.len() || !memcmp::eq(&got, &expected) {
+            return Err(protocol_error(
+                "upstream failed to prove it knows the password",
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> io::Result<Vec<u8>> {
+    let key = PKey::hmac(key)?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(data)?;
+    Ok(signer.sign_to_vec()?)
+}
+
+enum Upstream {
+    Tcp(TcpStream),
+    Unix(UnixStream),
+}
+
+impl Upstream {
+    async fn connect(address: &str) -> io::Result<Upstream> {
+        if address.starts_with('/') {
+            Ok(Upstream::Unix(UnixStream::connect(address).await?))
+        } else {
+            Ok(Upstream::Tcp(TcpStream::connect(address).await?))
+        }
+    }
+
+    /// Whether the connection stays on this host.
+    fn is_unix(&self) -> bool {
+        match self {
+            Upstream::Tcp(_) => false,
+            Upstream::Unix(_) => true,
+        }
+    }
+}
+
+impl AsyncRead for Upstream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Upstream::Tcp(s) => Pin::new(s).poll_read(cx, buf),
+            Upstream::Unix(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Upstream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Upstream::Tcp(s) => Pin::new(s).poll_write(cx, buf),
+            Upstream::Unix(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Upstream::Tcp(s) => Pin::new(s).poll_flush(cx),
+            Upstream::Unix(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Upstream::Tcp(s) => Pin::new(s).poll_shutdown(cx),
+            Upstream::Unix(s) => Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::{TcpListener, UnixListener, UnixStream};
+
+    use super::{
+        encode_startup, md5_password, parse_parameters, read_message, read_startup, serve, Message,
+        ScramClient, AUTH_CLEARTEXT, AUTH_OK,
+    };
+    use crate::highwire::PostgresGrant;
+
+    #[test]
+    fn test_scram() {
+        // RFC 7677's example exchange.
+        let mut scram = ScramClient::with_nonce("user", "pencil", "rOprNGfwEbeRWgbNEkqO");
+        assert_eq!(scram.client_first(), "n,,n=user,r=rOprNGfwEbeRWgbNEkqO");
+        let client_final = scram
+            .client_final(
+                "r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,\
+                 s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096",
+            )
+            .unwrap();
+        assert_eq!(
+            client_final,
+            "c=biws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,\
+             p=dHzbZapWIk4jUhN+Ute9ytag9zjfMHgsqmmiz7AndVQ="
+        );
+        scram
+            .verify_server_final("v=6rriTRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=")
+            .unwrap();
+        assert!(scram.verify_server_final("v=AAAA").is_err());
+
+        assert_eq!(
+            md5_password("postgres", "secret", b"abcd").unwrap(),
+            "md568a34aeb823f3662497ea0b9fb65e46a"
+        );
+    }
+
+    /// Logs a client in with a cleartext password, then answers every
+    /// query with an empty `CommandComplete`, or with `hang_up` closes the
+    /// connection at the first one.
+    async fn stand_in(mut server: UnixStream, grant: &PostgresGrant, hang_up: bool) {
+        let (_, body) = read_startup(&mut server).await.unwrap().unwrap();
+        let parameters = parse_parameters(&body).unwrap();
+        assert_eq!(parameters["user"], grant.user);
+        assert_eq!(parameters["database"], grant.database);
+        assert_eq!(parameters["application_name"], "test");
+
+        let ask = Message::new(b'R', AUTH_CLEARTEXT.to_be_bytes().to_vec());
+        server.write_all(&ask.encode()).await.unwrap();
+        let password = read_message(&mut server).await.unwrap().unwrap();
+        assert_eq!(password.body, b"hunter2\0");
+        let ok = Message::new(b'R', AUTH_OK.to_be_bytes().to_vec());
+        server.write_all(&ok.encode()).await.unwrap();
+        let ready = Message::new(b'Z', b"I".to_vec());
+        server.write_all(&ready.encode()).await.unwrap();
+
+        while let Some(msg) = read_message(&mut server).await.unwrap() {
+            match msg.tag {
+                b'Q' if hang_up => return,
+                b'Q' => {
+                    let done = Message::new(b'C', b"SELECT 0\0".to_vec());
+                    server.write_all(&done.encode()).await.unwrap();
+                    server.write_all(&ready.encode()).await.unwrap();
+                }
+                b'X' => return,
+                tag => panic!("unexpected message {}", tag),
+            }
+        }
+    }
+
+    #[test]
+    fn test_serve() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = dir.path().join("upstream.sock");
+        let grant = PostgresGrant {
+            user: "frontend".into(),
+            password: "hunter2".into(),
+            database: "frontend_db".into(),
+        };
+
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut listener = UnixListener::bind(&address).unwrap();
+            let upstream_grant = grant.clone();
+            tokio::spawn(async move {
+                // one upstream connection per session.
+                for _ in 0..2 {
+                    let (server, _) = listener.accept().await.unwrap();
+                    stand_in(server, &upstream_grant, false).await;
+                }
+            });
+
+            let (ours, mut component) = UnixStream::pair().unwrap();
+            let address = address.to_str().unwrap().to_string();
+            tokio::spawn(async move { serve(ours, &address, &grant).await.unwrap() });
+
+            let mut parameters = BTreeMap::new();
+            parameters.insert("user".to_string(), "whoever".to_string());
+            parameters.insert("application_name".to_string(), "test".to_string());
+            for _ in 0..2 {
+                component
+                    .write_all(&encode_startup(&parameters))
+                    .await
+                    .unwrap();
+                let auth = read_message(&mut component).await.unwrap().unwrap();
+                assert_eq!((auth.tag, auth.auth_code().unwrap()), (b'R', AUTH_OK));
+                let ready = read_message(&mut component).await.unwrap().unwrap();
+                assert_eq!(ready.tag, b'Z');
+
+                let query = Message::new(b'Q', b"SELECT\0".to_vec());
+                component.write_all(&query.encode()).await.unwrap();
+                let done = read_message(&mut component).await.unwrap().unwrap();
+                assert_eq!((done.tag, &done.body[..]), (b'C', &b"SELECT 0\0"[..]));
+                read_message(&mut component).await.unwrap().unwrap();
+
+                let terminate = Message::new(b'X', Vec::new());
+                component.write_all(&terminate.encode()).await.unwrap();
+            }
+        });
+    }
+
+    #[test]
+    fn test_no_cleartext_over_tcp() {
+        let grant = PostgresGrant {
+            user: "frontend".into(),
+            password: "hunter2".into(),
+            database: "frontend_db".into(),
+        };
+
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap().to_string();
+            let upstream = tokio::spawn(async move {
+                let (mut server, _) = listener.accept().await.unwrap();
+                read_startup(&mut server).await.unwrap().unwrap();
+                let ask = Message::new(b'R', AUTH_CLEARTEXT.to_be_bytes().to_vec());
+                server.write_all(&ask.encode()).await.unwrap();
+                // hung up on rather than sent the password.
+                assert!(read_message(&mut server).await.unwrap().is_none());
+            });
+
+            let (ours, mut component) = UnixStream::pair().unwrap();
+            tokio::spawn(async move { serve(ours, &address, &grant).await.unwrap() });
+
+            let mut parameters = BTreeMap::new();
+            parameters.insert("user".to_string(), "whoever".to_string());
+            component
+                .write_all(&encode_startup(&parameters))
+                .await
+                .unwrap();
+            let fatal = read_message(&mut component).await.unwrap().unwrap();
+            assert_eq!(fatal.tag, b'E');
+            upstream.await.unwrap();
+        });
+    }
+
+    #[test]
+    fn test_serve_after_upstream_hangs_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = dir.path().join("upstream.sock");
+        let grant = PostgresGrant {
+            user: "frontend".into(),
+            password: "hunter2".into(),
+            database: "frontend_db".into(),
+        };
+
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut listener = UnixListener::bind(&address).unwrap();
+            let upstream_grant = grant.clone();
+            tokio::spawn(async move {
+                // as if the server restarted mid-session.
+                let (server, _) = listener.accept().await.unwrap();
+                stand_in(server, &upstream_grant, true).await;
+                let (server, _) = listener.accept().await.unwrap();
+                stand_in(server, &upstream_grant, false).await;
+            });
+
+            let (ours, mut component) = UnixStream::pair().unwrap();
+            let address = address.to_str().unwrap().to_string();
+            tokio::spawn(async move { serve(ours, &address, &grant).await.unwrap() });
+
+            let mut parameters = BTreeMap::new();
+            parameters.insert("application_name".to_string(), "test".to_string());
+            let query = Message::new(b'Q', b"SELECT\0".to_vec());
+            for expected in &[b'E', b'C'] {
+                component
+                    .write_all(&encode_startup(&parameters))
+                    .await
+                    .unwrap();
+                let auth = read_message(&mut component).await.unwrap().unwrap();
+                assert_eq!((auth.tag, auth.auth_code().unwrap()), (b'R', AUTH_OK));
+                let ready = read_message(&mut component).await.unwrap().unwrap();
+                assert_eq!(ready.tag, b'Z');
+
+                component.write_all(&query.encode()).await.unwrap();
+                let reply = read_message(&mut component).await.unwrap().unwrap();
+                assert_eq!(reply.tag, *expected);
+            }
+        });
+    }
+}
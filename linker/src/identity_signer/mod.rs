@@ -0,0 +1,438 @@
+use std::fs::{self, DirBuilder, File};
+use std::io::{self, Write};
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::io::FromRawFd;
+use std::path::Path;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::future;
+use openssl::asn1::{Asn1Integer, Asn1Time};
+use openssl::bn::{BigNum, MsbOption};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::x509::extension::{
+    AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
+    SubjectKeyIdentifier,
+};
+use openssl::x509::{X509Builder, X509NameBuilder, X509};
+use owned_fd::OwnedFd;
+use sockets::socketpair_raw;
+use tempfile::NamedTempFile;
+use tokio::runtime::{Handle, Runtime};
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+use tracing::{event, span, Instrument, Level};
+use uuid::Uuid;
+use yscloud_config_model::{
+    FdProtocol, FileDescriptorRemote, Ksuid, ServiceFileDirection, SideCarServiceInfo,
+};
+
+use crate::intrinsic::InProcessService;
+use crate::{ExecSomething, ServiceFileDescriptor, CARGO_PKG_VERSION};
+
+use self::pb::certificate_issuer_server::{CertificateIssuer, CertificateIssuerServer};
+use self::pb::{RenewCertificateRequest, RenewCertificateResponse};
+
+mod pb {
+    tonic::include_proto!("org.yshi.certificate_issuer.v1");
+}
+
+pub const PACKAGE_ID: &str = "yscloud.identity-signer";
+
+pub const SERVICE_NAME: &str = "org.yshi.certificate_issuer.v1.CertificateIssuer";
+
+/// How long issued certificates are valid.  Components renew them through
+/// `RenewCertificate` well before they run out.
+pub const CERTIFICATE_TTL: Duration = Duration::from_secs(60 * 60);
+
+const CA_VALIDITY_DAYS: u32 = 10 * 365;
+
+// leeway for clocks that are a little behind ours.
+const NOT_BEFORE_SKEW: Duration = Duration::from_secs(5 * 60);
+
+const CA_KEY_FILE: &str = "ca-key.pem";
+const CA_CERT_FILE: &str = "ca.pem";
+
+/// Who a certificate is issued to.
+#[derive(Clone, Debug)]
+pub struct Identity {
+    pub deployment_name: String,
+    pub package_id: String,
+    pub instance_id: Uuid,
+}
+
+impl Identity {
+    /// The certificate's only subject alternative name.
+    pub fn uri(&self) -> String {
+        format!(
+            "yscloud://{}/{}/{}",
+            self.deployment_name, self.package_id, self.instance_id
+        )
+    }
+}
+
+fn io_other(err: ErrorStack) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, err)
+}
+
+fn new_key() -> Result<PKey<Private>, ErrorStack> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+    PKey::from_ec_key(EcKey::generate(&group)?)
+}
+
+fn random_serial() -> Result<Asn1Integer, ErrorStack> {
+    let mut serial = BigNum::new()?;
+    serial.rand(127, MsbOption::MAYBE_ZERO, false)?;
+    serial.to_asn1_integer()
+}
+
+fn unix_time(time: SystemTime) -> Result<Asn1Time, ErrorStack> {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    Asn1Time::from_unix(secs as libc::time_t)
+}
+
+/// The linker's local CA.  Its certificate is the trust root components
+/// check each other's certificates against.
+pub struct CertificateAuthority {
+    key: PKey<Private>,
+    cert: X509,
+}
+
+impl CertificateAuthority {
+    /// Loads the CA kept in `dir`, creating it on first use.  The key is
+    /// put in place last, so a CA that was only partly written is created
+    /// again.
+    pub fn load_or_create(dir: &Path) -> io::Result<CertificateAuthority> {
+        let key_path = dir.join(CA_KEY_FILE);
+        let cert_path = dir.join(CA_CERT_FILE);
+
+        if key_path.exists() && cert_path.exists() {
+            let key = PKey::private_key_from_pem(&fs::read(&key_path)?).map_err(io_other)?;
+            let cert = X509::from_pem(&fs::read(&cert_path)?).map_err(io_other)?;
+            return Ok(CertificateAuthority { key, cert });
+        }
+
+        DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+        let ca = CertificateAuthority::create().map_err(io_other)?;
+
+        // both are staged in `dir` itself, so the renames can't cross
+        // filesystems.  Temporary files are only readable by us.
+        let mut key_file = NamedTempFile::new_in(dir)?;
+        key_file.write_all(&ca.key.private_key_to_pem_pkcs8().map_err(io_other)?)?;
+        key_file.as_file().sync_all()?;
+        let mut cert_file = NamedTempFile::new_in(dir)?;
+        cert_file.write_all(&ca.cert.to_pem().map_err(io_other)?)?;
+        cert_file.as_file().sync_all()?;
+
+        cert_file.persist(&cert_path).map_err(|e| e.error)?;
+        key_file.persist(&key_path).map_err(|e| e.error)?;
+        File::open(dir)?.sync_all()?;
+
+        event!(
+            Level::INFO,
+            "created identity signer CA in {}",
+            dir.display()
+        );
+        Ok(ca)
+    }
+
+    fn create() -> Result<CertificateAuthority, ErrorStack> {
+        let key = new_key()?;
+
+        let mut name = X509NameBuilder::new()?;
+        name.append_entry_by_nid(Nid::COMMONNAME, PACKAGE_ID)?;
+        let name = name.build();
+
+        let serial = random_serial()?;
+        let not_before = unix_time(SystemTime::now() - NOT_BEFORE_SKEW)?;
+        let not_after = Asn1Time::days_from_now(CA_VALIDITY_DAYS)?;
+
+        let mut builder = X509Builder::new()?;
+        builder.set_version(2)?;
+        builder.set_serial_number(&serial)?;
+        builder.set_subject_name(&name)?;
+        builder.set_issuer_name(&name)?;
+        builder.set_pubkey(&key)?;
+        builder.set_not_before(&not_before)?;
+        builder.set_not_after(&not_after)?;
+        builder.append_extension(BasicConstraints::new().critical().ca().pathlen(0).build()?)?;
+        builder.append_extension(
+            KeyUsage::new()
+                .critical()
+                .key_cert_sign()
+                .crl_sign()
+                .build()?,
+        )?;
+        let key_id = SubjectKeyIdentifier::new().build(&builder.x509v3_context(None, None))?;
+        builder.append_extension(key_id)?;
+        builder.sign(&key, MessageDigest::sha256())?;
+
+        Ok(CertificateAuthority {
+            key,
+            cert: builder.build(),
+        })
+    }
+
+    /// A new key and a certificate for it, valid for `ttl`.
+    pub fn issue(
+        &self,
+        identity: &Identity,
+        ttl: Duration,
+    ) -> Result<(X509, PKey<Private>), ErrorStack> {
+        let key = new_key()?;
+
+        let mut name = X509NameBuilder::new()?;
+        name.append_entry_by_nid(Nid::COMMONNAME, &identity.instance_id.to_string())?;
+        let name = name.build();
+
+        let now = SystemTime::now();
+        let serial = random_serial()?;
+        let not_before = unix_time(now - NOT_BEFORE_SKEW)?;
+        let not_after = unix_time(now + ttl)?;
+
+        let mut builder = X509Builder::new()?;
+        builder.set_version(2)?;
+        builder.set_serial_number(&serial)?;
+        builder.set_subject_name(&name)?;
+        builder.set_issuer_name(self.cert.subject_name())?;
+        builder.set_pubkey(&key)?;
+        builder.set_not_before(&not_before)?;
+        builder.set_not_after(&not_after)?;
+        builder.append_extension(BasicConstraints::new().critical().build()?)?;
+        builder.append_extension(KeyUsage::new().critical().digital_signature().build()?)?;
+        builder.append_extension(
+            ExtendedKeyUsage::new()
+                .server_auth()
+                .client_auth()
+                .build()?,
+        )?;
+        let san = SubjectAlternativeName::new()
+            .uri(&identity.uri())
+            .build(&builder.x509v3_context(Some(&self.cert), None))?;
+        builder.append_extension(san)?;
+        let authority_key_id = AuthorityKeyIdentifier::new()
+            .keyid(true)
+            .build(&builder.x509v3_context(Some(&self.cert), None))?;
+        builder.append_extension(authority_key_id)?;
+        builder.sign(&self.key, MessageDigest::sha256())?;
+
+        Ok((builder.build(), key))
+    }
+}
+
+/// Serves `org.yshi.certificate_issuer.v1.CertificateIssuer` to every
+/// component, from a runtime thread of its own.
+#[derive(Clone)]
+pub struct IdentitySigner {
+    ca: Arc<CertificateAuthority>,
+    // stands in for a component instance in the app configuration.
+    instance_id: Uuid,
+    runtime: Handle,
+}
+
+impl IdentitySigner {
+    pub fn start(ca: CertificateAuthority) -> io::Result<IdentitySigner> {
+        let (handle_tx, handle_rx) = mpsc::channel();
+        thread::Builder::new()
+            .name(PACKAGE_ID.to_string())
+            .spawn(move || {
+                let mut runtime = Runtime::new().unwrap();
+                handle_tx.send(runtime.handle().clone()).unwrap();
+                runtime.block_on(future::pending::<()>());
+            })?;
+        let runtime = handle_rx
+            .recv()
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+        Ok(IdentitySigner {
+            ca: Arc::new(ca),
+            instance_id: Uuid::new_v4(),
+            runtime,
+        })
+    }
+
+    /// A fresh connection to the issuer for `identity`.  Each connection
+    /// carries a single HTTP/2 session, so a restarted component needs a new
+    /// one.
+    pub fn connect(&self, identity: Identity) -> io::Result<ServiceFileDescriptor> {
+        let (ours, theirs) = socketpair_raw()?;
+        let issuer = Issuer {
+            ca: Arc::clone(&self.ca),
+            identity,
+        };
+        let trace_id = Ksuid::generate();
+        let span = span!(Level::INFO, "connection", trace_id = %trace_id.fmt_base62());
+        self.runtime.spawn(serve(issuer, ours).instrument(span));
+
+        Ok(ServiceFileDescriptor {
+            file: theirs,
+            direction: ServiceFileDirection::Consuming,
+            service_name: SERVICE_NAME.to_string(),
+            remote: FileDescriptorRemote::SideCarService(SideCarServiceInfo {
+                instance_id: self.instance_id,
+                package_id: PACKAGE_ID.to_string(),
+                version: CARGO_PKG_VERSION.parse().unwrap(),
+            }),
+            trace_id: Some(trace_id),
+        })
+    }
+
+    /// Gives `exec` its implicit connection to the issuer, unless it
+    /// required one itself.  Components using `LISTEN_FDS` aren't ours and
+    /// wouldn't know what to do with it.
+    pub fn attach(&self, exec: &mut ExecSomething) -> io::Result<()> {
+        if exec.cfg.fd_protocol == FdProtocol::ListenFds {
+            return Ok(());
+        }
+        let required = exec
+            .cfg
+            .files
+            .iter()
+            .any(|f| f.service_name == SERVICE_NAME);
+        if !required {
+            let file = self.connect(Identity {
+                deployment_name: exec.cfg.deployment_name.clone(),
+                package_id: exec.cfg.package_id.clone(),
+                instance_id: exec.cfg.instance_id,
+            })?;
+            exec.cfg.files.push(file);
+        }
+        exec.identity_signer = Some(self.clone());
+        Ok(())
+    }
+}
+
+impl InProcessService for IdentitySigner {
+    fn connect(&self, consumer: Identity) -> io::Result<ServiceFileDescriptor> {
+        IdentitySigner::connect(self, consumer)
+    }
+}
+
+async fn serve(issuer: Issuer, fd: OwnedFd) {
+    let package_id = issuer.identity.package_id.clone();
+
+    // tonic only serves `Connected` streams, so the unix socket is driven as
+    // a TcpStream, like linker-connector does on the component side.
+    let stream = unsafe { std::net::TcpStream::from_raw_fd(fd.into_raw_fd()) };
+    let stream = stream
+        .set_nonblocking(true)
+        .and_then(|()| tokio::net::TcpStream::from_std(stream));
+    let stream = match stream {
+        Ok(stream) => stream,
+        Err(err) => {
+            event!(Level::WARN, "identity signer for {}: {}", package_id, err);
+            return;
+        }
+    };
+
+    let incoming = futures::stream::iter(vec![Ok::<_, io::Error>(stream)]);
+    let served = Server::builder()
+        .add_service(CertificateIssuerServer::new(issuer))
+        .serve_with_incoming(incoming)
+        .await;
+    if let Err(err) = served {
+        event!(Level::WARN, "identity signer for {}: {}", package_id, err);
+    }
+}
+
+struct Issuer {
+    ca: Arc<CertificateAuthority>,
+    identity: Identity,
+}
+
+#[tonic::async_trait]
+impl CertificateIssuer for Issuer {
+    // the requested hostname is echoed back but not certified: a component
+    // only gets its own identity.
+    async fn renew_certificate(
+        &self,
+        request: Request<RenewCertificateRequest>,
+    ) -> Result<Response<RenewCertificateResponse>, Status> {
+        let internal = |err: ErrorStack| Status::internal(err.to_string());
+
+        let (cert, key) = self
+            .ca
+            .issue(&self.identity, CERTIFICATE_TTL)
+            .map_err(internal)?;
+        event!(
+            Level::INFO,
+            "issued a certificate for {}",
+            self.identity.uri()
+        );
+
+        Ok(Response::new(RenewCertificateResponse {
+            hostname: request.into_inner().hostname,
+            certificates: vec![
+                cert.to_der().map_err(internal)?,
+                self.ca.cert.to_der().map_err(internal)?,
+            ],
+            private_key: key.private_key_to_pkcs8().map_err(internal)?,
+            ttl_duration_millis: CERTIFICATE_TTL.as_millis() as i64,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::time::Duration;
+
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    use super::{CertificateAuthority, Identity, CA_CERT_FILE, CA_KEY_FILE};
+
+    #[test]
+    fn test_issue() {
+        let dir = tempdir().unwrap();
+        let ca = CertificateAuthority::load_or_create(dir.path()).unwrap();
+        // loaded again rather than recreated.
+        let reloaded = CertificateAuthority::load_or_create(dir.path()).unwrap();
+        assert_eq!(ca.cert.to_der().unwrap(), reloaded.cert.to_der().unwrap());
+
+        // a key without its certificate is what a crash while creating the CA
+        // used to leave.
+        let partial = tempdir().unwrap();
+        fs::copy(
+            dir.path().join(CA_KEY_FILE),
+            partial.path().join(CA_KEY_FILE),
+        )
+        .unwrap();
+        let recreated = CertificateAuthority::load_or_create(partial.path()).unwrap();
+        assert_ne!(ca.cert.to_der().unwrap(), recreated.cert.to_der().unwrap());
+        assert!(partial.path().join(CA_CERT_FILE).exists());
+
+        let identity = Identity {
+            deployment_name: "example-deployment".into(),
+            package_id: "org.yshi.staticserver".into(),
+            instance_id: Uuid::new_v4(),
+        };
+        let (cert, key) = ca.issue(&identity, Duration::from_secs(60)).unwrap();
+
+        let ca_key = ca.cert.public_key().unwrap();
+        assert!(cert.verify(&ca_key).unwrap());
+        assert!(cert.public_key().unwrap().public_eq(&key));
+
+        let sans: Vec<String> = cert
+            .subject_alt_names()
+            .unwrap()
+            .iter()
+            .filter_map(|name| name.uri().map(|uri| uri.to_string()))
+            .collect();
+        assert_eq!(
+            sans,
+            vec![format!(
+                "yscloud://example-deployment/org.yshi.staticserver/{}",
+                identity.instance_id
+            )]
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! Platform intrinsic services: services a deployment requires but doesn't
+//! ship, provided by whichever linker runs it.  They are either handled in
+//! the linker process, or by linker-managed binaries listed in
+//! `<approot>/intrinsics.json`, which are versioned separately from the
+//! deployments using them.
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use failure::{Fail, Fallible};
+use serde::Deserialize;
+use yscloud_config_model::{DeployedApplicationManifest, DeploymentManifest, INTRINSIC_PACKAGE_ID};
+
+use crate::document;
+use crate::highwire;
+use crate::identity_signer::{self, Identity};
+use crate::ServiceFileDescriptor;
+
+pub const INTRINSICS_FILE: &str = "intrinsics.json";
+
+/// Services every linker provides in-process.
+pub const BUILTIN_SERVICES: &[&str] = &[identity_signer::SERVICE_NAME];
+
+pub fn is_builtin(service_name: &str) -> bool {
+    BUILTIN_SERVICES.contains(&service_name)
+        || service_name.starts_with(highwire::POSTGRES_SERVICE_PREFIX)
+}
+
+/// An intrinsic served from the linker process.
+pub trait InProcessService: Send + Sync {
+    /// A new connection to the service for `consumer`.
+    fn connect(&self, consumer: Identity) -> io::Result<ServiceFileDescriptor>;
+}
+
+/// The binaries a linker runs for the intrinsics it doesn't handle itself.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct IntrinsicsManifest {
+    #[serde(default)]
+    pub binaries: Vec<DeployedApplicationManifest>,
+}
+
+enum Provider {
+    InProcess(Arc<dyn InProcessService>),
+    // index into `Registry::binaries`
+    Binary(usize),
+}
+
+#[derive(Debug, Fail)]
+pub enum IntrinsicError {
+    Unknown {
+        service_name: String,
+        required_by: String,
+    },
+    Duplicate {
+        service_name: String,
+    },
+    // a deployment ships a package of the same name as an intrinsic binary.
+    Conflict {
+        package_id: String,
+    },
+    // binaries are restarted in place, without a control socket.
+    LiveConfig {
+        package_id: String,
+    },
+}
+
+impl fmt::Display for IntrinsicError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IntrinsicError::Unknown {
+                ref service_name,
+                ref required_by,
+            } => write!(
+                f,
+                "no intrinsic provides {} (required by {})",
+                service_name, required_by
+            ),
+            IntrinsicError::Duplicate { ref service_name } => {
+                write!(f, "intrinsic {} is provided more than once", service_name)
+            }
+            IntrinsicError::Conflict { ref package_id } => write!(
+                f,
+                "{} is both a deployment component and an intrinsic",
+                package_id
+            ),
+            IntrinsicError::LiveConfig { ref package_id } => write!(
+                f,
+                "intrinsic binary {} can't take live configuration",
+                package_id
+            ),
+        }
+    }
+}
+
+/// Maps intrinsic service names to what provides them.
+#[derive(Default)]
+pub struct Registry {
+    services: BTreeMap<String, Provider>,
+    binaries: Vec<DeployedApplicationManifest>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Default::default()
+    }
+
+    pub fn register(
+        &mut self,
+        service_name: &str,
+        service: Arc<dyn InProcessService>,
+    ) -> Result<(), IntrinsicError> {
+        self.insert(service_name, Provider::InProcess(service))
+    }
+
+    /// Registers the binaries in `approot`'s intrinsics file, if it has one.
+    pub fn load_binaries(&mut self, approot: &Path) -> Fallible<()> {
+        let path = approot.join(INTRINSICS_FILE);
+        if !path.exists() {
+            return Ok(());
+        }
+        let manifest: IntrinsicsManifest = document::read(&path, None)?;
+        for binary in manifest.binaries {
+            if binary.live_config {
+                return Err(IntrinsicError::LiveConfig {
+                    package_id: binary.package_id,
+                }
+                .into());
+            }
+            let index = self.binaries.len();
+            for service_name in &binary.provided_local_services {
+                self.insert(service_name, Provider::Binary(index))?;
+            }
+            self.binaries.push(binary);
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, service_name: &str, provider: Provider) -> Result<(), IntrinsicError> {
+        if self.services.contains_key(service_name) {
+            return Err(IntrinsicError::Duplicate {
+                service_name: service_name.to_string(),
+            });
+        }
+        self.services.insert(service_name.to_string(), provider);
+        Ok(())
+    }
+
+    /// Whether `package_id` is one of our binaries, restarted in place
+    /// rather than taking the deployment down when it exits.
+    pub fn is_binary(&self, package_id: &str) -> bool {
+        self.binaries.iter().any(|b| b.package_id == package_id)
+    }
+
+    /// Points requirements on intrinsics, by `INTRINSIC_PACKAGE_ID` or by a
+    /// package `dm` doesn't have, at their providers, adding the binaries
+    /// needed to `dm` as components.  Requirements on in-process intrinsics
+    /// are left to `connect`.
+    pub fn link(&self, dm: &mut DeploymentManifest) -> Result<(), IntrinsicError> {
+        let mut packages: BTreeSet<String> =
+            dm.components.iter().map(|c| c.package_id.clone()).collect();
+        for binary in &self.binaries {
+            if packages.contains(&binary.package_id) {
+                return Err(IntrinsicError::Conflict {
+                    package_id: binary.package_id.clone(),
+                });
+            }
+        }
+
+        // binaries added on the way may require intrinsics of their own.
+        let mut i = 0;
+        while i < dm.components.len() {
+            let mut needed = Vec::new();
+            let component = &mut dm.components[i];
+            for ls in &mut component.required_local_services {
+                if ls.package_id != INTRINSIC_PACKAGE_ID && packages.contains(&ls.package_id) {
+                    continue;
+                }
+                match self.services.get(&ls.service_name) {
+                    Some(Provider::InProcess(_)) => {
+                        ls.package_id = INTRINSIC_PACKAGE_ID.to_string();
+                    }
+                    Some(Provider::Binary(index)) => {
+                        ls.package_id = self.binaries[*index].package_id.clone();
+                        needed.push(*index);
+                    }
+                    None => {
+                        return Err(IntrinsicError::Unknown {
+                            service_name: ls.service_name.clone(),
+                            required_by: component.package_id.clone(),
+                        })
+                    }
+                }
+            }
+            for index in needed {
+                let binary = &self.binaries[index];
+                if packages.insert(binary.package_id.clone()) {
+                    dm.components.push(binary.clone());
+                }
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+
+    /// A connection to the in-process intrinsic `service_name`.
+    pub fn connect(
+        &self,
+        service_name: &str,
+        consumer: Identity,
+    ) -> io::Result<ServiceFileDescriptor> {
+        match self.services.get(service_name) {
+            Some(Provider::InProcess(service)) => service.connect(consumer),
+            _ => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not an in-process intrinsic", service_name),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+    use yscloud_config_model::migrate::CURRENT_FORMAT_VERSION;
+    use yscloud_config_model::{DeploymentManifest, ServiceId, INTRINSIC_PACKAGE_ID};
+
+    use super::{InProcessService, Registry, INTRINSICS_FILE};
+    use crate::identity_signer::Identity;
+    use crate::ServiceFileDescriptor;
+
+    struct Unreachable;
+
+    impl InProcessService for Unreachable {
+        fn connect(&self, _consumer: Identity) -> std::io::Result<ServiceFileDescriptor> {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn test_link() {
+        let component = |package_id: &str, required: &[(&str, &str)]| {
+            serde_json::json!({
+                "package_id": package_id,
+                "version": "1.0.0",
+                "provided_local_services": [],
+                "provided_remote_services": [],
+                "required_local_services": required
+                    .iter()
+                    .map(|(p, s)| serde_json::json!({"package_id": p, "service_name": s}))
+                    .collect::<Vec<_>>(),
+                "required_remote_services": [],
+                "sandbox": "unconfined",
+                "extras": {},
+                "image_type": "executable",
+                "artifacts": {},
+            })
+        };
+
+        let dir = tempdir().unwrap();
+        let mut logger = component(
+            "yscloud.log-target",
+            &[(INTRINSIC_PACKAGE_ID, "org.yshi.signer")],
+        );
+        logger["provided_local_services"] = serde_json::json!(["org.yshi.log_target.v1.LogTarget"]);
+        fs::write(
+            dir.path().join(INTRINSICS_FILE),
+            serde_json::to_vec(&serde_json::json!({ "binaries": [logger] })).unwrap(),
+        )
+        .unwrap();
+
+        let mut registry = Registry::new();
+        registry
+            .register("org.yshi.signer", Arc::new(Unreachable))
+            .unwrap();
+        registry.load_binaries(dir.path()).unwrap();
+        assert!(registry.is_binary("yscloud.log-target"));
+
+        let deployment = |required: &str| -> DeploymentManifest {
+            serde_json::from_value(serde_json::json!({
+                "format_version": CURRENT_FORMAT_VERSION,
+                "deployment_name": "example-deployment",
+                "components": [component(
+                    "org.yshi.frontend",
+                    &[(INTRINSIC_PACKAGE_ID, required)],
+                )],
+                "public_services": [],
+            }))
+            .unwrap()
+        };
+
+        let mut dm = deployment("org.yshi.log_target.v1.LogTarget");
+        registry.link(&mut dm).unwrap();
+
+        let package_ids: Vec<&str> = dm.components.iter().map(|c| &c.package_id[..]).collect();
+        assert_eq!(package_ids, vec!["org.yshi.frontend", "yscloud.log-target"]);
+        assert_eq!(
+            dm.components[0].required_local_services,
+            vec![ServiceId {
+                package_id: "yscloud.log-target".into(),
+                service_name: "org.yshi.log_target.v1.LogTarget".into(),
+            }]
+        );
+        assert_eq!(
+            dm.components[1].required_local_services[0].package_id,
+            INTRINSIC_PACKAGE_ID
+        );
+
+        let mut dm = deployment("org.yshi.missing");
+        assert_eq!(
+            format!("{}", registry.link(&mut dm).unwrap_err()),
+            "no intrinsic provides org.yshi.missing (required by org.yshi.frontend)"
+        );
+
+        let mut reloadable = component("yscloud.reloadable", &[]);
+        reloadable["live_config"] = serde_json::json!(true);
+        fs::write(
+            dir.path().join(INTRINSICS_FILE),
+            serde_json::to_vec(&serde_json::json!({ "binaries": [reloadable] })).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            format!("{}", Registry::new().load_binaries(dir.path()).unwrap_err()),
+            "intrinsic binary yscloud.reloadable can't take live configuration"
+        );
+    }
+}
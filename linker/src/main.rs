@@ -9,20 +9,33 @@ use tracing_subscriber::FmtSubscriber;
 
 use owned_fd::OwnedFd;
 use yscloud_config_model::{
-    AppConfiguration, FileDescriptorInfo, FileDescriptorRemote, PublicServiceBinder,
-    ServiceFileDirection,
+    Activation, AppConfiguration, FdProtocol, FileDescriptorInfo, FileDescriptorRemote, Ksuid,
+    PublicServiceBinder, ServiceFileDirection,
 };
 
 pub mod platform;
 
+mod adapter;
 mod artifact;
+mod audit;
 mod bind;
 mod cmdlet;
+mod document;
+mod highwire;
+mod identity_signer;
+mod intrinsic;
+mod manifest_diff;
+mod metrics;
+mod process;
 mod publish_artifact;
 mod registry;
+mod schema;
 mod start_daemon;
+mod template;
 mod util;
+mod workdir;
 
+use crate::identity_signer::IdentitySigner;
 use crate::platform::{ExecExtras, Executable};
 
 pub enum Void {}
@@ -34,8 +47,8 @@ fn main() {
     let mut my_subscriber_builder = FmtSubscriber::builder();
 
     use self::cmdlet::{
-        artifact_metastamp, create_release, publish_artifact, run, start_daemon,
-        unstable_setup_container,
+        artifact_metastamp, create_release, diff, gc_workdirs, migrate, print_schema,
+        publish_artifact, registry, run, start_daemon, unstable_setup_container,
     };
     let app = App::new(CARGO_PKG_NAME)
         .version(CARGO_PKG_VERSION)
@@ -56,7 +69,12 @@ fn main() {
         )
         .subcommand(artifact_metastamp::get_subcommand())
         .subcommand(create_release::get_subcommand())
+        .subcommand(diff::get_subcommand())
+        .subcommand(gc_workdirs::get_subcommand())
+        .subcommand(migrate::get_subcommand())
+        .subcommand(print_schema::get_subcommand())
         .subcommand(publish_artifact::get_subcommand())
+        .subcommand(registry::get_subcommand())
         .subcommand(run::get_subcommand())
         .subcommand(start_daemon::get_subcommand());
 
@@ -86,7 +104,12 @@ fn main() {
     let (sub_name, args) = matches.subcommand();
     let main_function = match sub_name {
         create_release::SUBCOMMAND_NAME => create_release::main,
+        diff::SUBCOMMAND_NAME => diff::main,
+        gc_workdirs::SUBCOMMAND_NAME => gc_workdirs::main,
+        migrate::SUBCOMMAND_NAME => migrate::main,
+        print_schema::SUBCOMMAND_NAME => print_schema::main,
         publish_artifact::SUBCOMMAND_NAME => publish_artifact::main,
+        registry::SUBCOMMAND_NAME => registry::main,
         run::SUBCOMMAND_NAME => run::main,
         start_daemon::SUBCOMMAND_NAME => start_daemon::main,
         unstable_setup_container::SUBCOMMAND_NAME => unstable_setup_container::main,
@@ -98,6 +121,8 @@ fn main() {
 
 pub struct AppPreforkConfiguration {
     deployment_name: String,
+    // see `AppConfiguration::trace_id`.
+    trace_id: Ksuid,
     package_id: String,
     artifact: Executable,
     instance_id: Uuid,
@@ -105,6 +130,13 @@ pub struct AppPreforkConfiguration {
     files: Vec<ServiceFileDescriptor>,
     extras: serde_json::Value,
     container_mounts: Vec<(PathBuf, PathBuf)>,
+    // the component's end of the live configuration control socket.
+    control: Option<OwnedFd>,
+    fd_protocol: FdProtocol,
+    // passed after `--config-fd N`.
+    arguments: Vec<String>,
+    // see `process::environment`.
+    environment: Vec<(String, String)>,
 }
 
 pub struct ServiceFileDescriptor {
@@ -112,6 +144,8 @@ pub struct ServiceFileDescriptor {
     direction: ServiceFileDirection,
     service_name: String,
     remote: FileDescriptorRemote,
+    // shared with the other end of the connection; listeners have none.
+    trace_id: Option<Ksuid>,
 }
 
 fn bind_service(binder: &PublicServiceBinder) -> io::Result<OwnedFd> {
@@ -127,6 +161,15 @@ fn bind_service(binder: &PublicServiceBinder) -> io::Result<OwnedFd> {
 pub struct ExecSomething {
     extras: ExecExtras,
     cfg: AppPreforkConfiguration,
+    // the linker's end of `cfg.control`.
+    control: Option<OwnedFd>,
+    activation: Activation,
+    // hands out a new issuer connection each time the component starts.
+    identity_signer: Option<IdentitySigner>,
+    // an intrinsic binary, restarted when it exits.
+    intrinsic: bool,
+    // emptied once the deployment has stopped, see `Workdir::Ephemeral`.
+    ephemeral_workdir: Option<PathBuf>,
 }
 
 
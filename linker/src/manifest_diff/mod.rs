@@ -0,0 +1,618 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use semver::Version;
+use serde::Serialize;
+use serde_json::Value;
+
+use yscloud_config_model::{
+    Activation, Adapter, ArtifactHashSet, DeployedApplicationManifest, DeploymentManifest,
+    FdProtocol, ImageType, PublicServiceBinder, Sandbox, ServiceId, Workdir,
+};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Change<T> {
+    pub old: T,
+    pub new: T,
+}
+
+fn change<T: PartialEq + Clone>(old: &T, new: &T) -> Option<Change<T>> {
+    if old == new {
+        return None;
+    }
+    Some(Change {
+        old: old.clone(),
+        new: new.clone(),
+    })
+}
+
+#[derive(Serialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ManifestDiff {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deployment_name: Option<Change<String>>,
+    pub added_components: Vec<ComponentSummary>,
+    pub removed_components: Vec<ComponentSummary>,
+    pub changed_components: Vec<ComponentDiff>,
+    pub public_services: Vec<PublicServiceDiff>,
+    pub path_overrides: Vec<PathOverrideDiff>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ComponentSummary {
+    pub package_id: String,
+    pub version: Version,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ComponentDiff {
+    pub package_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<Change<Version>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<Change<Sandbox>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_type: Option<Change<ImageType>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provided_local_services: Option<Change<Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provided_remote_services: Option<Change<Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required_local_services: Option<Change<Vec<ServiceId>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required_remote_services: Option<Change<Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub live_config: Option<Change<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fd_protocol: Option<Change<FdProtocol>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Change<Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<Change<BTreeMap<String, String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activation: Option<Change<Activation>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adapter: Option<Change<Option<Adapter>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workdir: Option<Change<Workdir>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extras: Vec<JsonChange>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<ArtifactChange>,
+}
+
+impl ComponentDiff {
+    fn is_empty(&self) -> bool {
+        self.version.is_none()
+            && self.sandbox.is_none()
+            && self.image_type.is_none()
+            && self.provided_local_services.is_none()
+            && self.provided_remote_services.is_none()
+            && self.required_local_services.is_none()
+            && self.required_remote_services.is_none()
+            && self.live_config.is_none()
+            && self.fd_protocol.is_none()
+            && self.arguments.is_none()
+            && self.environment.is_none()
+            && self.activation.is_none()
+            && self.adapter.is_none()
+            && self.workdir.is_none()
+            && self.extras.is_empty()
+            && self.artifacts.is_empty()
+    }
+}
+
+/// A single changed value in a JSON document, addressed by a JSON pointer
+/// (RFC 6901).  `old` is absent for additions and `new` for removals.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct JsonChange {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<Value>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct ArtifactChange {
+    pub platform_triple: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<ArtifactHashSet>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<ArtifactHashSet>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct PublicServiceDiff {
+    pub service_id: ServiceId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<PublicServiceBinder>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<PublicServiceBinder>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct PathOverrideDiff {
+    pub package_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<String>,
+}
+
+impl ManifestDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == ManifestDiff::default()
+    }
+}
+
+pub fn diff_manifests(old: &DeploymentManifest, new: &DeploymentManifest) -> ManifestDiff {
+    let mut out = ManifestDiff {
+        deployment_name: change(&old.deployment_name, &new.deployment_name),
+        ..Default::default()
+    };
+
+    let old_components: BTreeMap<&str, &DeployedApplicationManifest> = old
+        .components
+        .iter()
+        .map(|c| (&c.package_id[..], c))
+        .collect();
+    let new_components: BTreeMap<&str, &DeployedApplicationManifest> = new
+        .components
+        .iter()
+        .map(|c| (&c.package_id[..], c))
+        .collect();
+
+    let package_ids: BTreeSet<&str> = old_components
+        .keys()
+        .chain(new_components.keys())
+        .cloned()
+        .collect();
+
+    for package_id in package_ids {
+        match (
+            old_components.get(package_id),
+            new_components.get(package_id),
+        ) {
+            (Some(o), Some(n)) => {
+                let component_diff = diff_component(o, n);
+                if !component_diff.is_empty() {
+                    out.changed_components.push(component_diff);
+                }
+            }
+            (Some(o), None) => out.removed_components.push(ComponentSummary {
+                package_id: o.package_id.clone(),
+                version: o.version.clone(),
+            }),
+            (None, Some(n)) => out.added_components.push(ComponentSummary {
+                package_id: n.package_id.clone(),
+                version: n.version.clone(),
+            }),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    let old_services: BTreeMap<&ServiceId, &PublicServiceBinder> = old
+        .public_services
+        .iter()
+        .map(|ps| (&ps.service_id, &ps.binder))
+        .collect();
+    let new_services: BTreeMap<&ServiceId, &PublicServiceBinder> = new
+        .public_services
+        .iter()
+        .map(|ps| (&ps.service_id, &ps.binder))
+        .collect();
+
+    let service_ids: BTreeSet<&ServiceId> = old_services
+        .keys()
+        .chain(new_services.keys())
+        .cloned()
+        .collect();
+
+    for service_id in service_ids {
+        let o = old_services.get(service_id).cloned();
+        let n = new_services.get(service_id).cloned();
+        if o != n {
+            out.public_services.push(PublicServiceDiff {
+                service_id: service_id.clone(),
+                old: o.cloned(),
+                new: n.cloned(),
+            });
+        }
+    }
+
+    let package_ids: BTreeSet<&String> = old
+        .path_overrides
+        .keys()
+        .chain(new.path_overrides.keys())
+        .collect();
+
+    for package_id in package_ids {
+        let o = old.path_overrides.get(package_id);
+        let n = new.path_overrides.get(package_id);
+        if o != n {
+            out.path_overrides.push(PathOverrideDiff {
+                package_id: package_id.clone(),
+                old: o.cloned(),
+                new: n.cloned(),
+            });
+        }
+    }
+
+    out
+}
+
+fn diff_component(
+    old: &DeployedApplicationManifest,
+    new: &DeployedApplicationManifest,
+) -> ComponentDiff {
+    let mut extras = Vec::new();
+    diff_json(
+        &mut extras,
+        &mut String::new(),
+        Some(&old.extras),
+        Some(&new.extras),
+    );
+
+    let mut artifacts = Vec::new();
+    let triples: BTreeSet<&String> = old.artifacts.keys().chain(new.artifacts.keys()).collect();
+    for triple in triples {
+        let o = old.artifacts.get(triple);
+        let n = new.artifacts.get(triple);
+        if o != n {
+            artifacts.push(ArtifactChange {
+                platform_triple: triple.clone(),
+                old: o.cloned(),
+                new: n.cloned(),
+            });
+        }
+    }
+
+    ComponentDiff {
+        package_id: new.package_id.clone(),
+        version: change(&old.version, &new.version),
+        sandbox: change(&old.sandbox, &new.sandbox),
+        image_type: change(&old.image_type, &new.image_type),
+        provided_local_services: change(&old.provided_local_services, &new.provided_local_services),
+        provided_remote_services: change(
+            &old.provided_remote_services,
+            &new.provided_remote_services,
+        ),
+        required_local_services: change(&old.required_local_services, &new.required_local_services),
+        required_remote_services: change(
+            &old.required_remote_services,
+            &new.required_remote_services,
+        ),
+        live_config: change(&old.live_config, &new.live_config),
+        fd_protocol: change(&old.fd_protocol, &new.fd_protocol),
+        arguments: change(&old.process.arguments, &new.process.arguments),
+        environment: change(&old.process.environment, &new.process.environment),
+        activation: change(&old.activation, &new.activation),
+        adapter: change(&old.adapter, &new.adapter),
+        workdir: change(&old.workdir, &new.workdir),
+        extras,
+        artifacts,
+    }
+}
+
+/// Objects are compared key by key; any other differing values, including
+/// arrays, are reported whole.
+fn diff_json(
+    out: &mut Vec<JsonChange>,
+    path: &mut String,
+    old: Option<&Value>,
+    new: Option<&Value>,
+) {
+    match (old, new) {
+        (Some(Value::Object(o)), Some(Value::Object(n))) => {
+            let keys: BTreeSet<&String> = o.keys().chain(n.keys()).collect();
+            for key in keys {
+                let path_len = path.len();
+                path.push('/');
+                path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                diff_json(out, path, o.get(key), n.get(key));
+                path.truncate(path_len);
+            }
+        }
+        (o, n) if o != n => out.push(JsonChange {
+            path: path.clone(),
+            old: o.cloned(),
+            new: n.cloned(),
+        }),
+        _ => (),
+    }
+}
+
+fn compact<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "?".into())
+}
+
+fn compact_opt<T: Serialize>(value: &Option<T>) -> String {
+    match *value {
+        Some(ref v) => compact(v),
+        None => "(none)".into(),
+    }
+}
+
+impl fmt::Display for ManifestDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref c) = self.deployment_name {
+            writeln!(f, "deployment name: {} -> {}", c.old, c.new)?;
+        }
+        for c in &self.added_components {
+            writeln!(f, "+ {} v{}", c.package_id, c.version)?;
+        }
+        for c in &self.removed_components {
+            writeln!(f, "- {} v{}", c.package_id, c.version)?;
+        }
+        for c in &self.changed_components {
+            writeln!(f, "~ {}", c.package_id)?;
+            if let Some(ref v) = c.version {
+                writeln!(f, "    version: {} -> {}", v.old, v.new)?;
+            }
+            if let Some(ref v) = c.sandbox {
+                writeln!(f, "    sandbox: {} -> {}", compact(&v.old), compact(&v.new))?;
+            }
+            if let Some(ref v) = c.image_type {
+                writeln!(
+                    f,
+                    "    image type: {} -> {}",
+                    compact(&v.old),
+                    compact(&v.new)
+                )?;
+            }
+            if let Some(ref v) = c.provided_local_services {
+                writeln!(
+                    f,
+                    "    provided local services: {} -> {}",
+                    compact(&v.old),
+                    compact(&v.new)
+                )?;
+            }
+            if let Some(ref v) = c.provided_remote_services {
+                writeln!(
+                    f,
+                    "    provided remote services: {} -> {}",
+                    compact(&v.old),
+                    compact(&v.new)
+                )?;
+            }
+            if let Some(ref v) = c.required_local_services {
+                writeln!(
+                    f,
+                    "    required local services: {} -> {}",
+                    compact(&v.old),
+                    compact(&v.new)
+                )?;
+            }
+            if let Some(ref v) = c.required_remote_services {
+                writeln!(
+                    f,
+                    "    required remote services: {} -> {}",
+                    compact(&v.old),
+                    compact(&v.new)
+                )?;
+            }
+            if let Some(ref v) = c.live_config {
+                writeln!(f, "    live config: {} -> {}", v.old, v.new)?;
+            }
+            if let Some(ref v) = c.fd_protocol {
+                writeln!(
+                    f,
+                    "    fd protocol: {} -> {}",
+                    compact(&v.old),
+                    compact(&v.new)
+                )?;
+            }
+            if let Some(ref v) = c.arguments {
+                writeln!(
+                    f,
+                    "    arguments: {} -> {}",
+                    compact(&v.old),
+                    compact(&v.new)
+                )?;
+            }
+            if let Some(ref v) = c.environment {
+                writeln!(
+                    f,
+                    "    environment: {} -> {}",
+                    compact(&v.old),
+                    compact(&v.new)
+                )?;
+            }
+            if let Some(ref v) = c.activation {
+                writeln!(
+                    f,
+                    "    activation: {} -> {}",
+                    compact(&v.old),
+                    compact(&v.new)
+                )?;
+            }
+            if let Some(ref v) = c.adapter {
+                writeln!(
+                    f,
+                    "    adapter: {} -> {}",
+                    compact_opt(&v.old),
+                    compact_opt(&v.new)
+                )?;
+            }
+            if let Some(ref v) = c.workdir {
+                writeln!(f, "    workdir: {} -> {}", compact(&v.old), compact(&v.new))?;
+            }
+            for v in &c.extras {
+                writeln!(
+                    f,
+                    "    extras {}: {} -> {}",
+                    if v.path.is_empty() { "/" } else { &v.path },
+                    compact_opt(&v.old),
+                    compact_opt(&v.new)
+                )?;
+            }
+            for v in &c.artifacts {
+                writeln!(
+                    f,
+                    "    artifact {}: {} -> {}",
+                    v.platform_triple,
+                    v.old.as_ref().map(|a| &a.sha256[..]).unwrap_or("(none)"),
+                    v.new.as_ref().map(|a| &a.sha256[..]).unwrap_or("(none)"),
+                )?;
+            }
+        }
+        for s in &self.public_services {
+            writeln!(
+                f,
+                "public service {} ({}): {} -> {}",
+                s.service_id.service_name,
+                s.service_id.package_id,
+                compact_opt(&s.old),
+                compact_opt(&s.new)
+            )?;
+        }
+        for p in &self.path_overrides {
+            writeln!(
+                f,
+                "path override {}: {} -> {}",
+                p.package_id,
+                p.old.as_ref().map(|p| &p[..]).unwrap_or("(none)"),
+                p.new.as_ref().map(|p| &p[..]).unwrap_or("(none)"),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use yscloud_config_model::{Adapter, DeploymentManifest, Workdir};
+
+    use super::diff_manifests;
+
+    #[test]
+    fn test_diff_manifests() {
+        let old: DeploymentManifest = serde_json::from_value(json!({
+            "format_version": 1,
+            "deployment_name": "example-deployment",
+            "public_services": [{
+                "service_id": {
+                    "package_id": "org.yshi.staticserver",
+                    "service_name": "org.yshi.staticserver.http"
+                },
+                "binder": {"native_port_binder": {"bind_address": "::", "port": 8080}}
+            }],
+            "components": [{
+                "package_id": "org.yshi.staticserver",
+                "version": "1.0.5",
+                "provided_local_services": [],
+                "provided_remote_services": ["org.yshi.staticserver.http"],
+                "required_remote_services": [],
+                "required_local_services": [],
+                "sandbox": "unconfined",
+                "extras": {"vhosts": {"a/b": {"directory": "./test"}}},
+                "artifacts": {
+                    "x86_64-unknown-linux-gnu": {"content_length": null, "sha256": "aaaa"}
+                }
+            }, {
+                "package_id": "org.yshi.file-logger",
+                "version": "1.0.2",
+                "provided_local_services": [],
+                "provided_remote_services": [],
+                "required_remote_services": [],
+                "required_local_services": [],
+                "sandbox": "unconfined",
+                "extras": {}
+            }]
+        }))
+        .unwrap();
+
+        let mut new = old.clone();
+        new.components.remove(1);
+        new.components[0].version = semver::Version::parse("1.1.0").unwrap();
+        new.components[0].extras =
+            json!({"vhosts": {"a/b": {"directory": "./www"}}, "debug": true});
+        new.components[0]
+            .artifacts
+            .get_mut("x86_64-unknown-linux-gnu")
+            .unwrap()
+            .sha256 = "bbbb".into();
+        new.components[0].live_config = true;
+        new.components[0].process.arguments.push("--verbose".into());
+        new.components[0].workdir = Workdir::Ephemeral;
+        new.components[0]
+            .provided_local_services
+            .push("org.yshi.staticserver.admin".into());
+        new.components[0].required_remote_services = vec!["org.yshi.cdn".into()];
+        new.path_overrides
+            .insert("org.yshi.staticserver".into(), "/tmp/staticserver".into());
+        new.components[0].adapter = Some(Adapter {
+            path: "staticserver.conf".into(),
+            template: "port {{port.org.yshi.staticserver.http}}".into(),
+        });
+
+        let diff = diff_manifests(&old, &new);
+        assert_eq!(diff.removed_components.len(), 1);
+        assert!(diff.added_components.is_empty());
+        assert!(diff.public_services.is_empty());
+
+        let diff_json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(
+            diff_json["changed_components"][0]["extras"],
+            json!([
+                {"path": "/debug", "new": true},
+                {"path": "/vhosts/a~1b/directory", "old": "./test", "new": "./www"},
+            ])
+        );
+        assert_eq!(
+            diff_json["changed_components"][0]["version"],
+            json!({"old": "1.0.5", "new": "1.1.0"})
+        );
+        assert_eq!(
+            diff_json["changed_components"][0]["live_config"],
+            json!({"old": false, "new": true})
+        );
+        assert_eq!(
+            diff_json["changed_components"][0]["arguments"],
+            json!({"old": [], "new": ["--verbose"]})
+        );
+        assert_eq!(
+            diff_json["changed_components"][0]["adapter"]["old"],
+            json!(null)
+        );
+        assert_eq!(
+            diff_json["changed_components"][0]["adapter"]["new"]["path"],
+            json!("staticserver.conf")
+        );
+        assert_eq!(
+            diff_json["changed_components"][0]["workdir"],
+            json!({"old": "persistent", "new": "ephemeral"})
+        );
+        assert_eq!(
+            diff_json["changed_components"][0]["provided_local_services"],
+            json!({"old": [], "new": ["org.yshi.staticserver.admin"]})
+        );
+        assert_eq!(
+            diff_json["changed_components"][0]["required_remote_services"],
+            json!({"old": [], "new": ["org.yshi.cdn"]})
+        );
+        assert!(diff_json["changed_components"][0]
+            .get("provided_remote_services")
+            .is_none());
+        assert!(diff_json["changed_components"][0]
+            .get("environment")
+            .is_none());
+        assert_eq!(
+            diff_json["path_overrides"],
+            json!([{"package_id": "org.yshi.staticserver", "new": "/tmp/staticserver"}])
+        );
+
+        assert!(diff_manifests(&new, &new).is_empty());
+    }
+}
@@ -0,0 +1,398 @@
+//! The daemon's supervisor metrics, in the Prometheus text format.  They are
+//! served over a unix socket to anything speaking HTTP, e.g. `curl
+//! --unix-socket`, or a scraper behind a proxy.
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Write as _};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nix::unistd::Pid;
+use tracing::{event, Level};
+use uuid::Uuid;
+
+use crate::platform::{self, ChildExit, JournalEvent};
+
+// How long a scraper gets to send its request, and to read the answer: one
+// that stalls must not hold up the others.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+struct Instance {
+    instance_id: Uuid,
+    pid: Option<Pid>,
+    starts: u64,
+    last_exit: Option<ChildExit>,
+    start_duration: Option<Duration>,
+}
+
+#[derive(Default)]
+struct Download {
+    bytes: u64,
+    duration: Duration,
+}
+
+#[derive(Default)]
+struct State {
+    // by package id
+    instances: BTreeMap<String, Instance>,
+    running: HashMap<Pid, String>,
+    downloads: BTreeMap<String, Download>,
+}
+
+/// Shared by the supervisor loop, which records, and the endpoint, which
+/// renders.
+#[derive(Clone)]
+pub struct Metrics {
+    deployment_name: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl Metrics {
+    pub fn new(deployment_name: &str) -> Metrics {
+        Metrics {
+            deployment_name: deployment_name.to_string(),
+            state: Default::default(),
+        }
+    }
+
+    /// Makes an instance show up as down before it is first started, as
+    /// on-demand components are.
+    pub fn add_instance(&self, package_id: &str, instance_id: Uuid) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .instances
+            .entry(package_id.to_string())
+            .or_default()
+            .instance_id = instance_id;
+    }
+
+    /// An instance started by an earlier daemon.
+    pub fn adopted(&self, package_id: &str, instance_id: Uuid, pid: Pid) {
+        let mut state = self.state.lock().unwrap();
+        let instance = state.instances.entry(package_id.to_string()).or_default();
+        instance.instance_id = instance_id;
+        instance.pid = Some(pid);
+        state.running.insert(pid, package_id.to_string());
+    }
+
+    pub fn record(&self, ev: &JournalEvent) {
+        let mut state = self.state.lock().unwrap();
+        match *ev {
+            JournalEvent::Started {
+                package_id,
+                instance_id,
+                pid,
+                start_duration,
+            } => {
+                let instance = state.instances.entry(package_id.to_string()).or_default();
+                instance.instance_id = instance_id;
+                instance.pid = Some(pid);
+                instance.starts += 1;
+                instance.start_duration = Some(start_duration);
+                state.running.insert(pid, package_id.to_string());
+            }
+            JournalEvent::Exited { pid, exit } => {
+                let package_id = match state.running.remove(&pid) {
+                    Some(package_id) => package_id,
+                    None => return,
+                };
+                let instance = state.instances.get_mut(&package_id).unwrap();
+                instance.pid = None;
+                if exit.is_some() {
+                    instance.last_exit = exit;
+                }
+            }
+        }
+    }
+
+    pub fn downloaded(&self, package_id: &str, bytes: u64, duration: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let download = state.downloads.entry(package_id.to_string()).or_default();
+        download.bytes += bytes;
+        download.duration = duration;
+    }
+
+    pub fn render(&self) -> String {
+        let state = self.state.lock().unwrap();
+        let mut out = String::new();
+
+        let labels = |package_id: &str, instance: &Instance| {
+            format!(
+                "deployment=\"{}\",package_id=\"{}\",instance_id=\"{}\"",
+                escape(&self.deployment_name),
+                escape(package_id),
+                instance.instance_id
+            )
+        };
+        let instances = || state.instances.iter().map(|(p, i)| (labels(p, i), i));
+        let usage: Vec<_> = instances()
+            .filter_map(|(l, i)| Some((l, platform::resource_usage(i.pid?)?)))
+            .collect();
+
+        family(
+            &mut out,
+            "yscloud_instance_up",
+            "gauge",
+            "Whether the instance is running.",
+            instances().map(|(l, i)| (l, i.pid.is_some() as u8)),
+        );
+        family(
+            &mut out,
+            "yscloud_instance_restarts_total",
+            "counter",
+            "Times the instance was started again after its first start.",
+            instances().map(|(l, i)| (l, i.starts.saturating_sub(1))),
+        );
+        family(
+            &mut out,
+            "yscloud_instance_last_exit_code",
+            "gauge",
+            "Exit code of the instance's last process, if it exited.",
+            instances().filter_map(|(l, i)| match i.last_exit? {
+                ChildExit::Code(code) => Some((l, code)),
+                ChildExit::Signal(_) => None,
+            }),
+        );
+        family(
+            &mut out,
+            "yscloud_instance_last_exit_signal",
+            "gauge",
+            "Signal that killed the instance's last process, if one did.",
+            instances().filter_map(|(l, i)| match i.last_exit? {
+                ChildExit::Code(_) => None,
+                ChildExit::Signal(sig) => Some((l, sig as i32)),
+            }),
+        );
+        family(
+            &mut out,
+            "yscloud_instance_start_duration_seconds",
+            "gauge",
+            "Time taken to start the instance's current or last process.",
+            instances().filter_map(|(l, i)| Some((l, i.start_duration?.as_secs_f64()))),
+        );
+        family(
+            &mut out,
+            "yscloud_instance_cpu_seconds_total",
+            "counter",
+            "CPU time used by the running instance, or its cgroup if it has one.",
+            usage
+                .iter()
+                .map(|(l, u)| (l.clone(), u.cpu_time.as_secs_f64())),
+        );
+        family(
+            &mut out,
+            "yscloud_instance_memory_bytes",
+            "gauge",
+            "Memory used by the running instance, or its cgroup if it has one.",
+            usage.iter().map(|(l, u)| (l.clone(), u.memory_bytes)),
+        );
+
+        let downloads = || {
+            state.downloads.iter().map(|(p, d)| {
+                let labels = format!(
+                    "deployment=\"{}\",package_id=\"{}\"",
+                    escape(&self.deployment_name),
+                    escape(p)
+                );
+                (labels, d)
+            })
+        };
+        family(
+            &mut out,
+            "yscloud_artifact_download_bytes_total",
+            "counter",
+            "Bytes of artifacts downloaded.",
+            downloads().map(|(l, d)| (l, d.bytes)),
+        );
+        family(
+            &mut out,
+            "yscloud_artifact_download_duration_seconds",
+            "gauge",
+            "Time taken by the last download of the artifact.",
+            downloads().map(|(l, d)| (l, d.duration.as_secs_f64())),
+        );
+
+        out
+    }
+
+    /// Serves the metrics at `path` from a thread of its own.  A socket
+    /// already at `path`, say of an earlier daemon, is replaced, but nothing
+    /// else is.
+    pub fn serve(&self, path: &Path) -> io::Result<()> {
+        match fs::symlink_metadata(path) {
+            Ok(meta) if meta.file_type().is_socket() => fs::remove_file(path)?,
+            Ok(_) => {
+                let msg = format!("{} exists and is not a socket", path.display());
+                return Err(io::Error::new(io::ErrorKind::AlreadyExists, msg));
+            }
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => (),
+            Err(err) => return Err(err),
+        }
+        let listener = UnixListener::bind(path)?;
+        let metrics = self.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let result = stream.and_then(|stream| metrics.respond(stream));
+                if let Err(err) = result {
+                    event!(Level::WARN, "serving metrics failed: {}", err);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    // Whatever was asked for, the answer is the metrics.
+    fn respond(&self, mut stream: UnixStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+        let started = Instant::now();
+        let mut request = Vec::new();
+        let mut buf = [0; 1024];
+        while !request.ends_with(b"\r\n\r\n")
+            && request.len() < 8192
+            && started.elapsed() < REQUEST_TIMEOUT
+        {
+            match stream.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => request.extend_from_slice(&buf[..n]),
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        let body = self.render();
+        write!(
+            stream,
+            "HTTP/1.0 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    }
+}
+
+fn family<V: Display>(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    samples: impl Iterator<Item = (String, V)>,
+) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    for (labels, value) in samples {
+        let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    use nix::sys::signal::Signal;
+    use nix::unistd::Pid;
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    use super::Metrics;
+    use crate::platform::{ChildExit, JournalEvent};
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::new("example-deployment");
+        let instance_id = Uuid::nil();
+        metrics.add_instance("org.yshi.idle", instance_id);
+        for (pid, exit) in &[
+            (100, ChildExit::Code(3)),
+            (101, ChildExit::Signal(Signal::SIGKILL)),
+        ] {
+            metrics.record(&JournalEvent::Started {
+                package_id: "org.yshi.echo",
+                instance_id,
+                pid: Pid::from_raw(*pid),
+                start_duration: Duration::from_millis(5),
+            });
+            metrics.record(&JournalEvent::Exited {
+                pid: Pid::from_raw(*pid),
+                exit: Some(*exit),
+            });
+        }
+        metrics.downloaded("org.yshi.echo", 1024, Duration::from_millis(1500));
+
+        let out = metrics.render();
+        let labels = |package_id: &str| {
+            format!(
+                "{{deployment=\"example-deployment\",package_id=\"{}\",instance_id=\"{}\"}}",
+                package_id, instance_id
+            )
+        };
+        for line in &[
+            format!("yscloud_instance_up{} 0", labels("org.yshi.echo")),
+            format!("yscloud_instance_up{} 0", labels("org.yshi.idle")),
+            format!("yscloud_instance_restarts_total{} 1", labels("org.yshi.echo")),
+            format!("yscloud_instance_last_exit_signal{} 9", labels("org.yshi.echo")),
+            format!("yscloud_instance_start_duration_seconds{} 0.005", labels("org.yshi.echo")),
+            "yscloud_artifact_download_bytes_total{deployment=\"example-deployment\",package_id=\"org.yshi.echo\"} 1024".to_string(),
+        ] {
+            assert!(out.lines().any(|l| l == line), "{} not in {}", line, out);
+        }
+        assert!(!out.contains("yscloud_instance_last_exit_code{"));
+    }
+
+    #[test]
+    fn test_serve_replaces_only_sockets() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("metrics.sock");
+        let metrics = Metrics::new("example-deployment");
+
+        fs::write(&path, b"not a socket").unwrap();
+        assert!(metrics.serve(&path).is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"not a socket");
+
+        fs::remove_file(&path).unwrap();
+        metrics.serve(&path).unwrap();
+        // as left by an earlier daemon.
+        metrics.serve(&path).unwrap();
+    }
+
+    #[test]
+    fn test_serve_past_stalled_scraper() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("metrics.sock");
+        let metrics = Metrics::new("example-deployment");
+        // more than fits in the socket buffer.
+        for i in 0..20000 {
+            metrics.add_instance(&format!("org.yshi.echo-{}", i), Uuid::nil());
+        }
+        metrics.serve(&path).unwrap();
+
+        let mut stalled = UnixStream::connect(&path).unwrap();
+        stalled.write_all(b"GET / HTTP/1.0\r\n\r\n").unwrap();
+
+        let mut scraper = UnixStream::connect(&path).unwrap();
+        scraper
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        scraper.write_all(b"GET / HTTP/1.0\r\n\r\n").unwrap();
+        let mut response = String::new();
+        scraper.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.0 200 OK\r\n"));
+        assert!(response.contains("org.yshi.echo-19999"));
+        drop(stalled);
+    }
+}
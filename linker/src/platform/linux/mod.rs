@@ -1,29 +1,38 @@
 use std::error::Error as StdError;
 use std::ffi::{CStr, CString};
 use std::fmt;
-use std::io;
+use std::io::{self, Seek, SeekFrom, Write};
 use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use std::collections::HashSet;
 use std::fs::File;
 
 use digest::{Digest, FixedOutput};
 use nix::fcntl::{fcntl, open, OFlag};
-use nix::sys::stat::Mode;
-use nix::unistd::{execveat, fork, lseek64, write, ForkResult, Gid, Pid, Uid, Whence};
+use nix::sys::signal::Signal;
+use nix::sys::stat::{fstat, Mode};
+use nix::errno::Errno;
+use nix::unistd::{
+    execveat, fork, lseek64, pipe2, read, write, ForkResult, Gid, Pid, Uid, Whence,
+};
 use sha2::{Sha256, Sha512};
 use sha3::{Sha3_512, Keccak512};
 use tracing::{event, Level};
 use users::{get_group_by_name, get_user_by_name};
 
-use yscloud_config_model::ImageType;
+use yscloud_config_model::{FdProtocol, ImageType};
 use memfd::{MemFd, MemFdOptions, SealFlag};
 use owned_fd::{OwnedFd, IntoOwnedFd};
 
-use super::posix_imp::relabel_file_descriptors;
-pub use super::posix_imp::run_reified;
+use super::posix_imp::{command_line, move_above, relabel_file_descriptors};
+use super::ResourceUsage;
+pub use super::posix_imp::{
+    run_adopted, run_reified, run_reified_dev, AdoptedChild, AdoptedExit, ChildExit, JournalEvent,
+    JournalFn, ReloadFn,
+};
 use crate::{Void, AppPreforkConfiguration};
 
 pub mod arch;
@@ -154,6 +163,11 @@ impl Executable {
         })
     }
 
+    pub fn try_clone(&self) -> io::Result<Executable> {
+        let file = self.file.try_clone().map_err(nix_error_to_io_error)?;
+        Ok(Executable { file })
+    }
+
     pub fn execute(&self, arguments: &[&CStr], env: &[&CStr]) -> io::Result<Void> {
         use nix::fcntl::{AtFlags, FcntlArg, FdFlag};
 
@@ -184,8 +198,177 @@ pub const PLATFORM_TRIPLES: &[&str] = &[
     "x86_64-unknown-linux",
 ];
 
-pub fn keep_hook(c: &AppPreforkConfiguration, keep_map: &mut [bool]) {
-    keep_map[c.artifact.0.as_raw_fd() as usize] = true;
+/// Moves the descriptors needed until exec above the range the component's
+/// files are placed in, returning them.
+pub fn keep_hook(c: &mut AppPreforkConfiguration, min_fd: RawFd) -> io::Result<Vec<RawFd>> {
+    move_above(&mut c.artifact.0.file, min_fd)?;
+    Ok(vec![c.artifact.0.file.as_raw_fd()])
+}
+
+/// How many connections accepted on `listeners` `pid` holds, for telling
+/// whether an on-demand component is still serving them.  Descriptors it
+/// opens for itself don't count.
+pub fn connection_count(pid: Pid, listeners: &[RawFd]) -> Option<usize> {
+    let mut held = HashSet::new();
+    for entry in std::fs::read_dir(format!("/proc/{}/fd", pid)).ok()? {
+        // descriptors come and go while we look.
+        let target = match entry.and_then(|entry| std::fs::read_link(entry.path())) {
+            Ok(target) => target,
+            Err(_) => continue,
+        };
+        let inode = target
+            .to_str()
+            .and_then(|t| t.strip_prefix("socket:["))
+            .and_then(|t| t.strip_suffix(']'))
+            .and_then(|t| t.parse::<u64>().ok());
+        held.extend(inode);
+    }
+    let mut listening = HashSet::new();
+    for fd in listeners {
+        listening.insert(fstat(*fd).ok()?.st_ino as u64);
+    }
+
+    // accepted connections share their listener's local address.  The
+    // listeners are ours, so they are in our network namespace.
+    let mut count = 0;
+    for table in &["tcp", "tcp6", "unix"] {
+        let data = match std::fs::read_to_string(format!("/proc/self/net/{}", table)) {
+            Ok(data) => data,
+            Err(_) => continue,
+        };
+        let sockets: Vec<(u64, &str)> = data
+            .lines()
+            .skip(1)
+            .filter_map(|line| socket_entry(table, line))
+            .collect();
+        let addresses: HashSet<&str> = sockets
+            .iter()
+            .filter(|(inode, _)| listening.contains(inode))
+            .map(|(_, address)| *address)
+            .collect();
+        count += sockets
+            .iter()
+            .filter(|(inode, address)| {
+                held.contains(inode) && !listening.contains(inode) && addresses.contains(address)
+            })
+            .count();
+    }
+    Some(count)
+}
+
+/// The inode and local address of a socket in one of `/proc/net`'s tables.
+/// Only the port is kept for TCP, as a wildcard listener's connections have
+/// addresses of their own.
+fn socket_entry<'a>(table: &str, line: &'a str) -> Option<(u64, &'a str)> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if table == "unix" {
+        // unnamed sockets have no path.
+        Some((fields.get(6)?.parse().ok()?, fields.get(7)?))
+    } else {
+        let port = fields.get(1)?.rsplit(':').next()?;
+        Some((fields.get(9)?.parse().ok()?, port))
+    }
+}
+
+/// CPU time used by `pid`'s threads.
+pub fn cpu_time(pid: Pid) -> Option<Duration> {
+    let mut total = 0;
+    for task in std::fs::read_dir(format!("/proc/{}/task", pid)).ok()? {
+        let schedstat = match task {
+            Ok(task) => task.path().join("schedstat"),
+            Err(_) => continue,
+        };
+        // threads come and go while we look.
+        let stat = match std::fs::read_to_string(schedstat) {
+            Ok(stat) => stat,
+            Err(_) => continue,
+        };
+        total += stat.split_whitespace().next()?.parse::<u64>().ok()?;
+    }
+    Some(Duration::from_nanos(total))
+}
+
+/// What `pid` uses: its cgroup's totals if it has a (v2) cgroup of its own,
+/// otherwise those of the process alone.
+pub fn resource_usage(pid: Pid) -> Option<ResourceUsage> {
+    let cgroup = |pid: &str| -> Option<String> {
+        let cgroups = std::fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+        cgroups
+            .lines()
+            .find_map(|l| l.strip_prefix("0::"))
+            .map(|path| path.to_string())
+    };
+    if let Some(path) = cgroup(&pid.to_string()).filter(|c| Some(c) != cgroup("self").as_ref()) {
+        if let Some(usage) = cgroup_usage(&Path::new("/sys/fs/cgroup").join(&path[1..])) {
+            return Some(usage);
+        }
+    }
+
+    let statm = std::fs::read_to_string(format!("/proc/{}/statm", pid)).ok()?;
+    let resident: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+    Some(ResourceUsage {
+        cpu_time: cpu_time(pid)?,
+        memory_bytes: resident * page_size,
+    })
+}
+
+fn cgroup_usage(dir: &Path) -> Option<ResourceUsage> {
+    let cpu_stat = std::fs::read_to_string(dir.join("cpu.stat")).ok()?;
+    let usage_usec = cpu_stat
+        .lines()
+        .find_map(|l| l.strip_prefix("usage_usec "))?
+        .parse()
+        .ok()?;
+    let memory = std::fs::read_to_string(dir.join("memory.current")).ok()?;
+    Some(ResourceUsage {
+        cpu_time: Duration::from_micros(usage_usec),
+        memory_bytes: memory.trim().parse().ok()?,
+    })
+}
+
+/// Makes orphaned descendants of our children ours to reap, rather than
+/// init's.
+pub fn set_child_subreaper() -> io::Result<()> {
+    if unsafe { libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// When `pid` started, in clock ticks since boot.  A recorded pid is only
+/// the same process while this is unchanged.
+pub fn process_start_time(pid: Pid) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // the command name may contain spaces and parentheses of its own.
+    let after_comm = &stat[stat.rfind(')')? + 1..];
+    after_comm.split_whitespace().nth(19)?.parse().ok()
+}
+
+/// A descriptor for `pid` that keeps referring to it after it has exited,
+/// even if the pid is reused.  It becomes readable when the process exits.
+pub fn pidfd_open(pid: Pid) -> io::Result<OwnedFd> {
+    let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid.as_raw(), 0) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { OwnedFd::from_raw_fd(fd as RawFd) })
+}
+
+pub fn pidfd_send_signal(pidfd: &OwnedFd, signal: Signal) -> io::Result<()> {
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_pidfd_send_signal,
+            pidfd.as_raw_fd(),
+            signal as libc::c_int,
+            std::ptr::null::<libc::siginfo_t>(),
+            0,
+        )
+    };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
 }
 
 pub trait SandboxingStrategy {
@@ -326,33 +509,40 @@ impl ExecExtrasBuilder {
     }
 }
 
-fn exec_artifact_child(ext: &ExecExtras, c: &AppPreforkConfiguration) -> io::Result<Void> {
+fn exec_artifact_child(
+    ext: &ExecExtras,
+    c: &mut AppPreforkConfiguration,
+    exec_notify: &mut OwnedFd,
+) -> io::Result<Void> {
     let package_id = c.package_id.clone();
-    let app_config = relabel_file_descriptors(&c)?;
-    let tmpfile = open(
-        "/tmp",
-        OFlag::O_RDWR | OFlag::O_TMPFILE,
-        Mode::S_IRUSR | Mode::S_IWUSR,
-    )
-    .map_err(|e| {
-        event!(Level::WARN, "error opening temporary: {:?}", e);
-        io_other(e)
-    })?;
-
+    let app_config = relabel_file_descriptors(c, Some(exec_notify))?;
     let data = serde_json::to_string(&app_config)?;
-    let data_len =
-        write(tmpfile, data.as_bytes()).map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
-
-    lseek64(tmpfile, 0, Whence::SeekSet)
-        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
-
-    assert_eq!(data_len, data.len());
-    let tmpfile = format!("{}\0", tmpfile);
-    let arguments: &[&CStr] = &[
-        CStr::from_bytes_with_nul(b"yscloud-executable\0").unwrap(),
-        CStr::from_bytes_with_nul(b"--config-fd\0").unwrap(),
-        CStr::from_bytes_with_nul(tmpfile.as_bytes()).unwrap(),
-    ];
+
+    let config_fd = match c.fd_protocol {
+        FdProtocol::ConfigFd => {
+            let tmpfile = open(
+                "/tmp",
+                OFlag::O_RDWR | OFlag::O_TMPFILE,
+                Mode::S_IRUSR | Mode::S_IWUSR,
+            )
+            .map_err(|e| {
+                event!(Level::WARN, "error opening temporary: {:?}", e);
+                io_other(e)
+            })?;
+
+            let data_len = write(tmpfile, data.as_bytes())
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+            lseek64(tmpfile, 0, Whence::SeekSet)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+            assert_eq!(data_len, data.len());
+            Some(tmpfile)
+        }
+        FdProtocol::ListenFds => None,
+    };
+
+    let (arguments, env) = command_line(c, config_fd)?;
     event!(
         Level::INFO,
         "running {} {:?} -- {}",
@@ -364,26 +554,63 @@ fn exec_artifact_child(ext: &ExecExtras, c: &AppPreforkConfiguration) -> io::Res
     if let Some(ref sandbox) = ext.sandboxing_strategy {
         sandbox.preexec()?;
     }
-    let env: &[&CStr] = &[
-        CStr::from_bytes_with_nul(b"RUST_BACKTRACE=1\0").unwrap(),
-        CStr::from_bytes_with_nul(b"YSCLOUD=1\0").unwrap(),
-    ];
-    c.artifact.execute(arguments, env)?;
+    let arguments: Vec<&CStr> = arguments.iter().map(|a| a.as_c_str()).collect();
+    let env: Vec<&CStr> = env.iter().map(|e| e.as_c_str()).collect();
+    c.artifact.execute(&arguments, &env)?;
 
     unreachable!();
 }
 
-pub fn exec_artifact(e: &ExecExtras, c: AppPreforkConfiguration) -> io::Result<Pid> {
+/// A sealed memfd holding `data`, for sending a configuration update.
+pub fn config_update_file(data: &[u8]) -> io::Result<File> {
+    let mut mem_fd = MemFdOptions::new()
+        .cloexec(true)
+        .allow_sealing(true)
+        .open("yscloud-config")
+        .map_err(nix_error_to_io_error)?;
+
+    mem_fd.write_all(data)?;
+    mem_fd.seek(SeekFrom::Start(0))?;
+    mem_fd
+        .seal(
+            SealFlag::F_SEAL_SEAL
+                | SealFlag::F_SEAL_SHRINK
+                | SealFlag::F_SEAL_GROW
+                | SealFlag::F_SEAL_WRITE,
+        )
+        .map_err(nix_error_to_io_error)?;
+
+    Ok(mem_fd.into())
+}
+
+/// Starts `c`, returning once the child has exec'd the artifact or given up.
+pub fn exec_artifact(e: &ExecExtras, mut c: AppPreforkConfiguration) -> io::Result<Pid> {
+    // the child's end is close-on-exec, so we see EOF when it gets that far.
+    let (exec_wait, exec_notify) = pipe2(OFlag::O_CLOEXEC).map_err(io_other)?;
+    let exec_wait = unsafe { OwnedFd::from_raw_fd(exec_wait) };
+    let mut exec_notify = unsafe { OwnedFd::from_raw_fd(exec_notify) };
+
     match unsafe { fork() } {
         Ok(ForkResult::Child) => {
-            if let Err(err) = exec_artifact_child(e, &c) {
+            drop(exec_wait);
+            if let Err(err) = exec_artifact_child(e, &mut c, &mut exec_notify) {
                 event!(Level::WARN, "failed to execute: {:?}", err);
                 std::process::exit(1);
             } else {
                 unreachable!();
             }
         }
-        Ok(ForkResult::Parent { child, .. }) => Ok(child),
+        Ok(ForkResult::Parent { child, .. }) => {
+            drop(exec_notify);
+            let mut buf = [0; 1];
+            loop {
+                match read(exec_wait.as_raw_fd(), &mut buf) {
+                    Err(nix::Error::Sys(Errno::EINTR)) => continue,
+                    _ => break,
+                }
+            }
+            Ok(child)
+        }
         Err(err) => Err(io::Error::new(io::ErrorKind::Other, err)),
     }
 }
@@ -2,20 +2,27 @@ use std::error::Error as StdError;
 use std::ffi::{CStr, CString};
 use std::fmt;
 use std::fs::File;
-use std::io;
+use std::io::{self, Seek, SeekFrom, Write};
 use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::RawFd;
 use std::path::{Path, PathBuf};
 
 use digest::FixedOutput;
+use nix::sys::signal::Signal;
 use nix::unistd::{execve, fork, lseek, unlink, write, ForkResult, Pid, Whence};
+use owned_fd::OwnedFd;
 use rand::{thread_rng, Rng};
 use sha2::Sha256;
 use tempfile::{tempdir, tempdir_in, TempDir};
 use tracing::{event, Level};
-use yscloud_config_model::ImageType;
-
-use super::posix_imp::relabel_file_descriptors;
-pub use super::posix_imp::run_reified;
+use yscloud_config_model::{FdProtocol, ImageType};
+
+use super::posix_imp::{command_line, relabel_file_descriptors};
+use super::ResourceUsage;
+pub use super::posix_imp::{
+    run_adopted, run_reified, run_reified_dev, AdoptedChild, AdoptedExit, ChildExit, JournalEvent,
+    JournalFn, ReloadFn,
+};
 use crate::AppPreforkConfiguration;
 use crate::Void;
 
@@ -120,6 +127,15 @@ impl Executable {
         }
     }
 
+    /// The copy doesn't own the original's temporary directory, so it must
+    /// not outlive the original.
+    pub fn try_clone(&self) -> io::Result<Executable> {
+        Ok(Executable {
+            path: self.path.clone(),
+            temporary_dir: None,
+        })
+    }
+
     pub fn execute(&self, arguments: &[&CStr], env: &[&CStr]) -> io::Result<Void> {
         let path_bytes = OsStrExt::as_bytes(self.path.as_os_str());
         let artifact_path = CString::new(path_bytes).expect("valid c-string");
@@ -137,27 +153,54 @@ pub const EXTENSION: &str = "";
 #[cfg(target_arch = "x86_64")]
 pub const PLATFORM_TRIPLES: &[&str] = &["x86_64-apple-darwin"];
 
-pub fn keep_hook(_: &AppPreforkConfiguration, _keep_map: &mut [bool]) {}
+/// Executables are run by path, so nothing needs to be kept until exec.
+pub fn keep_hook(_: &mut AppPreforkConfiguration, _min_fd: RawFd) -> io::Result<Vec<RawFd>> {
+    Ok(Vec::new())
+}
+
+/// Not available here; on-demand components are only stopped for idling
+/// on their listening sockets.
+pub fn connection_count(_: Pid, _: &[RawFd]) -> Option<usize> {
+    None
+}
+
+pub fn resource_usage(_: Pid) -> Option<ResourceUsage> {
+    None
+}
+
+/// Orphans go to launchd here.
+pub fn set_child_subreaper() -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Other, "no child subreapers on macOS"))
+}
+
+/// Not available, so nothing is ever re-adopted here.
+pub fn process_start_time(_: Pid) -> Option<u64> {
+    None
+}
+
+pub fn pidfd_open(_: Pid) -> io::Result<OwnedFd> {
+    Err(io::Error::new(io::ErrorKind::Other, "no pidfds on macOS"))
+}
+
+pub fn pidfd_send_signal(_: &OwnedFd, _: Signal) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Other, "no pidfds on macOS"))
+}
 
 pub struct ExecConfig {
     executable: super::Executable,
     arguments: Vec<CString>,
+    env: Vec<CString>,
     /* extra_files: Vec<OwnedFd>, */
 }
 
 fn execute_child(e: &ExecConfig) -> io::Result<Void> {
-    let env = &[
-        CStr::from_bytes_with_nul(b"RUST_BACKTRACE=1\0").unwrap(),
-        CStr::from_bytes_with_nul(b"YSCLOUD=1\0").unwrap(),
-    ];
-
-    // need to relabel file descriptors here.
     let mut cstrs: Vec<&CStr> = Vec::new();
     for arg in &e.arguments {
         cstrs.push(arg);
     }
+    let env: Vec<&CStr> = e.env.iter().map(|v| v.as_c_str()).collect();
 
-    e.executable.execute(&cstrs[..], env)?;
+    e.executable.execute(&cstrs[..], &env[..])?;
 
     unreachable!();
 }
@@ -202,58 +245,62 @@ impl ExecExtrasBuilder {
 // let path_bytes = OsStrExt::as_bytes(artifact_path.as_os_str());
 // let artifact_path = CString::new(path_bytes).expect("valid c-string");
 
-fn exec_artifact_child(e: &ExecExtras, c: AppPreforkConfiguration) -> io::Result<Void> {
+fn exec_artifact_child(e: &ExecExtras, mut c: AppPreforkConfiguration) -> io::Result<Void> {
     use nix::fcntl::open;
     use nix::fcntl::OFlag;
     use nix::sys::stat::Mode;
 
     let package_id = c.package_id.clone();
 
-    let app_config = relabel_file_descriptors(&c)?;
-
-    let path = format!(
-        "/tmp/yscloud-{}-{}",
-        c.instance_id,
-        thread_rng().gen::<u64>()
-    );
+    let app_config = relabel_file_descriptors(&mut c, None)?;
+    let data = serde_json::to_string(&app_config)?;
 
-    let tmpfile = open(
-        &path as &str,
-        OFlag::O_CREAT | OFlag::O_RDWR,
-        Mode::S_IRUSR | Mode::S_IWUSR,
-    )
-    .map_err(|err| {
-        event!(
-            Level::WARN,
-            "error opening temporary ({}:{}): {:?}",
-            file!(),
-            line!(),
-            err
-        );
-        io::Error::new(io::ErrorKind::Other, err)
-    })?;
+    let config_fd = match c.fd_protocol {
+        FdProtocol::ConfigFd => {
+            let path = format!(
+                "/tmp/yscloud-{}-{}",
+                c.instance_id,
+                thread_rng().gen::<u64>()
+            );
+
+            let tmpfile = open(
+                &path as &str,
+                OFlag::O_CREAT | OFlag::O_RDWR,
+                Mode::S_IRUSR | Mode::S_IWUSR,
+            )
+            .map_err(|err| {
+                event!(
+                    Level::WARN,
+                    "error opening temporary ({}:{}): {:?}",
+                    file!(),
+                    line!(),
+                    err
+                );
+                io::Error::new(io::ErrorKind::Other, err)
+            })?;
+
+            if let Err(err) = unlink(&path as &str) {
+                event!(
+                    Level::WARN,
+                    "failed to unlink temporary file {}: {}",
+                    path,
+                    err
+                );
+            }
 
-    if let Err(err) = unlink(&path as &str) {
-        event!(
-            Level::WARN,
-            "failed to unlink temporary file {}: {}",
-            path,
-            err
-        );
-    }
+            let data_len = write(tmpfile, data.as_bytes())
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
 
-    let data = serde_json::to_string(&app_config)?;
-    let data_len =
-        write(tmpfile, data.as_bytes()).map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+            lseek(tmpfile, 0, Whence::SeekSet)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
 
-    lseek(tmpfile, 0, Whence::SeekSet).map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+            assert_eq!(data_len, data.len());
+            Some(tmpfile)
+        }
+        FdProtocol::ListenFds => None,
+    };
 
-    assert_eq!(data_len, data.len());
-    let arguments = vec![
-        CString::new("yscloud-executable").unwrap(),
-        CString::new("--config-fd").unwrap(),
-        CString::new(format!("{}", tmpfile)).unwrap(),
-    ];
+    let (arguments, env) = command_line(&c, config_fd)?;
 
     event!(
         Level::TRACE,
@@ -278,11 +325,20 @@ fn exec_artifact_child(e: &ExecExtras, c: AppPreforkConfiguration) -> io::Result
     execute_child(&ExecConfig {
         executable: c.artifact,
         arguments,
+        env,
     })?;
 
     unreachable!();
 }
 
+/// An unlinked temporary holding `data`, for sending a configuration update.
+pub fn config_update_file(data: &[u8]) -> io::Result<File> {
+    let mut file = tempfile::tempfile()?;
+    file.write_all(data)?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(file)
+}
+
 pub fn exec_artifact(e: &ExecExtras, c: AppPreforkConfiguration) -> io::Result<Pid> {
     match unsafe { fork() } {
         Ok(ForkResult::Child) => {
@@ -33,10 +33,24 @@ pub use self::linux::container as container;
 #[cfg(any(target_os = "macos", target_os = "linux"))]
 use self::posix as posix_imp;
 
-pub use self::imp::{run_reified, ExecExtras, ExecExtrasBuilder};
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub use self::posix_imp::app_configuration;
+
+pub use self::imp::{
+    pidfd_open, process_start_time, resource_usage, run_adopted, run_reified, run_reified_dev,
+    set_child_subreaper, AdoptedChild, AdoptedExit, ChildExit, ExecExtras, ExecExtrasBuilder,
+    JournalEvent, JournalFn, ReloadFn,
+};
 pub const EXTENSION: &str = imp::EXTENSION;
 pub const PLATFORM_TRIPLES: &[&str] = imp::PLATFORM_TRIPLES;
 
+/// CPU time and memory used by a component.
+#[derive(Debug, Clone, Copy)]
+pub struct ResourceUsage {
+    pub cpu_time: std::time::Duration,
+    pub memory_bytes: u64,
+}
+
 pub struct ExecutableFactory {
     os_impl: imp::ExecutableFactory,
     common: ExecutableFactoryCommon,
@@ -112,6 +126,10 @@ impl Executable {
         imp::Executable::open(path).map(Executable)
     }
 
+    pub fn try_clone(&self) -> io::Result<Executable> {
+        self.0.try_clone().map(Executable)
+    }
+
     pub fn execute(&self, arguments: &[&CStr], env: &[&CStr]) -> io::Result<Void> {
         imp::Executable::execute(&self.0, arguments, env)
     }
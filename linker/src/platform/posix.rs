@@ -1,61 +1,171 @@
 use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs;
 use std::io;
-use std::os::unix::io::AsRawFd;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
+use nix::errno::Errno;
+use nix::fcntl::{fcntl, FcntlArg};
+use nix::poll::{poll, PollFd, PollFlags};
 use nix::sys::signal::{kill, Signal};
-use nix::sys::wait::{waitpid, WaitStatus};
-use nix::unistd::Pid;
+use nix::sys::socket::{sendmsg, ControlMessage, MsgFlags};
+use nix::sys::uio::IoVec;
+use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
+use nix::unistd::{dup2, getpid, Pid};
+use owned_fd::OwnedFd;
 use tracing::{event, span, Level};
+use yscloud_config_model::{
+    Activation, DeploymentManifest, FdProtocol, Ksuid, ServiceFileDirection, CONFIG_UPDATE_MESSAGE,
+};
 
 use super::imp;
-use crate::platform::exec_artifact;
-use crate::{AppConfiguration, AppPreforkConfiguration, FileDescriptorInfo};
+use crate::artifact::direct_load_artifact;
+use crate::identity_signer::{self, Identity, IdentitySigner};
+use crate::platform::{exec_artifact, ExecExtras, Executable};
+use crate::workdir;
+use crate::{
+    AppConfiguration, AppPreforkConfiguration, ExecSomething, FileDescriptorInfo,
+    ServiceFileDescriptor,
+};
 
-pub fn relabel_file_descriptors(c: &AppPreforkConfiguration) -> io::Result<AppConfiguration> {
-    // this seems ghetto?
+// the first descriptor after stdio.
+const FIRST_FD: RawFd = 3;
 
-    let mut keep_map = [false; 2048];
-    for v in keep_map.iter_mut().take(3) {
-        *v = true;
+#[cfg(target_os = "linux")]
+const FD_DIR: &str = "/proc/self/fd";
+
+#[cfg(target_os = "macos")]
+const FD_DIR: &str = "/dev/fd";
+
+/// Runs in the forked child.  The component's files are moved to 3, 4, ...
+/// as `file_nums` has it, followed by the control socket, and everything else
+/// above stdio is closed, except `also_keep`, which is moved above them.
+pub fn relabel_file_descriptors(
+    c: &mut AppPreforkConfiguration,
+    also_keep: Option<&mut OwnedFd>,
+) -> io::Result<AppConfiguration> {
+    let placed_count = c.files.len() + c.control.iter().count();
+    let above = FIRST_FD + placed_count as RawFd;
+
+    // move everything out of the way first, so placing one descriptor can't
+    // clobber another that is yet to be placed.
+    for f in &mut c.files {
+        move_above(&mut f.file, above)?;
+    }
+    if let Some(ref mut control) = c.control {
+        move_above(control, above)?;
+    }
+    let mut keep = imp::keep_hook(c, above)?;
+    if let Some(fd) = also_keep {
+        move_above(fd, above)?;
+        keep.push(fd.as_raw_fd());
+    }
+
+    let control_num = FIRST_FD + c.files.len() as RawFd;
+    let targets = file_nums(c).into_iter().chain(Some(control_num));
+    let placed = c
+        .files
+        .iter_mut()
+        .map(|f| &mut f.file)
+        .chain(c.control.iter_mut());
+    for (fd, target) in placed.zip(targets) {
+        // unlike the original, the new descriptor isn't close-on-exec.
+        dup2(fd.as_raw_fd(), target).map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        *fd = unsafe { OwnedFd::from_raw_fd(target) };
     }
+    event!(
+        Level::DEBUG,
+        "placed {} files for {}:{}",
+        placed_count,
+        c.package_id,
+        c.instance_id
+    );
 
-    imp::keep_hook(c, &mut keep_map[..]);
+    close_descriptors_from(above, &keep)?;
 
-    for f in &c.files {
-        let file_num = f.file.as_raw_fd() as usize;
-        if keep_map.len() < file_num {
-            event!(
-                Level::WARN,
-                "a keep-file is over {} - are we leaking file descriptors?",
-                keep_map.len()
-            );
-            continue;
+    Ok(app_configuration(c))
+}
+
+/// Moves `fd` to the lowest free descriptor at or above `min_fd`.
+pub fn move_above(fd: &mut OwnedFd, min_fd: RawFd) -> io::Result<()> {
+    let moved = fcntl(fd.as_raw_fd(), FcntlArg::F_DUPFD_CLOEXEC(min_fd))
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+    // closes the original.
+    *fd = unsafe { OwnedFd::from_raw_fd(moved) };
+    Ok(())
+}
+
+/// Closes every descriptor from `first` up, except those in `keep`.
+fn close_descriptors_from(first: RawFd, keep: &[RawFd]) -> io::Result<()> {
+    let mut keep = keep.to_vec();
+    keep.sort();
+
+    #[cfg(target_os = "linux")]
+    {
+        if close_range_except(first, &keep).is_ok() {
+            return Ok(());
         }
-        event!(
-            Level::DEBUG,
-            "keeping {} for {}: {:?}",
-            file_num,
-            c.artifact,
-            f.service_name
-        );
-        keep_map[file_num] = true;
     }
-    for (i, keep) in keep_map.iter().enumerate() {
-        if !*keep && nix::unistd::close(i as i32).is_ok() {
-            event!(
-                Level::DEBUG,
-                "closed {} for {}:{}",
-                i,
-                c.package_id,
-                c.instance_id
-            );
+
+    // the directory's own descriptor is among these, but it's been closed
+    // by the time we get to it.
+    let open: Vec<RawFd> = fs::read_dir(FD_DIR)?
+        .filter_map(|e| e.ok()?.file_name().to_str()?.parse().ok())
+        .collect();
+    for fd in open {
+        if first <= fd && keep.binary_search(&fd).is_err() {
+            let _ = nix::unistd::close(fd);
         }
     }
 
-    Ok(AppConfiguration {
+    Ok(())
+}
+
+// fails on kernels before 5.9, which don't have close_range.
+#[cfg(target_os = "linux")]
+fn close_range_except(first: RawFd, sorted_keep: &[RawFd]) -> io::Result<()> {
+    fn close_range(low: libc::c_uint, high: libc::c_uint) -> io::Result<()> {
+        if unsafe { libc::syscall(libc::SYS_close_range, low, high, 0) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    let mut low = first as libc::c_uint;
+    for &fd in sorted_keep.iter().filter(|&&fd| first <= fd) {
+        let fd = fd as libc::c_uint;
+        if low < fd {
+            close_range(low, fd - 1)?;
+        }
+        low = fd + 1;
+    }
+    close_range(low, libc::c_uint::MAX)
+}
+
+/// The configuration a component is started with.  The layout is fixed by
+/// `relabel_file_descriptors`, so the linker can compute it before forking.
+/// Where each of `c.files` is placed: in declaration order, except that
+/// listening sockets come first for `FdProtocol::ListenFds`, as `LISTEN_FDS`
+/// only counts those and from 3 up.
+fn file_nums(c: &AppPreforkConfiguration) -> Vec<RawFd> {
+    let mut order: Vec<usize> = (0..c.files.len()).collect();
+    if c.fd_protocol == FdProtocol::ListenFds {
+        order.sort_by_key(|&i| c.files[i].direction != ServiceFileDirection::ServingListening);
+    }
+    let mut nums = vec![0; c.files.len()];
+    for (slot, i) in order.into_iter().enumerate() {
+        nums[i] = FIRST_FD + slot as RawFd;
+    }
+    nums
+}
+
+pub fn app_configuration(c: &AppPreforkConfiguration) -> AppConfiguration {
+    AppConfiguration {
         deployment_name: c.deployment_name.clone(),
         package_id: c.package_id.clone(),
         instance_id: c.instance_id,
@@ -63,58 +173,463 @@ pub fn relabel_file_descriptors(c: &AppPreforkConfiguration) -> io::Result<AppCo
         files: c
             .files
             .iter()
-            .map(|f| FileDescriptorInfo {
-                file_num: f.file.as_raw_fd(),
+            .zip(file_nums(c))
+            .map(|(f, file_num)| FileDescriptorInfo {
+                file_num,
                 direction: f.direction.clone(),
                 service_name: f.service_name.clone(),
                 remote: f.remote.clone(),
+                trace_id: f.trace_id,
             })
             .collect(),
         extras: c.extras.clone(),
-    })
+        control_fd: c
+            .control
+            .as_ref()
+            .map(|_| FIRST_FD + c.files.len() as RawFd),
+        trace_id: Some(c.trace_id),
+    }
+}
+
+/// The argv and environment a component is executed with, after its files
+/// have been relabelled.  `config_fd` holds its `AppConfiguration` and is
+/// left out for `FdProtocol::ListenFds`.
+pub fn command_line(
+    c: &AppPreforkConfiguration,
+    config_fd: Option<RawFd>,
+) -> io::Result<(Vec<CString>, Vec<CString>)> {
+    let mut arguments = vec!["yscloud-executable".to_string()];
+    let mut env: Vec<String> = c
+        .environment
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    env.push("YSCLOUD=1".to_string());
+
+    match c.fd_protocol {
+        FdProtocol::ConfigFd => {
+            let config_fd = config_fd.ok_or_else(|| {
+                io::Error::new(io::ErrorKind::Other, "missing configuration file")
+            })?;
+            arguments.push("--config-fd".to_string());
+            arguments.push(config_fd.to_string());
+        }
+        FdProtocol::ListenFds => {
+            let mut listening: Vec<(RawFd, &str)> = c
+                .files
+                .iter()
+                .zip(file_nums(c))
+                .filter(|(f, _)| f.direction == ServiceFileDirection::ServingListening)
+                .map(|(f, file_num)| (file_num, &f.service_name[..]))
+                .collect();
+            listening.sort();
+            let names: Vec<&str> = listening.iter().map(|(_, name)| *name).collect();
+            env.push(format!("LISTEN_FDS={}", names.len()));
+            env.push(format!("LISTEN_PID={}", getpid()));
+            env.push(format!("LISTEN_FDNAMES={}", names.join(":")));
+        }
+    }
+    arguments.extend(c.arguments.iter().cloned());
+
+    let to_cstrings = |strings: Vec<String>| {
+        strings
+            .into_iter()
+            .map(|s| CString::new(s).map_err(|err| io::Error::new(io::ErrorKind::Other, err)))
+            .collect::<io::Result<Vec<_>>>()
+    };
+    Ok((to_cstrings(arguments)?, to_cstrings(env)?))
+}
+
+/// Loads the deployment again, when the linker gets SIGHUP.
+pub type ReloadFn = Box<dyn FnMut() -> Result<DeploymentManifest, String> + Send>;
+
+/// How a child ended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChildExit {
+    Code(i32),
+    Signal(Signal),
+}
+
+/// A child `run_reified` started or lost.
+pub enum JournalEvent<'a> {
+    Started {
+        package_id: &'a str,
+        instance_id: uuid::Uuid,
+        pid: Pid,
+        // from forking until the artifact was exec'd, or the child gave up.
+        start_duration: Duration,
+    },
+    Exited {
+        pid: Pid,
+        // `None` for adopted children, whose status goes to their parent.
+        exit: Option<ChildExit>,
+    },
+}
+
+/// Told about every `JournalEvent`, so that the daemon can re-adopt its
+/// children after a restart and keep its metrics.  With one, SIGUSR2 makes
+/// `run_reified` return and leave its children running.
+pub type JournalFn = Box<dyn FnMut(JournalEvent)>;
+
+/// A running component's configuration, kept so that it can be updated.
+struct LiveInstance {
+    config: AppConfiguration,
+    // the linker's end, for packages with `live_config`.
+    control: Option<OwnedFd>,
 }
 
-pub fn run_reified(reified: Vec<crate::ExecSomething>) {
-    #[derive(Clone, Debug)]
+/// Sends changed extras to the components that accept them.  Anything else
+/// that changed needs a restart, which is left to the operator.
+fn reload_configuration(instances: &mut HashMap<String, LiveInstance>, reload: &mut ReloadFn) {
+    let dm = match reload() {
+        Ok(dm) => dm,
+        Err(err) => {
+            event!(
+                Level::ERROR,
+                "reloading the deployment failed, keeping the running configuration: {}",
+                err
+            );
+            return;
+        }
+    };
+
+    for component in &dm.components {
+        let instance = match instances.get_mut(&component.package_id) {
+            Some(instance) => instance,
+            None => {
+                event!(
+                    Level::WARN,
+                    "{} is not running, restart the deployment to start it",
+                    component.package_id
+                );
+                continue;
+            }
+        };
+        if instance.config.extras == component.extras {
+            continue;
+        }
+        let control = match instance.control {
+            Some(ref control) => control,
+            None => {
+                event!(
+                    Level::WARN,
+                    "{} doesn't accept live configuration, restart it to apply the change",
+                    component.package_id
+                );
+                continue;
+            }
+        };
+
+        let mut config = instance.config.clone();
+        config.extras = component.extras.clone();
+        match send_config_update(control, &config) {
+            Ok(()) => {
+                event!(
+                    Level::INFO,
+                    "sent new configuration to {}",
+                    component.package_id
+                );
+                instance.config = config;
+            }
+            Err(err) => event!(
+                Level::WARN,
+                "sending new configuration to {} failed: {}",
+                component.package_id,
+                err
+            ),
+        }
+    }
+}
+
+fn send_config_update(control: &OwnedFd, config: &AppConfiguration) -> io::Result<()> {
+    let data = serde_json::to_vec(config)?;
+    let file = imp::config_update_file(&data)?;
+
+    let fds = [file.as_raw_fd()];
+    let iov = [IoVec::from_slice(CONFIG_UPDATE_MESSAGE)];
+    let cmsgs = [ControlMessage::ScmRights(&fds)];
+    // a component that isn't reading its updates mustn't block the linker.
+    sendmsg(
+        control.as_raw_fd(),
+        &iov,
+        &cmsgs,
+        MsgFlags::MSG_DONTWAIT,
+        None,
+    )
+    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+    Ok(())
+}
+
+// How often `run_reified` looks for exited children and idle components,
+// while waiting for connections to on-demand ones.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// The least time between two starts of an intrinsic binary, so one that
+// can't start doesn't keep us busy.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+// How long children get to exit after SIGTERM before they are sent SIGKILL.
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Holds the sockets of a component that is started more than once: an
+/// on-demand component is started when a connection arrives, an intrinsic
+/// binary again whenever it exits.  Its consumers keep their connections
+/// meanwhile, since we hold the other ends.
+struct Activator {
+    template: InstanceTemplate,
+    artifact: Executable,
+    extras: ExecExtras,
+    // `None` for intrinsics, which are never idle.
+    idle_timeout: Option<Duration>,
+    pid: Option<Pid>,
+    last_active: Instant,
+    // when it last failed to start, so a connection it can't be started for
+    // doesn't keep us busy.
+    failed_at: Option<Instant>,
+}
+
+impl Activator {
+    fn new(
+        extras: ExecExtras,
+        cfg: AppPreforkConfiguration,
+        idle_timeout: Option<Duration>,
+        signer: Option<IdentitySigner>,
+    ) -> io::Result<Activator> {
+        let template = InstanceTemplate::new(&cfg, signer)?;
+        if idle_timeout.is_some() && template.listeners().next().is_none() {
+            let msg = format!("{} has no listening sockets", template.package_id);
+            return Err(io::Error::new(io::ErrorKind::Other, msg));
+        }
+
+        Ok(Activator {
+            template,
+            artifact: cfg.artifact,
+            extras,
+            idle_timeout,
+            pid: None,
+            last_active: Instant::now(),
+            failed_at: None,
+        })
+    }
+
+    fn start(&mut self) -> io::Result<Pid> {
+        let cfg = self.template.instantiate(self.artifact.try_clone()?)?;
+        let pid = exec_artifact(&self.extras, cfg)?;
+        self.pid = Some(pid);
+        self.last_active = Instant::now();
+        self.failed_at = None;
+        Ok(pid)
+    }
+
+    /// A running component is active while connections are waiting to be
+    /// accepted and while it holds connections it accepted.  Work it does on
+    /// its own, such as timers, doesn't count.
+    fn is_idle(&mut self) -> bool {
+        let (pid, idle_timeout) = match (self.pid, self.idle_timeout) {
+            (Some(pid), Some(idle_timeout)) => (pid, idle_timeout),
+            _ => return false,
+        };
+
+        let listeners: Vec<RawFd> = self.template.listeners().collect();
+        let active = listeners.iter().any(|fd| readable(*fd))
+            || imp::connection_count(pid, &listeners).map_or(false, |count| 0 < count);
+
+        if active {
+            self.last_active = Instant::now();
+        }
+        idle_timeout <= self.last_active.elapsed()
+    }
+}
+
+fn readable(fd: RawFd) -> bool {
+    let mut fds = [PollFd::new(fd, PollFlags::POLLIN)];
+    match poll(&mut fds, 0) {
+        Ok(n) => 0 < n,
+        Err(_) => false,
+    }
+}
+
+/// Waits up to `timeout` for a connection to an on-demand component,
+/// returning the indices of those that got one.  Running components that
+/// were active during the last `POLL_INTERVAL` aren't watched, so a slow
+/// `accept` doesn't keep waking us up.
+fn wait_for_connections(activators: &[Activator], timeout: Duration) -> Vec<usize> {
+    let mut owners = Vec::new();
+    let mut fds = Vec::new();
+    for (i, activator) in activators.iter().enumerate() {
+        if activator.idle_timeout.is_none() {
+            continue;
+        }
+        if activator.pid.is_some() && activator.last_active.elapsed() < POLL_INTERVAL {
+            continue;
+        }
+        if let Some(failed_at) = activator.failed_at {
+            if failed_at.elapsed() < RESTART_DELAY {
+                continue;
+            }
+        }
+        for fd in activator.template.listeners() {
+            owners.push(i);
+            fds.push(PollFd::new(fd, PollFlags::POLLIN));
+        }
+    }
+
+    // interrupted by a signal, handled elsewhere.
+    if poll(&mut fds, timeout.as_millis() as i32).is_err() {
+        return Vec::new();
+    }
+
+    let mut ready: Vec<usize> = fds
+        .iter()
+        .zip(owners)
+        .filter(|(fd, _)| fd.revents().map_or(false, |r| !r.is_empty()))
+        .map(|(_, i)| i)
+        .collect();
+    ready.dedup();
+    ready
+}
+
+pub fn run_reified(
+    reified: Vec<crate::ExecSomething>,
+    reload: Option<ReloadFn>,
+    journal: Option<JournalFn>,
+) {
+    #[derive(Debug)]
     struct ChildInfo {
         package_name: String,
-        sent_kill: Arc<AtomicBool>,
-        running: Arc<AtomicBool>,
+        sent_kill: bool,
+        // when an idle on-demand component was sent SIGTERM.
+        idle_stop: Option<Instant>,
+        // index into `activators`, for on-demand components.
+        activator: Option<usize>,
     }
 
     let span = span!(Level::INFO, "run_reified");
     let _span_entered = span.enter();
 
+    let detachable = journal.is_some();
+    let mut journal = journal;
+    let mut record = |event: JournalEvent| {
+        if let Some(ref mut journal) = journal {
+            journal(event);
+        }
+    };
+
     let mut pids = HashMap::<Pid, ChildInfo>::new();
+    let mut activators = Vec::new();
+    let mut instances = HashMap::<String, LiveInstance>::new();
+    let mut ephemeral_workdirs = Vec::new();
+    // once some are running, a component that can't be started stops the
+    // rest rather than taking them down with a panic.
+    let mut start_failed = false;
     for a in reified {
+        ephemeral_workdirs.extend(a.ephemeral_workdir);
         let package_id = a.cfg.package_id.clone();
+        instances.insert(
+            package_id.clone(),
+            LiveInstance {
+                config: app_configuration(&a.cfg),
+                control: a.control,
+            },
+        );
+
+        if let Activation::OnDemand { idle_timeout_secs } = a.activation {
+            let idle_timeout = Some(Duration::from_secs(idle_timeout_secs));
+            let activator = match Activator::new(a.extras, a.cfg, idle_timeout, a.identity_signer) {
+                Ok(activator) => activator,
+                Err(err) => {
+                    event!(Level::ERROR, "failed to set up {}: {}", package_id, err);
+                    start_failed = true;
+                    break;
+                }
+            };
+            event!(
+                Level::INFO,
+                package_id = &package_id[..],
+                "waiting for the first connection"
+            );
+            activators.push(activator);
+            continue;
+        }
+        if a.intrinsic {
+            let mut activator = match Activator::new(a.extras, a.cfg, None, a.identity_signer) {
+                Ok(activator) => activator,
+                Err(err) => {
+                    event!(Level::ERROR, "failed to set up {}: {}", package_id, err);
+                    start_failed = true;
+                    break;
+                }
+            };
+            let started = Instant::now();
+            let pid = match activator.start() {
+                Ok(pid) => pid,
+                Err(err) => {
+                    // restarted after `RESTART_DELAY`, like one that exited.
+                    event!(Level::ERROR, "failed to start {}: {}", package_id, err);
+                    activator.last_active = Instant::now();
+                    activators.push(activator);
+                    continue;
+                }
+            };
+            event!(Level::DEBUG, package_id = &package_id[..], child.pid = ?pid, "started intrinsic");
+            record(JournalEvent::Started {
+                package_id: &package_id,
+                instance_id: activator.template.instance_id,
+                pid,
+                start_duration: started.elapsed(),
+            });
+            pids.insert(
+                pid,
+                ChildInfo {
+                    package_name: package_id,
+                    sent_kill: false,
+                    idle_stop: None,
+                    activator: Some(activators.len()),
+                },
+            );
+            activators.push(activator);
+            continue;
+        }
 
         event!(
             Level::DEBUG,
             package_id = &package_id[..],
             "creating process"
         );
-        let child = exec_artifact(&a.extras, a.cfg).unwrap();
+        let instance_id = a.cfg.instance_id;
+        let started = Instant::now();
+        let child = match exec_artifact(&a.extras, a.cfg) {
+            Ok(child) => child,
+            Err(err) => {
+                event!(Level::ERROR, "failed to start {}: {}", package_id, err);
+                start_failed = true;
+                break;
+            }
+        };
         event!(Level::DEBUG, package_id = &package_id[..], child.pid = ?child, "created process");
+        record(JournalEvent::Started {
+            package_id: &package_id,
+            instance_id,
+            pid: child,
+            start_duration: started.elapsed(),
+        });
 
         pids.insert(
             child,
             ChildInfo {
                 package_name: package_id,
-                sent_kill: Arc::new(AtomicBool::new(false)),
-                running: Arc::new(AtomicBool::new(true)),
+                sent_kill: false,
+                idle_stop: None,
+                activator: None,
             },
         );
     }
 
-    fn kill_all(pids: &HashMap<Pid, ChildInfo>, second_kill: bool) {
+    fn kill_all(pids: &mut HashMap<Pid, ChildInfo>, second_kill: bool) {
         for (pid, info) in pids {
-            if info.running.load(Ordering::SeqCst)
-                && (!info.sent_kill.load(Ordering::SeqCst) || second_kill)
-            {
-                if !second_kill {
-                    info.sent_kill.store(true, Ordering::SeqCst);
-                }
+            if !info.sent_kill || second_kill {
+                info.sent_kill = true;
                 event!(
                     Level::INFO,
                     "sending {} ({}) SIGTERM",
@@ -135,75 +650,683 @@ pub fn run_reified(reified: Vec<crate::ExecSomething>) {
 
     use signal_hook::iterator::Signals;
     let signals = Signals::new(&[signal_hook::SIGINT]).unwrap();
+    // without a reload function SIGHUP keeps its default, terminating us.
+    if reload.is_some() {
+        signals.add_signal(signal_hook::SIGHUP).unwrap();
+    }
+    let detach = Arc::new(AtomicBool::new(false));
+    if detachable {
+        signal_hook::flag::register(signal_hook::SIGUSR2, Arc::clone(&detach)).unwrap();
+    }
 
-    let kill_targets = pids.clone();
+    // the children change as on-demand components come and go, so they are
+    // signalled from here, after the signal thread counts the interrupts.
+    let interrupts = Arc::new(AtomicUsize::new(0));
+    let interrupts_seen = Arc::clone(&interrupts);
     thread::spawn(move || {
-        if let Some(sig) = signals.forever().next() {
-            signals.close();
+        let mut reload = reload;
+        for sig in signals.forever() {
+            if sig == signal_hook::SIGHUP {
+                event!(Level::INFO, "got {}, reloading configuration", sig);
+                if let Some(ref mut reload) = reload {
+                    reload_configuration(&mut instances, reload);
+                }
+                continue;
+            }
+
+            if interrupts_seen.fetch_add(1, Ordering::SeqCst) == 0 {
+                event!(
+                    Level::INFO,
+                    "got {}, signaling to children to terminate",
+                    sig
+                );
+            } else {
+                signals.close();
+                event!(
+                    Level::INFO,
+                    "got {}, signaling to children to terminate (2nd attempt)",
+                    sig
+                );
+            }
+        }
+    });
+
+    let mut interrupts_handled = 0;
+    let mut stopping = start_failed;
+    let mut stopping_since = None;
+    let mut child_exited_nonzero = start_failed;
+    if start_failed {
+        kill_all(&mut pids, false);
+    }
+    loop {
+        if detach.load(Ordering::SeqCst) && !stopping {
+            // the next linker couldn't start them again: it would have
+            // neither their listening sockets nor the other ends of their
+            // connections.
+            if !activators.is_empty() {
+                detach.store(false, Ordering::SeqCst);
+                event!(
+                    Level::WARN,
+                    "got SIGUSR2, but {} on-demand components or intrinsics can't be handed over, not detaching",
+                    activators.len()
+                );
+            } else {
+                event!(
+                    Level::WARN,
+                    "got SIGUSR2, leaving {} children running for the next linker",
+                    pids.len()
+                );
+                return;
+            }
+        }
+
+        let interrupted = interrupts.load(Ordering::SeqCst);
+        if interrupts_handled < interrupted {
+            interrupts_handled = interrupted;
+            stopping = true;
+            kill_all(&mut pids, 1 < interrupted);
+        }
+
+        loop {
+            let (pid, failed, exit) = match waitpid(None, Some(WaitPidFlag::WNOHANG)) {
+                Ok(WaitStatus::Exited(pid, exit_code)) => {
+                    if let Some(child_info) = pids.get(&pid) {
+                        event!(
+                            Level::INFO,
+                            "child {} exited {}",
+                            child_info.package_name,
+                            exit_code
+                        );
+                    }
+                    (pid, exit_code != 0, ChildExit::Code(exit_code))
+                }
+                // literally why.
+                Ok(WaitStatus::Signaled(pid, sig, cored)) => {
+                    if let Some(child_info) = pids.get(&pid) {
+                        event!(
+                            Level::INFO,
+                            "child {} exited via signal {}",
+                            child_info.package_name,
+                            sig
+                        );
+                    }
+                    // we might also want to detect bad exit signals?
+                    (pid, cored, ChildExit::Signal(sig))
+                }
+                Ok(WaitStatus::StillAlive) | Err(nix::Error::Sys(Errno::ECHILD)) => break,
+                Ok(ws) => {
+                    event!(Level::WARN, "waitpid got an unexpected {:?}", ws);
+                    continue;
+                }
+                Err(err) => {
+                    panic!("waitpid err {}", err);
+                }
+            };
+
+            let child_info = match pids.remove(&pid) {
+                Some(child_info) => child_info,
+                None => continue,
+            };
+            record(JournalEvent::Exited {
+                pid,
+                exit: Some(exit),
+            });
+            match child_info.activator {
+                // back to waiting for a connection, or to be restarted.
+                Some(i) => activators[i].pid = None,
+                None => {
+                    child_exited_nonzero |= failed;
+                    stopping = true;
+                    kill_all(&mut pids, false);
+                }
+            }
+        }
+
+        if stopping {
+            if pids.is_empty() {
+                break;
+            }
+            let since = *stopping_since.get_or_insert_with(Instant::now);
+            if STOP_TIMEOUT <= since.elapsed() {
+                for (pid, info) in &pids {
+                    event!(
+                        Level::WARN,
+                        "{} ({}) still running after {:?}, sending SIGKILL",
+                        pid,
+                        info.package_name,
+                        STOP_TIMEOUT
+                    );
+                    let _ = kill(*pid, Signal::SIGKILL);
+                }
+                stopping_since = Some(Instant::now());
+            }
+            thread::sleep(POLL_INTERVAL);
+            continue;
+        }
+
+        for activator in &mut activators {
+            let pid = match activator.pid {
+                Some(pid) => pid,
+                None => continue,
+            };
+            let child_info = pids.get_mut(&pid).unwrap();
+            if let Some(since) = child_info.idle_stop {
+                if STOP_TIMEOUT <= since.elapsed() {
+                    event!(
+                        Level::WARN,
+                        "{} ({}) still running after {:?}, sending SIGKILL",
+                        pid,
+                        child_info.package_name,
+                        STOP_TIMEOUT
+                    );
+                    let _ = kill(pid, Signal::SIGKILL);
+                    child_info.idle_stop = Some(Instant::now());
+                }
+            } else if !child_info.sent_kill && activator.is_idle() {
+                event!(
+                    Level::INFO,
+                    "{} idle for {:?}, stopping it",
+                    child_info.package_name,
+                    activator.idle_timeout.unwrap()
+                );
+                child_info.sent_kill = true;
+                child_info.idle_stop = Some(Instant::now());
+                let _ = kill(pid, Signal::SIGTERM);
+            }
+        }
+
+        for (i, activator) in activators.iter_mut().enumerate() {
+            let restart = activator.idle_timeout.is_none()
+                && activator.pid.is_none()
+                && RESTART_DELAY <= activator.last_active.elapsed();
+            if !restart {
+                continue;
+            }
+            let package_id = activator.template.package_id.clone();
+            let started = Instant::now();
+            match activator.start() {
+                Ok(pid) => {
+                    event!(Level::WARN, package_id = &package_id[..], child.pid = ?pid, "restarted intrinsic");
+                    record(JournalEvent::Started {
+                        package_id: &package_id,
+                        instance_id: activator.template.instance_id,
+                        pid,
+                        start_duration: started.elapsed(),
+                    });
+                    pids.insert(
+                        pid,
+                        ChildInfo {
+                            package_name: package_id,
+                            sent_kill: false,
+                            idle_stop: None,
+                            activator: Some(i),
+                        },
+                    );
+                }
+                Err(err) => {
+                    // tried again after `RESTART_DELAY`.
+                    activator.last_active = Instant::now();
+                    event!(Level::ERROR, "failed to restart {}: {}", package_id, err);
+                }
+            }
+        }
+
+        for i in wait_for_connections(&activators, POLL_INTERVAL) {
+            let activator = &mut activators[i];
+            if activator.pid.is_some() {
+                activator.last_active = Instant::now();
+                continue;
+            }
+            let package_id = activator.template.package_id.clone();
+            let started = Instant::now();
+            let pid = match activator.start() {
+                Ok(pid) => pid,
+                Err(err) => {
+                    // tried again on a connection after `RESTART_DELAY`.
+                    activator.last_active = Instant::now();
+                    activator.failed_at = Some(Instant::now());
+                    event!(Level::ERROR, "failed to activate {}: {}", package_id, err);
+                    continue;
+                }
+            };
+            event!(Level::INFO, package_id = &package_id[..], child.pid = ?pid, "activated");
+            record(JournalEvent::Started {
+                package_id: &package_id,
+                instance_id: activator.template.instance_id,
+                pid,
+                start_duration: started.elapsed(),
+            });
+            pids.insert(
+                pid,
+                ChildInfo {
+                    package_name: package_id,
+                    sent_kill: false,
+                    idle_stop: None,
+                    activator: Some(i),
+                },
+            );
+        }
+    }
+    for path in &ephemeral_workdirs {
+        if let Err(err) = workdir::empty(path) {
+            event!(Level::WARN, "failed to empty {}: {}", path.display(), err);
+        }
+    }
+    if child_exited_nonzero {
+        std::process::exit(1);
+    }
+}
+
+/// A component started by an earlier linker, found alive through its
+/// journal.
+#[derive(Debug)]
+pub struct AdoptedChild {
+    pub package_id: String,
+    pub pid: Pid,
+    pub pidfd: OwnedFd,
+}
+
+/// Why `run_adopted` returned.
+#[derive(Debug, PartialEq)]
+pub enum AdoptedExit {
+    /// SIGINT: they were all stopped.
+    Interrupted,
+    /// SIGUSR2: they were left running again.
+    Detached,
+    /// One of them exited and the rest were stopped.  A component we didn't
+    /// start can't be started again with the same connections.
+    ChildExited,
+}
+
+/// Supervises the components of a deployment an earlier linker started.
+/// They aren't our children, so they are watched and signalled through
+/// pidfds, and their exit status is lost to us.  With `stop`, they are
+/// stopped right away.  `ephemeral_workdirs` are emptied once they have
+/// all stopped.
+pub fn run_adopted(
+    children: Vec<AdoptedChild>,
+    ephemeral_workdirs: Vec<PathBuf>,
+    mut journal: JournalFn,
+    stop: bool,
+) -> AdoptedExit {
+    let span = span!(Level::INFO, "run_adopted");
+    let _span_entered = span.enter();
+
+    let interrupt = Arc::new(AtomicBool::new(false));
+    let detach = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::SIGINT, Arc::clone(&interrupt)).unwrap();
+    signal_hook::flag::register(signal_hook::SIGUSR2, Arc::clone(&detach)).unwrap();
+
+    let mut children = children;
+    let mut outcome = if stop {
+        Some(AdoptedExit::ChildExited)
+    } else {
+        None
+    };
+    let mut sent_kill: Option<Instant> = None;
+    loop {
+        if outcome.is_none() && detach.load(Ordering::SeqCst) {
             event!(
-                Level::INFO,
-                "got {}, signaling to children to terminate",
-                sig
+                Level::WARN,
+                "got SIGUSR2, leaving {} children running for the next linker",
+                children.len()
             );
-            kill_all(&kill_targets, false);
+            return AdoptedExit::Detached;
         }
-        if let Some(sig) = signals.forever().next() {
-            signals.close();
+        if outcome.is_none() && interrupt.load(Ordering::SeqCst) {
             event!(
                 Level::INFO,
-                "got {}, signaling to children to terminate (2nd attempt)",
-                sig
+                "got SIGINT, signaling to children to terminate"
             );
-            kill_all(&kill_targets, true);
+            outcome = Some(AdoptedExit::Interrupted);
         }
-    });
-
-    let mut remaining_children = pids.len();
-    let mut child_exited_nonzero = false;
-    while 0 < remaining_children {
-        match waitpid(None, None) {
-            Ok(WaitStatus::Exited(pid, exit_code)) => {
-                let child_info = &pids[&pid];
+        if outcome.is_some() && sent_kill.is_none() {
+            sent_kill = Some(Instant::now());
+            for child in &children {
                 event!(
                     Level::INFO,
-                    "child {} exited {}",
-                    child_info.package_name,
-                    exit_code
+                    "sending {} ({}) SIGTERM",
+                    child.pid,
+                    child.package_id
                 );
-                if exit_code != 0 {
-                    child_exited_nonzero = true;
+                if let Err(err) = imp::pidfd_send_signal(&child.pidfd, Signal::SIGTERM) {
+                    event!(
+                        Level::WARN,
+                        "signaling {} failed: {}",
+                        child.package_id,
+                        err
+                    );
+                }
+            }
+        }
+        if children.is_empty() {
+            for path in &ephemeral_workdirs {
+                if let Err(err) = workdir::empty(path) {
+                    event!(Level::WARN, "failed to empty {}: {}", path.display(), err);
                 }
-                remaining_children -= 1;
-                child_info.running.store(false, Ordering::SeqCst);
-                kill_all(&pids, false);
             }
-            // literally why.
-            Ok(WaitStatus::Signaled(pid, sig, cored)) => {
-                let child_info = &pids[&pid];
+            return outcome.unwrap_or(AdoptedExit::ChildExited);
+        }
+        if sent_kill.map_or(false, |sent| STOP_TIMEOUT <= sent.elapsed()) {
+            sent_kill = Some(Instant::now());
+            for child in &children {
                 event!(
-                    Level::INFO,
-                    "child {} exited via signal {}",
-                    child_info.package_name,
-                    sig
+                    Level::WARN,
+                    "{} ({}) still running after {:?}, sending SIGKILL",
+                    child.pid,
+                    child.package_id,
+                    STOP_TIMEOUT
                 );
-                remaining_children -= 1;
-                if cored {
-                    // we might also want to detect bad exit signals?
-                    child_exited_nonzero = true;
+                let _ = imp::pidfd_send_signal(&child.pidfd, Signal::SIGKILL);
+            }
+        }
+
+        let mut fds: Vec<PollFd> = children
+            .iter()
+            .map(|c| PollFd::new(c.pidfd.as_raw_fd(), PollFlags::POLLIN))
+            .collect();
+        // interrupted by a signal, seen on the next round.
+        if poll(&mut fds, POLL_INTERVAL.as_millis() as i32).is_err() {
+            continue;
+        }
+        // as a subreaper we inherit whatever the components orphan.
+        while let Ok(ws) = waitpid(None, Some(WaitPidFlag::WNOHANG)) {
+            if ws == WaitStatus::StillAlive {
+                break;
+            }
+        }
+        let exited: Vec<bool> = fds
+            .iter()
+            .map(|fd| fd.revents().map_or(false, |r| !r.is_empty()))
+            .collect();
+
+        let mut i = 0;
+        children.retain(|child| {
+            let gone = exited[i];
+            i += 1;
+            if gone {
+                event!(Level::INFO, "adopted child {} exited", child.package_id);
+                journal(JournalEvent::Exited {
+                    pid: child.pid,
+                    exit: None,
+                });
+            }
+            !gone
+        });
+        if exited.contains(&true) && outcome.is_none() {
+            outcome = Some(AdoptedExit::ChildExited);
+        }
+    }
+}
+
+// How often `run_reified_dev` looks for exited children and changed binaries.
+const DEV_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Everything needed to start a component again, with the file descriptors
+/// duplicated so they outlive the child they were handed to.  Used by
+/// `run --dev` and for on-demand components.
+struct InstanceTemplate {
+    deployment_name: String,
+    trace_id: Ksuid,
+    package_id: String,
+    instance_id: uuid::Uuid,
+    version: String,
+    files: Vec<ServiceFileDescriptor>,
+    extras: serde_json::Value,
+    container_mounts: Vec<(PathBuf, PathBuf)>,
+    fd_protocol: FdProtocol,
+    arguments: Vec<String>,
+    environment: Vec<(String, String)>,
+    // an issuer connection is only good for one HTTP/2 session, so each
+    // instance gets a new one instead of a copy.
+    identity_signer: Option<IdentitySigner>,
+}
+
+impl InstanceTemplate {
+    fn new(
+        c: &AppPreforkConfiguration,
+        identity_signer: Option<IdentitySigner>,
+    ) -> io::Result<InstanceTemplate> {
+        let mut files = Vec::with_capacity(c.files.len());
+        for f in &c.files {
+            if identity_signer.is_some() && f.service_name == identity_signer::SERVICE_NAME {
+                continue;
+            }
+            files.push(ServiceFileDescriptor {
+                file: f
+                    .file
+                    .try_clone()
+                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?,
+                direction: f.direction.clone(),
+                service_name: f.service_name.clone(),
+                remote: f.remote.clone(),
+                trace_id: f.trace_id,
+            });
+        }
+
+        Ok(InstanceTemplate {
+            deployment_name: c.deployment_name.clone(),
+            trace_id: c.trace_id,
+            package_id: c.package_id.clone(),
+            instance_id: c.instance_id,
+            version: c.version.clone(),
+            files,
+            extras: c.extras.clone(),
+            container_mounts: c.container_mounts.clone(),
+            fd_protocol: c.fd_protocol,
+            arguments: c.arguments.clone(),
+            environment: c.environment.clone(),
+            identity_signer,
+        })
+    }
+
+    fn listeners(&self) -> impl Iterator<Item = RawFd> + '_ {
+        self.files
+            .iter()
+            .filter(|f| f.direction == ServiceFileDirection::ServingListening)
+            .map(|f| f.file.as_raw_fd())
+    }
+
+    fn instantiate(&self, artifact: Executable) -> io::Result<AppPreforkConfiguration> {
+        let mut files = Vec::with_capacity(self.files.len());
+        for f in &self.files {
+            files.push(ServiceFileDescriptor {
+                file: f
+                    .file
+                    .try_clone()
+                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?,
+                direction: f.direction.clone(),
+                service_name: f.service_name.clone(),
+                remote: f.remote.clone(),
+                trace_id: f.trace_id,
+            });
+        }
+        if let Some(ref signer) = self.identity_signer {
+            files.push(signer.connect(Identity {
+                deployment_name: self.deployment_name.clone(),
+                package_id: self.package_id.clone(),
+                instance_id: self.instance_id,
+            })?);
+        }
+
+        Ok(AppPreforkConfiguration {
+            deployment_name: self.deployment_name.clone(),
+            trace_id: self.trace_id,
+            package_id: self.package_id.clone(),
+            artifact,
+            instance_id: self.instance_id,
+            version: self.version.clone(),
+            files,
+            extras: self.extras.clone(),
+            container_mounts: self.container_mounts.clone(),
+            // components started more than once can't take live
+            // configuration: `process::check_activation` rejects it for
+            // on-demand ones, `intrinsic::Registry::load_binaries` for
+            // intrinsic binaries.
+            control: None,
+            fd_protocol: self.fd_protocol,
+            arguments: self.arguments.clone(),
+            environment: self.environment.clone(),
+        })
+    }
+}
+
+struct DevChild {
+    template: InstanceTemplate,
+    extras: ExecExtras,
+    pid: Option<Pid>,
+    // the override binary, and its mtime when last started.
+    watch: Option<(PathBuf, Option<SystemTime>)>,
+    restart: bool,
+}
+
+fn modified(path: &PathBuf) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Like `run_reified`, but for `run --dev`: a component exiting doesn't take
+/// the deployment down, and components in `watch` (package id to binary
+/// path) are restarted whenever their binary changes on disk.  Runs until
+/// SIGINT.
+pub fn run_reified_dev(reified: Vec<ExecSomething>, watch: HashMap<String, PathBuf>) {
+    let span = span!(Level::INFO, "run_reified_dev");
+    let _span_entered = span.enter();
+
+    let shutdown = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::SIGINT, Arc::clone(&shutdown)).unwrap();
+
+    let mut children = Vec::new();
+    for a in reified {
+        if a.activation != Activation::Always {
+            event!(
+                Level::WARN,
+                "starting on-demand component {} right away in development mode",
+                a.cfg.package_id
+            );
+        }
+        let template = InstanceTemplate::new(&a.cfg, a.identity_signer).unwrap();
+        let watch = watch
+            .get(&a.cfg.package_id)
+            .map(|path| (path.clone(), modified(path)));
+
+        let pid = exec_artifact(&a.extras, a.cfg).unwrap();
+        event!(Level::INFO, package_id = &template.package_id[..], child.pid = ?pid, "started");
+
+        children.push(DevChild {
+            template,
+            extras: a.extras,
+            pid: Some(pid),
+            watch,
+            restart: false,
+        });
+    }
+
+    let mut sent_shutdown = false;
+    loop {
+        if shutdown.load(Ordering::SeqCst) && !sent_shutdown {
+            sent_shutdown = true;
+            event!(
+                Level::INFO,
+                "got SIGINT, signaling to children to terminate"
+            );
+            for child in &children {
+                if let Some(pid) = child.pid {
+                    let _ = kill(pid, Signal::SIGTERM);
+                }
+            }
+        }
+
+        if !sent_shutdown {
+            for child in &mut children {
+                let (path, last_modified) = match child.watch {
+                    Some(ref mut w) => w,
+                    None => continue,
+                };
+                let now_modified = modified(path);
+                if now_modified.is_none() || now_modified == *last_modified {
+                    continue;
+                }
+                *last_modified = now_modified;
+
+                event!(
+                    Level::WARN,
+                    "{} changed, restarting {}",
+                    path.display(),
+                    child.template.package_id
+                );
+                child.restart = true;
+                match child.pid {
+                    Some(pid) => {
+                        let _ = kill(pid, Signal::SIGTERM);
+                    }
+                    None => restart_dev_child(child),
                 }
-                child_info.running.store(false, Ordering::SeqCst);
-                kill_all(&pids, false);
+            }
+        }
+
+        let (pid, how) = match waitpid(None, Some(WaitPidFlag::WNOHANG)) {
+            Ok(WaitStatus::Exited(pid, exit_code)) => (pid, format!("exited {}", exit_code)),
+            Ok(WaitStatus::Signaled(pid, sig, _cored)) => {
+                (pid, format!("exited via signal {}", sig))
+            }
+            Ok(WaitStatus::StillAlive) => {
+                thread::sleep(DEV_POLL_INTERVAL);
+                continue;
             }
             Ok(ws) => {
                 event!(Level::WARN, "waitpid got an unexpected {:?}", ws);
+                continue;
             }
-            Err(err) => {
-                panic!("waitpid err {}", err);
+            Err(nix::Error::Sys(Errno::ECHILD)) if sent_shutdown => break,
+            Err(nix::Error::Sys(Errno::ECHILD)) => {
+                // everything has exited; wait for a binary to change.
+                thread::sleep(DEV_POLL_INTERVAL);
+                continue;
             }
+            Err(nix::Error::Sys(Errno::EINTR)) => continue,
+            Err(err) => panic!("waitpid err {}", err),
+        };
+
+        let child = match children.iter_mut().find(|c| c.pid == Some(pid)) {
+            Some(child) => child,
+            None => continue,
         };
+        child.pid = None;
+        event!(Level::INFO, "child {} {}", child.template.package_id, how);
+
+        if child.restart && !sent_shutdown {
+            restart_dev_child(child);
+        } else if !sent_shutdown && child.watch.is_some() {
+            event!(
+                Level::WARN,
+                "{} will be started again when its binary changes",
+                child.template.package_id
+            );
+        }
     }
-    if child_exited_nonzero {
-        std::process::exit(1);
+}
+
+fn restart_dev_child(child: &mut DevChild) {
+    child.restart = false;
+
+    let path = &child.watch.as_ref().unwrap().0;
+    let started = direct_load_artifact(&path.to_string_lossy())
+        .and_then(|artifact| child.template.instantiate(artifact))
+        .and_then(|cfg| exec_artifact(&child.extras, cfg));
+
+    match started {
+        Ok(pid) => {
+            event!(Level::INFO, package_id = &child.template.package_id[..], child.pid = ?pid, "restarted");
+            child.pid = Some(pid);
+        }
+        Err(err) => {
+            event!(
+                Level::ERROR,
+                "failed to restart {}: {}",
+                child.template.package_id,
+                err
+            );
+        }
     }
 }
 
@@ -264,3 +1387,213 @@ pub fn waitpid_stream() -> WaitpidStream {
 
     WaitpidStream { channel: rx }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+    use std::net::{TcpListener, TcpStream};
+    use std::os::unix::io::{FromRawFd, IntoRawFd};
+    use std::process::Command;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use nix::sys::socket::accept;
+    use nix::unistd::{close, getpid, Pid};
+    use owned_fd::OwnedFd;
+    use sockets::{seqpacket_pair_raw, socketpair_raw};
+    use uuid::Uuid;
+    use yscloud_config_model::{
+        FdProtocol, FileDescriptorRemote, Ksuid, Protocol, ServiceFileDirection, SocketInfo,
+        SocketMode,
+    };
+
+    use super::{app_configuration, command_line, Activator};
+    use crate::platform::{ExecExtras, Executable};
+    use crate::{AppPreforkConfiguration, ServiceFileDescriptor};
+
+    #[test]
+    fn test_app_configuration_layout() {
+        let file = |direction, service_name: &str| ServiceFileDescriptor {
+            file: socketpair_raw().unwrap().0,
+            direction,
+            service_name: service_name.into(),
+            remote: FileDescriptorRemote::Socket(SocketInfo {
+                mode: SocketMode::Listening,
+                protocol: Protocol::Stream,
+                flags: vec![],
+            }),
+            trace_id: None,
+        };
+
+        let c = AppPreforkConfiguration {
+            deployment_name: "example-deployment".into(),
+            trace_id: Ksuid::generate(),
+            package_id: "org.yshi.staticserver".into(),
+            artifact: Executable::open("/bin/sh").unwrap(),
+            instance_id: Uuid::new_v4(),
+            version: "1.0.5".into(),
+            files: vec![
+                file(
+                    ServiceFileDirection::ServingListening,
+                    "org.yshi.staticserver.http",
+                ),
+                file(
+                    ServiceFileDirection::Consuming,
+                    "org.yshi.log_target.v1.LogTarget",
+                ),
+            ],
+            extras: serde_json::json!({}),
+            container_mounts: vec![],
+            control: Some(seqpacket_pair_raw().unwrap().0),
+            fd_protocol: FdProtocol::ConfigFd,
+            arguments: vec![],
+            environment: vec![],
+        };
+
+        let config = app_configuration(&c);
+        let file_nums: Vec<_> = config.files.iter().map(|f| f.file_num).collect();
+        assert_eq!(file_nums, vec![3, 4]);
+        assert_eq!(
+            config.files[1].service_name,
+            "org.yshi.log_target.v1.LogTarget"
+        );
+        assert_eq!(config.control_fd, Some(5));
+        assert_eq!(config.trace_id, Some(c.trace_id));
+    }
+
+    #[test]
+    fn test_command_line() {
+        let mut c = AppPreforkConfiguration {
+            deployment_name: "example-deployment".into(),
+            trace_id: Ksuid::generate(),
+            package_id: "org.yshi.staticserver".into(),
+            artifact: Executable::open("/bin/sh").unwrap(),
+            instance_id: Uuid::new_v4(),
+            version: "1.0.5".into(),
+            files: vec![],
+            extras: serde_json::json!({}),
+            container_mounts: vec![],
+            control: None,
+            fd_protocol: FdProtocol::ConfigFd,
+            arguments: vec!["--verbose".into()],
+            environment: vec![("TZ".into(), "UTC".into())],
+        };
+        let strings = |v: Vec<CString>| -> Vec<String> {
+            v.into_iter().map(|s| s.into_string().unwrap()).collect()
+        };
+
+        let (arguments, env) = command_line(&c, Some(3)).unwrap();
+        assert_eq!(
+            strings(arguments),
+            vec!["yscloud-executable", "--config-fd", "3", "--verbose"]
+        );
+        assert_eq!(strings(env), vec!["TZ=UTC", "YSCLOUD=1"]);
+
+        c.fd_protocol = FdProtocol::ListenFds;
+        // connections aren't listeners, and are placed after them.
+        c.files.push(ServiceFileDescriptor {
+            file: socketpair_raw().unwrap().0,
+            direction: ServiceFileDirection::Consuming,
+            service_name: "org.yshi.log_target.v1.LogTarget".into(),
+            remote: FileDescriptorRemote::Socket(SocketInfo {
+                mode: SocketMode::Connected,
+                protocol: Protocol::Stream,
+                flags: vec![],
+            }),
+            trace_id: None,
+        });
+        c.files.push(ServiceFileDescriptor {
+            file: socketpair_raw().unwrap().0,
+            direction: ServiceFileDirection::ServingListening,
+            service_name: "http".into(),
+            remote: FileDescriptorRemote::Socket(SocketInfo {
+                mode: SocketMode::Listening,
+                protocol: Protocol::Stream,
+                flags: vec![],
+            }),
+            trace_id: None,
+        });
+        let (arguments, env) = command_line(&c, None).unwrap();
+        assert_eq!(strings(arguments), vec!["yscloud-executable", "--verbose"]);
+        let env = strings(env);
+        assert!(env.contains(&"LISTEN_FDS=1".to_string()));
+        assert!(env.contains(&"LISTEN_FDNAMES=http".to_string()));
+        let file_nums: Vec<_> = app_configuration(&c)
+            .files
+            .iter()
+            .map(|f| f.file_num)
+            .collect();
+        assert_eq!(file_nums, vec![4, 3]);
+    }
+
+    #[test]
+    fn test_idle_despite_cpu_use() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let c = AppPreforkConfiguration {
+            deployment_name: "example-deployment".into(),
+            trace_id: Ksuid::generate(),
+            package_id: "org.yshi.staticserver".into(),
+            artifact: Executable::open("/bin/sh").unwrap(),
+            instance_id: Uuid::new_v4(),
+            version: "1.0.5".into(),
+            files: vec![ServiceFileDescriptor {
+                file: unsafe { OwnedFd::from_raw_fd(listener.into_raw_fd()) },
+                direction: ServiceFileDirection::ServingListening,
+                service_name: "org.yshi.staticserver.http".into(),
+                remote: FileDescriptorRemote::Socket(SocketInfo {
+                    mode: SocketMode::Listening,
+                    protocol: Protocol::Stream,
+                    flags: vec![],
+                }),
+                trace_id: None,
+            }],
+            extras: serde_json::json!({}),
+            container_mounts: vec![],
+            control: None,
+            fd_protocol: FdProtocol::ConfigFd,
+            arguments: vec![],
+            environment: vec![],
+        };
+        let idle_timeout = Duration::from_millis(200);
+        let extras = ExecExtras::builder().build();
+        let mut activator = Activator::new(extras, c, Some(idle_timeout), None).unwrap();
+
+        // busy, but never accepts anything.
+        let mut ticker = Command::new("/bin/sh")
+            .arg("-c")
+            .arg("while :; do :; done")
+            .spawn()
+            .unwrap();
+        activator.pid = Some(Pid::from_raw(ticker.id() as i32));
+
+        let deadline = Instant::now() + 5 * idle_timeout;
+        let mut idle = activator.is_idle();
+        while !idle && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(50));
+            idle = activator.is_idle();
+        }
+        // a connection waiting to be accepted is activity, though.
+        let _pending = TcpStream::connect(addr).unwrap();
+        let idle_with_pending = activator.is_idle();
+
+        ticker.kill().unwrap();
+        ticker.wait().unwrap();
+        assert!(idle);
+        assert!(!idle_with_pending);
+
+        // so is one that was accepted, until it is closed; here we stand in
+        // for the component.
+        if cfg!(target_os = "linux") {
+            let listener = activator.template.listeners().next().unwrap();
+            let accepted = accept(listener).unwrap();
+            activator.pid = Some(getpid());
+            let _log = tempfile::tempfile().unwrap();
+            thread::sleep(2 * idle_timeout);
+            assert!(!activator.is_idle());
+            close(accepted).unwrap();
+            thread::sleep(2 * idle_timeout);
+            assert!(activator.is_idle());
+        }
+    }
+}
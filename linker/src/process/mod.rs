@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use failure::Fail;
+use yscloud_config_model::{Activation, DeployedApplicationManifest, ProcessSettings};
+
+/// Variables a component inherits from the linker when they are set.
+/// Nothing else in the linker's environment is passed on, so secrets it
+/// was started with stay with it.
+pub const PASS_ENVIRONMENT: &[&str] = &["RUST_LOG", "RUST_BACKTRACE", "TZ"];
+
+/// Set by the linker itself; a manifest may not override them.
+pub const RESERVED_ENVIRONMENT: &[&str] =
+    &["YSCLOUD", "LISTEN_FDS", "LISTEN_PID", "LISTEN_FDNAMES"];
+
+#[derive(Debug, Fail)]
+pub struct InvalidProcessSettings {
+    package_id: String,
+    reason: String,
+}
+
+impl fmt::Display for InvalidProcessSettings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid process settings for {}: {}",
+            self.package_id, self.reason
+        )
+    }
+}
+
+/// Checks that `settings` can be turned into an argv and environment.
+pub fn check(package_id: &str, settings: &ProcessSettings) -> Result<(), InvalidProcessSettings> {
+    let invalid = |reason: String| InvalidProcessSettings {
+        package_id: package_id.to_string(),
+        reason,
+    };
+
+    for arg in &settings.arguments {
+        if arg.contains('\0') {
+            return Err(invalid(format!("argument {:?} contains a NUL byte", arg)));
+        }
+    }
+    for (name, value) in &settings.environment {
+        if name.is_empty() || name.contains('=') || name.contains('\0') {
+            return Err(invalid(format!("bad environment variable name {:?}", name)));
+        }
+        if RESERVED_ENVIRONMENT.contains(&&name[..]) {
+            return Err(invalid(format!("{} is set by the linker", name)));
+        }
+        if value.contains('\0') {
+            return Err(invalid(format!("{} contains a NUL byte", name)));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `component` can be started the way its `activation` asks.
+/// An on-demand component is woken by its public services, and nothing may
+/// depend on it staying up.
+pub fn check_activation(
+    component: &DeployedApplicationManifest,
+) -> Result<(), InvalidProcessSettings> {
+    if component.activation == Activation::Always {
+        return Ok(());
+    }
+    let invalid = |reason: &str| InvalidProcessSettings {
+        package_id: component.package_id.clone(),
+        reason: reason.to_string(),
+    };
+
+    if component.provided_remote_services.is_empty() {
+        return Err(invalid("on-demand components need a public service"));
+    }
+    if !component.provided_local_services.is_empty() {
+        return Err(invalid("on-demand components can't provide local services"));
+    }
+    if component.live_config {
+        return Err(invalid(
+            "on-demand components can't take live configuration",
+        ));
+    }
+    Ok(())
+}
+
+/// The environment a component starts with, apart from the variables in
+/// `RESERVED_ENVIRONMENT`.  Later sources win: defaults, then those of the
+/// linker's variables, `linker_env`, named in `pass`, then the manifest.
+pub fn environment<S, I>(
+    settings: &ProcessSettings,
+    pass: &[S],
+    linker_env: I,
+) -> Vec<(String, String)>
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = (String, String)>,
+{
+    let mut env = BTreeMap::new();
+    env.insert("RUST_BACKTRACE".to_string(), "1".to_string());
+    for (name, value) in linker_env {
+        if pass.iter().any(|p| p.as_ref() == name) {
+            env.insert(name, value);
+        }
+    }
+    for (name, value) in &settings.environment {
+        env.insert(name.clone(), value.clone());
+    }
+    env.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use yscloud_config_model::{FdProtocol, ImageType, Sandbox};
+
+    use super::*;
+
+    #[test]
+    fn test_environment() {
+        let mut settings = ProcessSettings::default();
+        settings
+            .environment
+            .insert("RUST_BACKTRACE".into(), "full".into());
+        settings
+            .environment
+            .insert("APP_MODE".into(), "prod".into());
+        check("org.yshi.example", &settings).unwrap();
+
+        let linker_env = vec![
+            ("YSCLOUD_TEST_PASSED".to_string(), "yes".to_string()),
+            ("YSCLOUD_TEST_SECRET".to_string(), "hunter2".to_string()),
+        ];
+        let pass = ["YSCLOUD_TEST_PASSED", "YSCLOUD_TEST_UNSET"];
+        let env = environment(&settings, &pass, linker_env);
+        assert_eq!(
+            env,
+            vec![
+                ("APP_MODE".to_string(), "prod".to_string()),
+                ("RUST_BACKTRACE".to_string(), "full".to_string()),
+                ("YSCLOUD_TEST_PASSED".to_string(), "yes".to_string()),
+            ]
+        );
+
+        settings.environment.insert("LISTEN_FDS".into(), "1".into());
+        assert!(check("org.yshi.example", &settings).is_err());
+    }
+
+    #[test]
+    fn test_check_activation() {
+        let mut component = DeployedApplicationManifest {
+            package_id: "org.yshi.admin".into(),
+            version: "1.0.0".parse().unwrap(),
+            provided_local_services: vec![],
+            provided_remote_services: vec!["org.yshi.admin.http".into()],
+            required_local_services: vec![],
+            required_remote_services: vec![],
+            sandbox: Sandbox::Unconfined,
+            extras: serde_json::json!({}),
+            image_type: ImageType::Executable,
+            live_config: false,
+            fd_protocol: FdProtocol::ConfigFd,
+            process: ProcessSettings::default(),
+            activation: Activation::OnDemand {
+                idle_timeout_secs: 600,
+            },
+            adapter: None,
+            workdir: Default::default(),
+            artifacts: Default::default(),
+        };
+        check_activation(&component).unwrap();
+
+        component.live_config = true;
+        assert!(check_activation(&component).is_err());
+
+        component.live_config = false;
+        component
+            .provided_local_services
+            .push("org.yshi.admin.v1.Admin".into());
+        assert!(check_activation(&component).is_err());
+    }
+}
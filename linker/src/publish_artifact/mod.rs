@@ -1,44 +1,44 @@
-use std::fs::File;
-use std::io::{self, Seek, SeekFrom};
+use std::collections::BTreeSet;
+use std::fmt;
 use std::path::PathBuf;
 
-use digest::FixedOutput;
+use failure::{Fail, Fallible};
 use semver::Version;
-use serde::Serialize;
-use sha2::Sha256;
-use sha3::Keccak512;
+use tracing::{event, Level};
 
-use crate::util::hexify;
+use yscloud_config_model::ApplicationManifest;
 
-pub fn start(cfg: Config) {
-    eprintln!("config: {:#?}", cfg);
+use crate::registry::{FileRegistry, PublishOutcome, BLOBS_DIR_NAME};
+use crate::schema::compile_extras_schema;
 
-    let mut artifact_file = File::open(cfg.artifact).expect("artifact not found");
+pub fn start(cfg: Config) -> Fallible<PublishOutcome> {
+    event!(Level::DEBUG, "config: {:?}", cfg);
 
-    let mut sha256_state = Sha256::default();
-    let mut keccak512_state = Keccak512::default();
+    validate_package_id(&cfg.package_id)?;
+    validate_manifest(&cfg.manifest)?;
 
-    io::copy(&mut artifact_file, &mut sha256_state).unwrap();
-    let mut sha256_scratch = [0; 256 / 8 * 2];
-    let sha256_str = hexify(&mut sha256_scratch, &sha256_state.finalize_fixed()).unwrap();
+    let registry = FileRegistry::new(&cfg.registry);
+    let blob = registry.stage_blob(&cfg.artifact)?;
+    event!(
+        Level::INFO,
+        package_id = &cfg.package_id[..],
+        version = %cfg.version,
+        host_triple = &cfg.host_triple[..],
+        sha256 = &blob.sha256[..],
+        content_length = blob.content_length,
+        "staged artifact",
+    );
 
-    artifact_file.seek(SeekFrom::Start(0)).unwrap();
-    io::copy(&mut artifact_file, &mut keccak512_state).unwrap();
-    let mut keccak512_scratch = [0; 512 / 8 * 2];
-    let keccak512_str = hexify(&mut keccak512_scratch, &keccak512_state.finalize_fixed()).unwrap();
+    let outcome = registry.publish(
+        &cfg.package_id,
+        &cfg.version,
+        &cfg.manifest,
+        &cfg.host_triple,
+        blob,
+    )?;
 
-    let metadata = artifact_file.metadata().expect("metadata fetch failure");
-
-    let serialized = serde_json::to_string(&ArtifactEntry {
-        file_size: metadata.len(),
-        sha256: sha256_str,
-        keccak512: keccak512_str,
-    })
-    .unwrap();
-
-    eprintln!("{} {}", cfg.host_triple, serialized);
-
-    unimplemented!();
+    event!(Level::INFO, "publish finished: {:?}", outcome);
+    Ok(outcome)
 }
 
 #[derive(Clone, Debug)]
@@ -48,11 +48,218 @@ pub struct Config {
     pub version: Version,
     pub host_triple: String,
     pub artifact: PathBuf,
+    pub manifest: ApplicationManifest,
+}
+
+#[derive(Debug, Fail)]
+pub struct InvalidManifest {
+    reason: String,
+}
+
+impl fmt::Display for InvalidManifest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid application manifest: {}", self.reason)
+    }
+}
+
+fn invalid(reason: String) -> InvalidManifest {
+    InvalidManifest { reason }
+}
+
+#[derive(Debug, Fail)]
+pub struct InvalidPackageId {
+    package_id: String,
+}
+
+impl fmt::Display for InvalidPackageId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid package id: {:?}", self.package_id)
+    }
+}
+
+fn is_path_component(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && !name
+            .chars()
+            .any(|c| c == '/' || c.is_whitespace() || c.is_control())
+}
+
+/// Package ids name directories in the registry, next to its blobs.
+pub fn validate_package_id(package_id: &str) -> Result<(), InvalidPackageId> {
+    if !is_path_component(package_id) || package_id == BLOBS_DIR_NAME {
+        return Err(InvalidPackageId {
+            package_id: package_id.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Service names end up as socket file names inside container workdirs, so
+/// they're restricted to something that's safe as a single path component.
+pub fn validate_manifest(manifest: &ApplicationManifest) -> Result<(), InvalidManifest> {
+    let lists: &[(&str, &[String])] = &[
+        ("provided_remote_services", &manifest.provided_remote_services),
+        ("provided_local_services", &manifest.provided_local_services),
+        ("required_remote_services", &manifest.required_remote_services),
+        ("required_local_services", &manifest.required_local_services),
+    ];
+
+    for (list_name, services) in lists {
+        let mut seen = BTreeSet::new();
+        for service_name in services.iter() {
+            if service_name.is_empty() {
+                return Err(invalid(format!("{} contains an empty name", list_name)));
+            }
+            if !is_path_component(service_name) {
+                return Err(invalid(format!(
+                    "{} contains an invalid name: {:?}",
+                    list_name, service_name
+                )));
+            }
+            if !seen.insert(service_name) {
+                return Err(invalid(format!(
+                    "{} lists {:?} more than once",
+                    list_name, service_name
+                )));
+            }
+        }
+    }
+
+    for service_name in &manifest.required_local_services {
+        if manifest.provided_local_services.contains(service_name) {
+            return Err(invalid(format!(
+                "{:?} is both provided and required locally",
+                service_name
+            )));
+        }
+    }
+
+    if let Some(ref extras_schema) = manifest.extras_schema {
+        compile_extras_schema(extras_schema).map_err(|err| invalid(err.to_string()))?;
+    }
+
+    Ok(())
 }
 
-#[derive(Serialize)]
-struct ArtifactEntry<'a> {
-    file_size: u64,
-    sha256: &'a str,
-    keccak512: &'a str,
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use semver::Version;
+    use tempfile::tempdir;
+
+    use yscloud_config_model::{permissions, ApplicationManifest, FdProtocol};
+
+    use super::{start, Config};
+    use crate::registry::PublishOutcome;
+
+    fn manifest() -> ApplicationManifest {
+        ApplicationManifest {
+            provided_remote_services: Vec::new(),
+            provided_local_services: vec!["org.yshi.log_target.v1.LogTarget".to_string()],
+            required_remote_services: Vec::new(),
+            required_local_services: Vec::new(),
+            permissions: vec![permissions::UNCONSTRAINED],
+            extras_schema: None,
+            live_config: false,
+            fd_protocol: FdProtocol::ConfigFd,
+        }
+    }
+
+    fn config(registry: &Path, artifact: &Path, host_triple: &str) -> Config {
+        Config {
+            registry: registry.to_owned(),
+            package_id: "org.yshi.file-logger".into(),
+            version: Version::parse("1.0.2").unwrap(),
+            host_triple: host_triple.into(),
+            artifact: artifact.to_owned(),
+            manifest: manifest(),
+        }
+    }
+
+    #[test]
+    fn test_publish_merges_triples() {
+        let registry = tempdir().unwrap();
+        let scratch = tempdir().unwrap();
+
+        let linux_gnu = scratch.path().join("linux-gnu");
+        fs::write(&linux_gnu, b"gnu binary").unwrap();
+        let linux_musl = scratch.path().join("linux-musl");
+        fs::write(&linux_musl, b"musl binary").unwrap();
+
+        let cfg = config(registry.path(), &linux_gnu, "x86_64-unknown-linux-gnu");
+        assert_eq!(start(cfg.clone()).unwrap(), PublishOutcome::CreatedVersion);
+        assert_eq!(start(cfg).unwrap(), PublishOutcome::AlreadyPublished);
+
+        let cfg = config(registry.path(), &linux_musl, "x86_64-unknown-linux-musl");
+        assert_eq!(start(cfg).unwrap(), PublishOutcome::AddedTriple);
+
+        let version_dir = registry.path().join("org.yshi.file-logger/v1.0.2");
+        let sha256s = fs::read_to_string(version_dir.join("sha256")).unwrap();
+        let lines: Vec<&str> = sha256s.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("  x86_64-unknown-linux-gnu"));
+        assert!(lines[1].ends_with("  x86_64-unknown-linux-musl"));
+
+        for line in lines {
+            let sha256 = line.split("  ").next().unwrap();
+            let blob = registry.path().join("blobs/sha256").join(sha256);
+            assert!(blob.exists(), "missing blob {}", blob.display());
+        }
+    }
+
+    #[test]
+    fn test_publish_rejects_different_bytes() {
+        let registry = tempdir().unwrap();
+        let scratch = tempdir().unwrap();
+
+        let artifact = scratch.path().join("artifact");
+        fs::write(&artifact, b"first build").unwrap();
+        let cfg = config(registry.path(), &artifact, "x86_64-unknown-linux-gnu");
+        start(cfg.clone()).unwrap();
+
+        fs::write(&artifact, b"second build").unwrap();
+        assert!(start(cfg.clone()).is_err());
+
+        let mut cfg = cfg;
+        cfg.host_triple = "x86_64-unknown-linux-musl".into();
+        cfg.manifest.required_remote_services.push("org.yshi.other".into());
+        assert!(start(cfg).is_err());
+    }
+
+    #[test]
+    fn test_validate_package_id() {
+        super::validate_package_id("org.yshi.file-logger").unwrap();
+        for package_id in &["", "blobs", "..", ".lock", "a/b", "/etc", "a b"] {
+            assert!(super::validate_package_id(package_id).is_err());
+        }
+
+        let registry = tempdir().unwrap();
+        let scratch = tempdir().unwrap();
+        let artifact = scratch.path().join("artifact");
+        fs::write(&artifact, b"binary").unwrap();
+        let mut cfg = config(registry.path(), &artifact, "x86_64-unknown-linux-gnu");
+        cfg.package_id = "../escape".into();
+        assert!(start(cfg).is_err());
+        assert!(!registry.path().join("../escape").exists());
+    }
+
+    #[test]
+    fn test_validate_manifest() {
+        let mut m = manifest();
+        assert!(super::validate_manifest(&m).is_ok());
+
+        m.required_local_services = m.provided_local_services.clone();
+        assert!(super::validate_manifest(&m).is_err());
+
+        let mut m = manifest();
+        m.provided_remote_services.push("../escape".into());
+        assert!(super::validate_manifest(&m).is_err());
+
+        let mut m = manifest();
+        m.extras_schema = Some(serde_json::json!({"type": "no-such-type"}));
+        assert!(super::validate_manifest(&m).is_err());
+    }
 }
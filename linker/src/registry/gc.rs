@@ -0,0 +1,409 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{read_dir, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use failure::Fallible;
+use semver::Version;
+use tracing::{event, Level};
+
+use yscloud_config_model::DeploymentManifest;
+
+use super::{
+    registry_file_get_versions, registry_file_read_sha256s, FileRegistry, PathFailure,
+    BLOBS_DIR_NAME, SHA256_FILE_NAME,
+};
+
+/// Blobs this young are left alone even when unreferenced: `publish` persists
+/// the blob before the version directory that references it, and refreshes
+/// the mtime of one it reuses.
+const BLOB_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// The prefix of what `apply_gc` has taken out of view but not removed yet.
+const DOOMED_PREFIX: &str = ".gc-";
+
+pub struct RetentionPolicy {
+    /// The number of newest versions to keep for every package.
+    pub keep_versions: usize,
+    /// package_id -> versions which must be kept regardless of age.
+    pub pinned: BTreeMap<String, BTreeSet<Version>>,
+}
+
+impl RetentionPolicy {
+    pub fn new(keep_versions: usize) -> RetentionPolicy {
+        RetentionPolicy {
+            keep_versions,
+            pinned: Default::default(),
+        }
+    }
+
+    pub fn pin_deployment(&mut self, dm: &DeploymentManifest) {
+        for component in &dm.components {
+            self.pinned
+                .entry(component.package_id.clone())
+                .or_insert_with(Default::default)
+                .insert(component.version.clone());
+        }
+    }
+
+    fn is_pinned(&self, package_id: &str, version: &Version) -> bool {
+        self.pinned
+            .get(package_id)
+            .map(|versions| versions.contains(version))
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct GcPlan {
+    pub remove_versions: Vec<(String, Version)>,
+    pub remove_blobs: Vec<String>,
+    /// Versions and blobs an interrupted `apply_gc` left behind.
+    pub remove_leftovers: Vec<PathBuf>,
+}
+
+impl GcPlan {
+    pub fn is_empty(&self) -> bool {
+        self.remove_versions.is_empty()
+            && self.remove_blobs.is_empty()
+            && self.remove_leftovers.is_empty()
+    }
+}
+
+fn is_recent(path: &Path, now: SystemTime) -> Fallible<bool> {
+    let metadata = path
+        .metadata()
+        .map_err(|e| PathFailure::new(path.to_owned(), e))?;
+    let age = now.duration_since(metadata.modified()?).unwrap_or_default();
+    Ok(age < BLOB_GRACE_PERIOD)
+}
+
+impl FileRegistry {
+    fn package_ids(&self) -> Fallible<Vec<String>> {
+        let mut out = Vec::new();
+
+        let entries = read_dir(&self.base_path)
+            .map_err(|e| PathFailure::new(self.base_path.clone(), e))?;
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            if let Ok(name) = entry.file_name().into_string() {
+                if name == BLOBS_DIR_NAME || name.starts_with('.') {
+                    continue;
+                }
+                out.push(name);
+            }
+        }
+
+        out.sort();
+        Ok(out)
+    }
+
+    fn version_blobs(&self, package_id: &str, version: &Version) -> Fallible<Vec<String>> {
+        let mut sha256_path = self.version_path(package_id, version);
+        sha256_path.push(SHA256_FILE_NAME);
+        let file = File::open(&sha256_path).map_err(|e| PathFailure::new(sha256_path, e))?;
+        let sha256s = registry_file_read_sha256s(file)?;
+        Ok(sha256s.into_iter().map(|(_, v)| v).collect())
+    }
+
+    /// Locks every package, including those created while we were at it.
+    fn lock_all_packages(&self) -> Fallible<BTreeMap<String, File>> {
+        let mut locks = BTreeMap::new();
+        loop {
+            let package_ids = self.package_ids()?;
+            if package_ids.iter().all(|id| locks.contains_key(id)) {
+                return Ok(locks);
+            }
+            for package_id in package_ids {
+                if !locks.contains_key(&package_id) {
+                    let lock = self.lock_package(&package_id)?;
+                    locks.insert(package_id, lock);
+                }
+            }
+        }
+    }
+
+    /// Works out what `apply_gc` would remove without touching anything.
+    pub fn plan_gc(&self, policy: &RetentionPolicy) -> Fallible<GcPlan> {
+        let mut plan = GcPlan::default();
+        let mut referenced_blobs = BTreeSet::new();
+
+        for package_id in self.package_ids()? {
+            let package_path = self.base_path.join(&package_id);
+            for entry in
+                read_dir(&package_path).map_err(|e| PathFailure::new(package_path.clone(), e))?
+            {
+                let entry = entry?;
+                let name = entry.file_name();
+                if name.to_string_lossy().starts_with(DOOMED_PREFIX) {
+                    plan.remove_leftovers.push(entry.path());
+                }
+            }
+
+            let mut versions = registry_file_get_versions(&self.base_path, &package_id)?;
+            versions.sort_by(|a, b| b.cmp(a));
+
+            for (idx, version) in versions.into_iter().enumerate() {
+                if idx < policy.keep_versions || policy.is_pinned(&package_id, &version) {
+                    referenced_blobs.extend(self.version_blobs(&package_id, &version)?);
+                } else {
+                    plan.remove_versions.push((package_id.clone(), version));
+                }
+            }
+        }
+
+        for (package_id, versions) in &policy.pinned {
+            for version in versions {
+                if !self.version_path(package_id, version).exists() {
+                    event!(
+                        Level::WARN,
+                        "pinned {} v{} is not in the registry",
+                        package_id,
+                        version
+                    );
+                }
+            }
+        }
+
+        let mut blob_dir = self.base_path.clone();
+        blob_dir.push(BLOBS_DIR_NAME);
+        blob_dir.push("sha256");
+        if !blob_dir.exists() {
+            return Ok(plan);
+        }
+
+        let now = SystemTime::now();
+        for entry in read_dir(&blob_dir).map_err(|e| PathFailure::new(blob_dir.clone(), e))? {
+            let entry = entry?;
+            let name = match entry.file_name().into_string() {
+                Ok(name) => name,
+                Err(_) => continue,
+            };
+            if name.starts_with(DOOMED_PREFIX) {
+                plan.remove_leftovers.push(entry.path());
+                continue;
+            }
+            // staged blobs from in-progress publishes.
+            if name.starts_with('.') || referenced_blobs.contains(&name) {
+                continue;
+            }
+
+            if is_recent(&entry.path(), now)? {
+                event!(Level::DEBUG, "keeping recent unreferenced blob {}", name);
+                continue;
+            }
+
+            plan.remove_blobs.push(name);
+        }
+
+        Ok(plan)
+    }
+
+    pub fn apply_gc(&self, plan: &GcPlan) -> Fallible<()> {
+        for path in &plan.remove_leftovers {
+            let removed = if path.is_dir() {
+                std::fs::remove_dir_all(path)
+            } else {
+                std::fs::remove_file(path)
+            };
+            match removed {
+                Ok(()) => event!(Level::INFO, "removed leftover {}", path.display()),
+                Err(ref e) if e.kind() == io::ErrorKind::NotFound => (),
+                Err(e) => return Err(PathFailure::new(path.clone(), e).into()),
+            }
+        }
+
+        for (package_id, version) in &plan.remove_versions {
+            let _lock = self.lock_package(package_id)?;
+
+            // move the directory out of view first so readers never observe
+            // a half-deleted version.
+            let version_path = self.version_path(package_id, version);
+            let mut doomed_path: PathBuf = version_path.clone();
+            doomed_path.set_file_name(format!("{}v{}", DOOMED_PREFIX, version));
+
+            std::fs::rename(&version_path, &doomed_path)
+                .map_err(|e| PathFailure::new(version_path, e))?;
+            std::fs::remove_dir_all(&doomed_path)
+                .map_err(|e| PathFailure::new(doomed_path, e))?;
+
+            event!(Level::INFO, "removed {} v{}", package_id, version);
+        }
+
+        if plan.remove_blobs.is_empty() {
+            return Ok(());
+        }
+
+        // publishes since the plan was made may have taken blobs up again.
+        // Those that have finished reference them, and the locks keep others
+        // from finishing; one that is under way has refreshed the mtime of
+        // the blob it reuses, which is looked at once the blob is out of its
+        // way.
+        let _locks = self.lock_all_packages()?;
+        let mut referenced_blobs = BTreeSet::new();
+        for package_id in self.package_ids()? {
+            for version in registry_file_get_versions(&self.base_path, &package_id)? {
+                referenced_blobs.extend(self.version_blobs(&package_id, &version)?);
+            }
+        }
+
+        for sha256 in &plan.remove_blobs {
+            if referenced_blobs.contains(sha256) {
+                event!(Level::INFO, "keeping blob {}, referenced again", sha256);
+                continue;
+            }
+
+            let blob_path = self.blob_path(sha256);
+            let mut doomed_path = blob_path.clone();
+            doomed_path.set_file_name(format!("{}{}", DOOMED_PREFIX, sha256));
+            match std::fs::rename(&blob_path, &doomed_path) {
+                Ok(()) => (),
+                Err(ref e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(PathFailure::new(blob_path, e).into()),
+            }
+            if is_recent(&doomed_path, SystemTime::now())? {
+                event!(Level::INFO, "keeping blob {}, reused again", sha256);
+                std::fs::rename(&doomed_path, &blob_path)
+                    .map_err(|e| PathFailure::new(blob_path, e))?;
+                continue;
+            }
+            std::fs::remove_file(&doomed_path).map_err(|e| PathFailure::new(doomed_path, e))?;
+            event!(Level::INFO, "removed blob {}", sha256);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, OpenOptions};
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    use semver::Version;
+    use tempfile::tempdir;
+    use yscloud_config_model::{ApplicationManifest, FdProtocol};
+
+    use super::{FileRegistry, RetentionPolicy};
+
+    fn age_file(path: &Path, age: Duration) {
+        let file = OpenOptions::new().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - age).unwrap();
+    }
+
+    #[test]
+    fn test_plan_gc_keeps_newest_and_pinned() {
+        let root = tempdir().unwrap();
+        let registry = FileRegistry::new(root.path());
+
+        let blob_dir = root.path().join("blobs/sha256");
+        fs::create_dir_all(&blob_dir).unwrap();
+
+        for (idx, version) in ["1.0.0", "1.1.0", "1.2.0", "2.0.0"].iter().enumerate() {
+            let version_dir = root.path().join(format!("org.yshi.example/v{}", version));
+            fs::create_dir_all(&version_dir).unwrap();
+            let sha256 = format!("{:064}", idx);
+            fs::write(
+                version_dir.join("sha256"),
+                format!("{}  x86_64-unknown-linux-gnu\n", sha256),
+            )
+            .unwrap();
+            fs::write(blob_dir.join(&sha256), b"").unwrap();
+            age_file(&blob_dir.join(&sha256), super::BLOB_GRACE_PERIOD * 2);
+        }
+
+        let mut policy = RetentionPolicy::new(2);
+        policy
+            .pinned
+            .entry("org.yshi.example".into())
+            .or_insert_with(Default::default)
+            .insert(Version::parse("1.0.0").unwrap());
+
+        let plan = registry.plan_gc(&policy).unwrap();
+        assert_eq!(
+            plan.remove_versions,
+            vec![("org.yshi.example".to_string(), Version::parse("1.1.0").unwrap())]
+        );
+        assert_eq!(plan.remove_blobs, vec![format!("{:064}", 1)]);
+
+        registry.apply_gc(&plan).unwrap();
+        assert!(!root.path().join("org.yshi.example/v1.1.0").exists());
+        assert!(root.path().join("org.yshi.example/v1.0.0").exists());
+        assert!(!blob_dir.join(format!("{:064}", 1)).exists());
+        assert!(registry.plan_gc(&policy).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_gc_keeps_reused_blobs() {
+        let root = tempdir().unwrap();
+        let registry = FileRegistry::new(root.path());
+        let artifact = root.path().join("artifact");
+        fs::write(&artifact, b"binary").unwrap();
+
+        // left unreferenced long ago, along with what an interrupted gc had
+        // hidden.
+        let sha256 = registry.stage_blob(&artifact).unwrap().sha256;
+        let blob_path = registry.blob_path(&sha256);
+        fs::copy(&artifact, &blob_path).unwrap();
+        age_file(&blob_path, super::BLOB_GRACE_PERIOD * 2);
+        let leftover_version = root.path().join("org.yshi.example/.gc-v0.9.0");
+        fs::create_dir_all(&leftover_version).unwrap();
+        let leftover_blob = blob_path.with_file_name(format!(".gc-{:064}", 0));
+        fs::write(&leftover_blob, b"").unwrap();
+
+        let policy = RetentionPolicy::new(1);
+        let plan = registry.plan_gc(&policy).unwrap();
+        assert_eq!(plan.remove_blobs, vec![sha256.clone()]);
+        assert_eq!(plan.remove_leftovers.len(), 2);
+
+        // published again before the plan is applied.
+        let manifest = ApplicationManifest {
+            provided_remote_services: Vec::new(),
+            provided_local_services: Vec::new(),
+            required_remote_services: Vec::new(),
+            required_local_services: Vec::new(),
+            permissions: Vec::new(),
+            extras_schema: None,
+            live_config: false,
+            fd_protocol: FdProtocol::ConfigFd,
+        };
+        let blob = registry.stage_blob(&artifact).unwrap();
+        let version = Version::parse("1.0.0").unwrap();
+        registry
+            .publish(
+                "org.yshi.example",
+                &version,
+                &manifest,
+                "x86_64-unknown-linux-gnu",
+                blob,
+            )
+            .unwrap();
+        let age = SystemTime::now()
+            .duration_since(fs::metadata(&blob_path).unwrap().modified().unwrap())
+            .unwrap_or_default();
+        assert!(age < super::BLOB_GRACE_PERIOD);
+
+        // referenced, even once it looks old again.
+        age_file(&blob_path, super::BLOB_GRACE_PERIOD * 2);
+        registry.apply_gc(&plan).unwrap();
+        assert!(blob_path.exists());
+        assert!(!leftover_version.exists());
+        assert!(!leftover_blob.exists());
+
+        // and merely reused, but not yet referenced.
+        fs::remove_dir_all(registry.version_path("org.yshi.example", &version)).unwrap();
+        fs::OpenOptions::new()
+            .write(true)
+            .open(&blob_path)
+            .unwrap()
+            .set_modified(SystemTime::now())
+            .unwrap();
+        registry.apply_gc(&plan).unwrap();
+        assert!(blob_path.exists());
+    }
+}
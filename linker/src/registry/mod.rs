@@ -2,25 +2,50 @@ use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::ffi::OsString;
 use std::fs::read_dir;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::os::unix::io::AsRawFd;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::SystemTime;
 use std::fmt;
 
-use failure::Fallible;
+use digest::{Digest, FixedOutput};
+use failure::{Fail, Fallible};
 use futures::future::FutureExt;
 use futures::prelude::Future;
+use nix::fcntl::{flock, FlockArg};
 use semver::{Version, VersionReq};
+use sha2::Sha256;
+use tempfile::NamedTempFile;
+use tracing::{event, Level};
 
 use yscloud_config_model::{ApplicationManifest, RegistryEntry};
 
+use crate::util::hexify;
+
+mod gc;
+pub use self::gc::RetentionPolicy;
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const SHA256_FILE_NAME: &str = "sha256";
+const LOCK_FILE_NAME: &str = ".lock";
+pub const BLOBS_DIR_NAME: &str = "blobs";
+
 pub trait Registry {
-    fn find_best_entry_for_version(
+    /// Finds the newest version of `package_id` that satisfies every one of
+    /// `ver_reqs`.
+    fn find_best_entry_for_versions(
+        &self,
+        package_id: &str,
+        ver_reqs: &[VersionReq],
+    ) -> Pin<Box<dyn Future<Output = Fallible<RegistryEntry>> + Send + 'static>>;
+
+    fn find_exact_entry(
         &self,
         package_id: &str,
-        ver_req: &VersionReq,
+        version: &Version,
     ) -> Pin<Box<dyn Future<Output = Fallible<RegistryEntry>> + Send + 'static>>;
 }
 
@@ -47,12 +72,20 @@ impl RegistryShared {
 }
 
 impl Registry for RegistryShared {
-    fn find_best_entry_for_version(
+    fn find_best_entry_for_versions(
         &self,
         package_id: &str,
-        ver_req: &VersionReq,
+        ver_reqs: &[VersionReq],
     ) -> Pin<Box<dyn Future<Output = Fallible<RegistryEntry>> + Send + 'static>> {
-        Registry::find_best_entry_for_version(&*self.0, package_id, ver_req)
+        Registry::find_best_entry_for_versions(&*self.0, package_id, ver_reqs)
+    }
+
+    fn find_exact_entry(
+        &self,
+        package_id: &str,
+        version: &Version,
+    ) -> Pin<Box<dyn Future<Output = Fallible<RegistryEntry>> + Send + 'static>> {
+        Registry::find_exact_entry(&*self.0, package_id, version)
     }
 }
 
@@ -62,25 +95,245 @@ impl FileRegistry {
             base_path: base_path.to_owned(),
         }
     }
+
+    /// Serializes writers of the same package; released when the file is dropped.
+    fn lock_package(&self, package_id: &str) -> Fallible<File> {
+        let mut lock_path = self.base_path.clone();
+        lock_path.push(package_id);
+        lock_path.push(LOCK_FILE_NAME);
+        let lock_file = File::create(&lock_path).map_err(|e| PathFailure::new(lock_path, e))?;
+        flock(lock_file.as_raw_fd(), FlockArg::LockExclusive)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        Ok(lock_file)
+    }
+
+    pub fn version_path(&self, package_id: &str, version: &Version) -> PathBuf {
+        let mut path = self.base_path.clone();
+        path.push(package_id);
+        path.push(format!("v{}", version));
+        path
+    }
+
+    /// Artifact blobs are stored content-addressed, shared by all packages.
+    pub fn blob_path(&self, sha256: &str) -> PathBuf {
+        let mut path = self.base_path.clone();
+        path.push(BLOBS_DIR_NAME);
+        path.push("sha256");
+        path.push(sha256);
+        path
+    }
+
+    /// Copies an artifact into the blob directory under a temporary name,
+    /// hashing it along the way.  Nothing becomes visible in the registry
+    /// until the staged blob is passed to `publish`.
+    pub fn stage_blob(&self, artifact: &Path) -> Fallible<StagedBlob> {
+        let mut blob_dir = self.base_path.clone();
+        blob_dir.push(BLOBS_DIR_NAME);
+        blob_dir.push("sha256");
+        std::fs::create_dir_all(&blob_dir).map_err(|e| PathFailure::new(blob_dir.clone(), e))?;
+
+        let mut source = File::open(artifact).map_err(|e| PathFailure::new(artifact.into(), e))?;
+        let mut file = NamedTempFile::new_in(&blob_dir)?;
+        let mut sha256_state = Sha256::default();
+        let mut content_length = 0;
+
+        let mut buf = vec![0; 128 * 1024];
+        loop {
+            let read_length = source.read(&mut buf[..])?;
+            if read_length == 0 {
+                break;
+            }
+            content_length += read_length as u64;
+            sha256_state.update(&buf[..read_length]);
+            file.write_all(&buf[..read_length])?;
+        }
+        file.as_file().sync_all()?;
+
+        let mut scratch = [0; 256 / 8 * 2];
+        let sha256 = hexify(&mut scratch, &sha256_state.finalize_fixed())
+            .unwrap()
+            .to_string();
+
+        Ok(StagedBlob {
+            file,
+            sha256,
+            content_length,
+        })
+    }
+
+    /// Adds `host_triple` to the `package_id` at `version`, creating the
+    /// version directory if this is the first triple published for it.
+    ///
+    /// A version's manifest is shared by all of its triples, so publishing a
+    /// different manifest under an existing version is rejected, as is
+    /// publishing different bytes for a triple that is already present.
+    pub fn publish(
+        &self,
+        package_id: &str,
+        version: &Version,
+        manifest: &ApplicationManifest,
+        host_triple: &str,
+        blob: StagedBlob,
+    ) -> Fallible<PublishOutcome> {
+        let mut package_path = self.base_path.clone();
+        package_path.push(package_id);
+        std::fs::create_dir_all(&package_path)
+            .map_err(|e| PathFailure::new(package_path.clone(), e))?;
+
+        let _lock = self.lock_package(package_id)?;
+
+        let blob_path = self.blob_path(&blob.sha256);
+        let version_path = self.version_path(package_id, version);
+        let existing = if version_path.exists() {
+            Some(registry_file_load_version(&self.base_path, package_id, version)?)
+        } else {
+            None
+        };
+
+        let mut sha256s = BTreeMap::new();
+        if let Some(existing) = existing {
+            if existing.manifest != *manifest {
+                return Err(PublishConflict {
+                    package_id: package_id.to_string(),
+                    version: version.clone(),
+                    reason: "manifest differs from the one already published".into(),
+                }
+                .into());
+            }
+
+            if let Some(existing_sha256) = existing.sha256s.get(host_triple) {
+                if *existing_sha256 != blob.sha256 {
+                    return Err(PublishConflict {
+                        package_id: package_id.to_string(),
+                        version: version.clone(),
+                        reason: format!(
+                            "{} is already published with sha256 {}, refusing to replace it with {}",
+                            host_triple, existing_sha256, blob.sha256
+                        ),
+                    }
+                    .into());
+                }
+
+                // re-publishing identical bytes only needs to restore the blob,
+                // in case it was garbage collected.
+                blob.persist(&blob_path)?;
+                return Ok(PublishOutcome::AlreadyPublished);
+            }
+
+            sha256s.extend(existing.sha256s.into_iter());
+        }
+
+        sha256s.insert(host_triple.to_string(), blob.sha256.clone());
+        blob.persist(&blob_path)?;
+
+        if version_path.exists() {
+            let mut sha256_path = version_path.clone();
+            sha256_path.push(SHA256_FILE_NAME);
+
+            let mut staged = NamedTempFile::new_in(&version_path)?;
+            registry_file_write_sha256s(staged.as_file_mut(), &sha256s)?;
+            staged
+                .persist(&sha256_path)
+                .map_err(|e| PathFailure::new(sha256_path, e.error))?;
+
+            return Ok(PublishOutcome::AddedTriple);
+        }
+
+        let staged_dir = tempfile::Builder::new()
+            .prefix(&format!(".v{}-", version))
+            .tempdir_in(&package_path)?;
+
+        let mut manifest_path = staged_dir.path().to_owned();
+        manifest_path.push(MANIFEST_FILE_NAME);
+        let mut manifest_file = File::create(&manifest_path)?;
+        serde_json::to_writer_pretty(&mut manifest_file, manifest)?;
+        manifest_file.write_all(b"\n")?;
+        manifest_file.sync_all()?;
+
+        let mut sha256_path = staged_dir.path().to_owned();
+        sha256_path.push(SHA256_FILE_NAME);
+        registry_file_write_sha256s(&mut File::create(&sha256_path)?, &sha256s)?;
+
+        let staged_dir = staged_dir.into_path();
+        std::fs::rename(&staged_dir, &version_path).map_err(|e| {
+            let _ = std::fs::remove_dir_all(&staged_dir);
+            PathFailure::new(version_path.clone(), e)
+        })?;
+
+        Ok(PublishOutcome::CreatedVersion)
+    }
+}
+
+pub struct StagedBlob {
+    file: NamedTempFile,
+    pub sha256: String,
+    pub content_length: u64,
+}
+
+impl StagedBlob {
+    /// Reusing a blob that is already present refreshes its mtime, so gc
+    /// doesn't take it while it is unreferenced.
+    fn persist(self, path: &Path) -> Fallible<()> {
+        match OpenOptions::new().write(true).open(path) {
+            Ok(existing) => {
+                event!(Level::DEBUG, "blob {} already present", self.sha256);
+                existing
+                    .set_modified(SystemTime::now())
+                    .map_err(|e| PathFailure::new(path.to_owned(), e))?;
+                return Ok(());
+            }
+            // e.g. just taken by gc.
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(PathFailure::new(path.to_owned(), e).into()),
+        }
+        self.file
+            .persist(path)
+            .map_err(|e| PathFailure::new(path.to_owned(), e.error))?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PublishOutcome {
+    CreatedVersion,
+    AddedTriple,
+    AlreadyPublished,
+}
+
+#[derive(Debug, Fail)]
+pub struct PublishConflict {
+    package_id: String,
+    version: Version,
+    reason: String,
+}
+
+impl fmt::Display for PublishConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "can not publish {} v{}: {}",
+            self.package_id, self.version, self.reason
+        )
+    }
 }
 
 impl Registry for FileRegistry {
-    fn find_best_entry_for_version(
+    fn find_best_entry_for_versions(
         &self,
         package_id: &str,
-        ver_req: &VersionReq,
+        ver_reqs: &[VersionReq],
     ) -> Pin<Box<dyn Future<Output = Fallible<RegistryEntry>> + Send + 'static>> {
 
         let base_path = self.base_path.clone();
         let package_id = package_id.to_owned();
-        let ver_req = ver_req.to_owned();
+        let ver_reqs = ver_reqs.to_owned();
 
         async move {
             let mut current_winner: Option<Version> = None;
 
             for version in registry_file_get_versions(&base_path, &package_id)? {
 
-                if ver_req.matches(&version) {
+                if ver_reqs.iter().all(|r| r.matches(&version)) {
                     if let Some(old_candidate) = current_winner.as_mut() {
                         if *old_candidate < version {
                             *old_candidate = version;
@@ -100,6 +353,15 @@ impl Registry for FileRegistry {
         }
         .boxed()
     }
+
+    fn find_exact_entry(
+        &self,
+        package_id: &str,
+        version: &Version,
+    ) -> Pin<Box<dyn Future<Output = Fallible<RegistryEntry>> + Send + 'static>> {
+        let res = registry_file_load_version(&self.base_path, package_id, version);
+        futures::future::ready(res).boxed()
+    }
 }
 
 struct PathFailure {
@@ -171,12 +433,31 @@ fn registry_file_load_version(
     let mut hash_path = base_path.to_owned();
     hash_path.push(package_id);
     hash_path.push(format!("v{}", version));
-    hash_path.push("sha256");
+    hash_path.push(SHA256_FILE_NAME);
+
+    let file = File::open(&hash_path).map_err(|e| PathFailure::new(hash_path.clone(), e))?;
+    let sha256s = registry_file_read_sha256s(file)?;
+
+    let mut manifest_path = base_path.to_owned();
+    manifest_path.push(package_id);
+    manifest_path.push(format!("v{}", version));
+    manifest_path.push(MANIFEST_FILE_NAME);
+
+    let file = File::open(&manifest_path).map_err(|e| PathFailure::new(manifest_path.clone(), e))?;
+    let manifest: ApplicationManifest = serde_json::from_reader(BufReader::new(file))?;
+
+    Ok(RegistryEntry {
+        version: version.clone(),
+        sha256s,
+        manifest,
+    })
+}
 
+/// Parses a `sha256` file: one `<hex-sha256>  <host-triple>` per line.
+fn registry_file_read_sha256s<R: Read>(rdr: R) -> Fallible<HashMap<String, String>> {
     let mut sha256s: HashMap<String, String> = Default::default();
 
-    let file = File::open(&hash_path)?;
-    for line in BufReader::new(file).lines() {
+    for line in BufReader::new(rdr).lines() {
         let line = line?;
         let line = line.trim();
         if line == "" {
@@ -192,19 +473,19 @@ fn registry_file_load_version(
         sha256s.insert(host_triple.to_string(), sha256.to_string());
     }
 
-    let mut manifest_path = base_path.to_owned();
-    manifest_path.push(package_id);
-    manifest_path.push(format!("v{}", version));
-    manifest_path.push("manifest.json");
-
-    let file = File::open(&manifest_path)?;
-    let manifest: ApplicationManifest = serde_json::from_reader(BufReader::new(file))?;
+    Ok(sha256s)
+}
 
-    Ok(RegistryEntry {
-        version: version.clone(),
-        sha256s,
-        manifest,
-    })
+fn registry_file_write_sha256s(file: &mut File, sha256s: &BTreeMap<String, String>) -> Fallible<()> {
+    {
+        let mut wr = BufWriter::new(&mut *file);
+        for (host_triple, sha256) in sha256s {
+            writeln!(wr, "{}  {}", sha256, host_triple)?;
+        }
+        wr.flush()?;
+    }
+    file.sync_all()?;
+    Ok(())
 }
 
 impl Default for MemRegistry {
@@ -223,10 +504,10 @@ impl MemRegistry {
 }
 
 impl Registry for MemRegistry {
-    fn find_best_entry_for_version(
+    fn find_best_entry_for_versions(
         &self,
         package_id: &str,
-        ver_req: &VersionReq,
+        ver_reqs: &[VersionReq],
     ) -> Pin<Box<dyn Future<Output = Fallible<RegistryEntry>> + Send + 'static>> {
         let min_version = Version::new(0, 0, 0);
         let min_key = (package_id.to_owned(), min_version);
@@ -236,7 +517,7 @@ impl Registry for MemRegistry {
             if p_id != package_id {
                 break;
             }
-            if !ver_req.matches(version) {
+            if !ver_reqs.iter().all(|r| r.matches(version)) {
                 continue;
             }
             if let Some(old_candidate) = current_winner.as_mut() {
@@ -257,4 +538,20 @@ impl Registry for MemRegistry {
         })
         .boxed()
     }
+
+    fn find_exact_entry(
+        &self,
+        package_id: &str,
+        version: &Version,
+    ) -> Pin<Box<dyn Future<Output = Fallible<RegistryEntry>> + Send + 'static>> {
+        let key = (package_id.to_owned(), version.to_owned());
+        futures::future::ready(match self.known_entries.get(&key) {
+            Some(entry) => Ok(entry.clone()),
+            None => {
+                let msg = format!("{} v{} is not in the registry", package_id, version);
+                Err(io::Error::new(io::ErrorKind::Other, msg).into())
+            }
+        })
+        .boxed()
+    }
 }
@@ -0,0 +1,99 @@
+use std::fmt;
+
+use failure::Fail;
+use jsonschema::JSONSchema;
+use serde_json::Value;
+
+#[derive(Debug, Fail)]
+pub struct InvalidExtrasSchema {
+    reason: String,
+}
+
+impl fmt::Display for InvalidExtrasSchema {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid extras schema: {}", self.reason)
+    }
+}
+
+#[derive(Debug, Fail)]
+pub struct InvalidConfiguration {
+    package_id: String,
+    // (JSON pointer, message)
+    errors: Vec<(String, String)>,
+}
+
+impl fmt::Display for InvalidConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid configuration for {}:", self.package_id)?;
+        for (path, message) in &self.errors {
+            let path = if path.is_empty() { "/" } else { &path[..] };
+            write!(f, "\n    {}: {}", path, message)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn compile_extras_schema(schema: &Value) -> Result<JSONSchema, InvalidExtrasSchema> {
+    JSONSchema::compile(schema).map_err(|err| InvalidExtrasSchema {
+        reason: err.to_string(),
+    })
+}
+
+/// Checks a package's configuration against the schema from its manifest.
+/// Packages which don't publish a schema accept anything.
+pub fn validate_extras(
+    package_id: &str,
+    schema: Option<&Value>,
+    extras: &Value,
+) -> failure::Fallible<()> {
+    let schema = match schema {
+        Some(schema) => compile_extras_schema(schema)?,
+        None => return Ok(()),
+    };
+
+    if let Err(errors) = schema.validate(extras) {
+        let errors = errors
+            .map(|err| (err.instance_path.to_string(), err.to_string()))
+            .collect();
+
+        return Err(InvalidConfiguration {
+            package_id: package_id.to_string(),
+            errors,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::validate_extras;
+
+    #[test]
+    fn test_validate_extras() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "hostnames": {
+                    "type": "array",
+                    "items": {"type": "string"}
+                }
+            },
+            "required": ["hostnames"]
+        });
+
+        let extras = json!({"hostnames": ["yshi.org"]});
+        validate_extras("org.yshi.sni_multiplexor", Some(&schema), &extras).unwrap();
+        validate_extras("org.yshi.sni_multiplexor", None, &json!(null)).unwrap();
+
+        let extras = json!({"hostnames": ["yshi.org", 443]});
+        let err = validate_extras("org.yshi.sni_multiplexor", Some(&schema), &extras).unwrap_err();
+        assert_eq!(
+            format!("{}", err),
+            "invalid configuration for org.yshi.sni_multiplexor:\n    /hostnames/1: 443 is not of type \"string\""
+        );
+    }
+}
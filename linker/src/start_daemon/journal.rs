@@ -0,0 +1,240 @@
+//! What the daemon is running, kept under the approot so that a restarted
+//! daemon (e.g. after an upgrade) can re-adopt the components instead of
+//! restarting them.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use failure::Fallible;
+use nix::unistd::Pid;
+use serde::{Deserialize, Serialize};
+use tracing::{event, Level};
+use uuid::Uuid;
+use yscloud_config_model::{DeploymentManifest, Ksuid};
+
+use crate::document::{self, Format};
+use crate::platform::{self, AdoptedChild, JournalEvent, JournalFn};
+
+pub const JOURNAL_FILE: &str = "daemon-journal.json";
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct Journal {
+    // deployment name -> what's running of it
+    #[serde(default)]
+    pub deployments: BTreeMap<String, JournalDeployment>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct JournalDeployment {
+    pub manifest: DeploymentManifest,
+    // what the instances got as `AppConfiguration::trace_id`.
+    #[serde(default)]
+    pub trace_id: Option<Ksuid>,
+    pub instances: Vec<JournalInstance>,
+    // the `Workdir::Ephemeral` directories, emptied once the deployment stops.
+    #[serde(default)]
+    pub ephemeral_workdirs: Vec<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct JournalInstance {
+    pub package_id: String,
+    pub instance_id: Uuid,
+    pub pid: i32,
+    // see `platform::process_start_time`, which guards against pid reuse.
+    pub start_time: Option<u64>,
+}
+
+impl Journal {
+    pub fn path(approot: &Path) -> PathBuf {
+        approot.join(JOURNAL_FILE)
+    }
+
+    /// An empty journal if there is none yet.
+    pub fn load(path: &Path) -> Fallible<Journal> {
+        if !path.exists() {
+            return Ok(Journal::default());
+        }
+        document::read(path, Some(Format::Json))
+    }
+
+    pub fn save(&self, path: &Path) -> Fallible<()> {
+        document::write_value(path, Format::Json, &serde_json::to_value(self)?)
+    }
+}
+
+impl JournalDeployment {
+    /// Opens a pidfd for every recorded instance that is still the process
+    /// we started.  Fails with the ones found if any is gone, since a
+    /// deployment is only adopted whole.
+    pub fn adopt(&self) -> Result<Vec<AdoptedChild>, Vec<AdoptedChild>> {
+        let mut adopted = Vec::new();
+        let mut complete = true;
+        for instance in &self.instances {
+            let pid = Pid::from_raw(instance.pid);
+            let pidfd = match platform::pidfd_open(pid) {
+                Ok(pidfd) => pidfd,
+                Err(err) => {
+                    event!(
+                        Level::INFO,
+                        "{} ({}) is gone: {}",
+                        instance.package_id,
+                        pid,
+                        err
+                    );
+                    complete = false;
+                    continue;
+                }
+            };
+            // checked after opening the pidfd, so the pid can't be reused in
+            // between.
+            let start_time = platform::process_start_time(pid);
+            if start_time.is_none() || start_time != instance.start_time {
+                event!(
+                    Level::INFO,
+                    "{} ({}) is gone, its pid was reused",
+                    instance.package_id,
+                    pid
+                );
+                complete = false;
+                continue;
+            }
+            adopted.push(AdoptedChild {
+                package_id: instance.package_id.clone(),
+                pid,
+                pidfd,
+            });
+        }
+
+        if complete {
+            Ok(adopted)
+        } else {
+            Err(adopted)
+        }
+    }
+}
+
+/// Keeps the journal at `path` up to date for `manifest`'s deployment,
+/// started with `trace_id` and using `ephemeral_workdirs`.  A deployment is dropped from it once none of
+/// its instances are left.
+pub fn journal_fn(
+    path: PathBuf,
+    manifest: DeploymentManifest,
+    trace_id: Ksuid,
+    ephemeral_workdirs: Vec<PathBuf>,
+) -> Fallible<JournalFn> {
+    let mut journal = Journal::load(&path)?;
+    let deployment_name = manifest.deployment_name.clone();
+    let mut manifest = Some(manifest);
+
+    Ok(Box::new(move |ev| {
+        match ev {
+            JournalEvent::Started {
+                package_id,
+                instance_id,
+                pid,
+                ..
+            } => {
+                let deployment = journal
+                    .deployments
+                    .entry(deployment_name.clone())
+                    .or_insert_with(|| JournalDeployment {
+                        manifest: manifest.take().unwrap(),
+                        trace_id: Some(trace_id),
+                        instances: Vec::new(),
+                        ephemeral_workdirs: ephemeral_workdirs.clone(),
+                    });
+                deployment.instances.push(JournalInstance {
+                    package_id: package_id.to_string(),
+                    instance_id,
+                    pid: pid.as_raw(),
+                    start_time: platform::process_start_time(pid),
+                });
+            }
+            JournalEvent::Exited { pid, .. } => {
+                let deployment = match journal.deployments.get_mut(&deployment_name) {
+                    Some(deployment) => deployment,
+                    None => return,
+                };
+                deployment.instances.retain(|i| i.pid != pid.as_raw());
+                if deployment.instances.is_empty() {
+                    let deployment = journal.deployments.remove(&deployment_name).unwrap();
+                    manifest = Some(deployment.manifest);
+                }
+            }
+        }
+
+        // an out of date journal only costs us a restart of the deployment.
+        if let Err(err) = journal.save(&path) {
+            event!(Level::ERROR, "writing {} failed: {}", path.display(), err);
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use nix::unistd::{getpid, Pid};
+    use tempfile::tempdir;
+    use uuid::Uuid;
+    use yscloud_config_model::migrate::CURRENT_FORMAT_VERSION;
+    use yscloud_config_model::{DeploymentManifest, Ksuid};
+
+    use super::{journal_fn, Journal};
+    use crate::platform::JournalEvent;
+
+    #[test]
+    fn test_journal() {
+        let dir = tempdir().unwrap();
+        let path = Journal::path(dir.path());
+        let manifest = DeploymentManifest {
+            format_version: CURRENT_FORMAT_VERSION,
+            deployment_name: "example-deployment".into(),
+            public_services: Vec::new(),
+            components: Vec::new(),
+            path_overrides: Default::default(),
+        };
+
+        let trace_id = Ksuid::generate();
+        let workdirs = vec![dir.path().join("example-deployment/org.yshi.staticserver")];
+        let mut record =
+            journal_fn(path.clone(), manifest.clone(), trace_id, workdirs.clone()).unwrap();
+        // we stand in for a running component.
+        record(JournalEvent::Started {
+            package_id: "org.yshi.staticserver",
+            instance_id: Uuid::new_v4(),
+            pid: getpid(),
+            start_duration: Duration::from_millis(1),
+        });
+
+        let journal = Journal::load(&path).unwrap();
+        let deployment = &journal.deployments["example-deployment"];
+        assert_eq!(deployment.manifest, manifest);
+        assert_eq!(deployment.trace_id, Some(trace_id));
+        assert_eq!(deployment.ephemeral_workdirs, workdirs);
+        let adopted = deployment.adopt().unwrap();
+        assert_eq!(adopted[0].pid, getpid());
+
+        let mut stale = journal;
+        stale
+            .deployments
+            .get_mut("example-deployment")
+            .unwrap()
+            .instances[0]
+            .start_time = Some(1);
+        assert!(stale.deployments["example-deployment"].adopt().is_err());
+
+        record(JournalEvent::Exited {
+            pid: getpid(),
+            exit: None,
+        });
+        record(JournalEvent::Exited {
+            pid: Pid::from_raw(1),
+            exit: None,
+        });
+        assert_eq!(Journal::load(&path).unwrap(), Journal::default());
+    }
+}
@@ -1,46 +1,196 @@
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::error::Error as StdError;
-use std::fs::File;
 use std::io::{self, Write};
 use std::os::unix::io::AsRawFd;
 use std::path::Path;
 use std::path::PathBuf;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
 
 use failure::Fallible;
 use futures::future::{Future, FutureExt};
 use futures::stream::StreamExt;
+use nix::unistd::Pid;
 use semver::Version;
 use sockets::socketpair_raw;
-use tracing::{event, Level};
+use tracing::{event, span, Level};
 use uuid::Uuid;
 use yscloud_config_model::{
-    DeployedApplicationManifest, DeploymentManifest, FileDescriptorRemote, Protocol,
+    DeployedApplicationManifest, DeploymentManifest, FileDescriptorRemote, Ksuid, Protocol,
     PublicServiceBinder, Sandbox, ServiceFileDirection, SideCarServiceInfo, SocketInfo, SocketMode,
-    ImageType,
+    ImageType, Workdir, INTRINSIC_PACKAGE_ID,
 };
 
-use crate::platform::{Executable, ExecutableFactory, ExecutableFactoryHasher};
+use crate::adapter;
+use crate::audit::{self, Action};
+use crate::identity_signer::{self, CertificateAuthority, Identity, IdentitySigner};
+use crate::highwire;
+use crate::intrinsic;
+use crate::metrics::Metrics;
+use crate::platform::{
+    AdoptedExit, Executable, ExecutableFactory, ExecutableFactoryHasher, JournalFn,
+};
+use crate::process;
+use crate::util::load_deployment_manifest;
+use crate::workdir;
 use crate::{
     artifact::direct_load_artifact, bind_service, AppPreforkConfiguration, ExecExtras,
     ExecSomething, ServiceFileDescriptor,
 };
 
+pub use self::journal::Journal;
+
+mod journal;
+
 const DEFAULT_MAX_ARTIFACT_SIZE: u64 = 5000 * 1 << 20; // 5000 MB
 
+pub const MANIFEST_FILE: &str = "example-deployment-manifest.json";
+
 pub fn start(cfg: Config) {
-    let rdr = File::open("example-deployment-manifest.json").unwrap();
-    let mut target_deployment_manifest =
-        serde_json::from_reader::<_, DeploymentManifest>(rdr).unwrap();
+    let mut target_deployment_manifest = load_deployment_manifest(MANIFEST_FILE, None).unwrap();
 
     target_deployment_manifest.path_overrides = cfg.overrides.clone();
 
-    let fut = download_components(&cfg, &target_deployment_manifest);
+    let ca_dir = cfg.approot.join(identity_signer::PACKAGE_ID);
+    let ca = CertificateAuthority::load_or_create(&ca_dir).unwrap();
+    let signer = IdentitySigner::start(ca).unwrap();
+
+    let mut intrinsics = intrinsic::Registry::new();
+    intrinsics
+        .register(identity_signer::SERVICE_NAME, Arc::new(signer.clone()))
+        .unwrap();
+    highwire::register(&cfg.approot, &mut intrinsics).unwrap();
+    intrinsics.load_binaries(&cfg.approot).unwrap();
+    intrinsics.link(&mut target_deployment_manifest).unwrap();
+
+    if let Err(err) = crate::platform::set_child_subreaper() {
+        event!(Level::WARN, "can't become a child subreaper: {}", err);
+    }
+
+    let metrics = Metrics::new(&target_deployment_manifest.deployment_name);
+    if let Some(ref path) = cfg.metrics_socket {
+        metrics.serve(path).unwrap();
+    }
+
+    let journal_path = Journal::path(&cfg.approot);
+    if !adopt_previous(&journal_path, &target_deployment_manifest, &metrics) {
+        return;
+    }
+
+    let trace_id = Ksuid::generate();
+    let span = span!(Level::INFO, "deployment", trace_id = %trace_id.fmt_base62());
+    let _guard = span.enter();
+
+    let fut = download_components(&cfg, &target_deployment_manifest, &metrics);
     let mut rt = tokio::runtime::Runtime::new().unwrap();
     let xx = rt.block_on(fut).unwrap();
-    let reified = reify_service_connections(&target_deployment_manifest, xx, &cfg.approot).unwrap();
-    crate::platform::run_reified(reified);
+    let mut reified = reify_service_connections(
+        &target_deployment_manifest,
+        trace_id,
+        xx,
+        &cfg.approot,
+        &intrinsics,
+    )
+    .unwrap();
+    for exec in &mut reified {
+        signer.attach(exec).unwrap();
+        metrics.add_instance(&exec.cfg.package_id, exec.cfg.instance_id);
+    }
+    let ephemeral_workdirs = reified
+        .iter()
+        .filter_map(|exec| exec.ephemeral_workdir.clone())
+        .collect();
+    let journal = journal::journal_fn(
+        journal_path,
+        target_deployment_manifest.clone(),
+        trace_id,
+        ephemeral_workdirs,
+    )
+    .unwrap();
+    crate::platform::run_reified(reified, None, Some(observed(&metrics, journal)));
+}
+
+/// Has `metrics` see what goes into the journal.
+fn observed(metrics: &Metrics, mut journal: JournalFn) -> JournalFn {
+    let metrics = metrics.clone();
+    Box::new(move |ev| {
+        metrics.record(&ev);
+        journal(ev);
+    })
+}
+
+/// Takes over whatever a previous daemon left running of `dm`'s deployment.
+/// Returns whether `dm` still needs starting: it does if nothing was left,
+/// if that was a different manifest or part of it is gone, in which case
+/// the rest is stopped first.
+fn adopt_previous(journal_path: &Path, dm: &DeploymentManifest, metrics: &Metrics) -> bool {
+    let mut previous = Journal::load(journal_path).unwrap();
+    let deployment = match previous.deployments.remove(&dm.deployment_name) {
+        Some(deployment) => deployment,
+        None => return true,
+    };
+
+    // the components still have the one they were started with.
+    let trace_id = deployment.trace_id.unwrap_or_else(Ksuid::generate);
+    let span = span!(Level::INFO, "deployment", trace_id = %trace_id.fmt_base62());
+    let _guard = span.enter();
+
+    let (children, stop) = match deployment.adopt() {
+        Ok(children) if deployment.manifest == *dm => (children, false),
+        Ok(children) => {
+            event!(
+                Level::INFO,
+                "deployment manifest changed, restarting {}",
+                dm.deployment_name
+            );
+            (children, true)
+        }
+        Err(children) => {
+            event!(
+                Level::WARN,
+                "{} is partially gone, restarting it",
+                dm.deployment_name
+            );
+            (children, true)
+        }
+    };
+    if !stop {
+        event!(
+            Level::INFO,
+            "adopting {} running components of {}",
+            children.len(),
+            dm.deployment_name
+        );
+        for instance in &deployment.instances {
+            metrics.adopted(
+                &instance.package_id,
+                instance.instance_id,
+                Pid::from_raw(instance.pid),
+            );
+        }
+    }
+
+    let journal = journal::journal_fn(
+        journal_path.to_owned(),
+        dm.clone(),
+        trace_id,
+        deployment.ephemeral_workdirs.clone(),
+    )
+    .unwrap();
+    let outcome = crate::platform::run_adopted(
+        children,
+        deployment.ephemeral_workdirs,
+        observed(metrics, journal),
+        stop,
+    );
+    if outcome != AdoptedExit::ChildExited {
+        return false;
+    }
+    // forget the instances we found dead along with the stopped ones.
+    previous.save(journal_path).unwrap();
+    true
 }
 
 #[derive(Clone)]
@@ -49,6 +199,7 @@ pub struct Config {
     // this might be a remote resource or a local one - our type is incorrect here.
     pub artifacts: String,
     pub control_socket: PathBuf,
+    pub metrics_socket: Option<PathBuf>,
     pub overrides: HashMap<String, String>,
 }
 
@@ -66,6 +217,7 @@ struct PackageKey {
 async fn download_components(
     cfg: &Config,
     dm: &DeploymentManifest,
+    metrics: &Metrics,
 ) -> Fallible<HashMap<PackageKey, Component>> {
     let dm: DeploymentManifest = dm.clone();
 
@@ -92,16 +244,24 @@ async fn download_components(
                         component.package_id
                     );
 
-                    let executable = direct_load_artifact(&path)?;
-                    Ok((pkg_key, Component { executable }))
+                    let executable = direct_load_artifact(&path);
+                    audit::record(
+                        Action::ArtifactOverride {
+                            package_id: &component.package_id,
+                            path: &path,
+                        },
+                        &executable,
+                    );
+                    Ok((pkg_key, Component { executable: executable? }))
                 }
                 .boxed(),
             );
         } else {
             let cfg = cfg.clone();
+            let metrics = metrics.clone();
             futures.push(
                 async move {
-                    let executable = find_artifact(&cfg, component).await?;
+                    let executable = find_artifact(&cfg, component, &metrics).await?;
                     Ok((pkg_key, Component { executable }))
                 }
                 .boxed(),
@@ -115,6 +275,7 @@ async fn download_components(
     async fn find_artifact(
         cfg: &Config,
         dam: &DeployedApplicationManifest,
+        metrics: &Metrics,
     ) -> Fallible<Executable> {
         let mut artifact_specific: Option<(&str, &str)> = None;
         for p in crate::platform::PLATFORM_TRIPLES {
@@ -136,6 +297,7 @@ async fn download_components(
         let filename = &uri[cfg.artifacts.len() + 1..];
 
         event!(Level::INFO, "fetching {} from {}", dam.package_id, uri);
+        let started = Instant::now();
         // FIXME: async
         let response = reqwest::get(&uri).await?;
         // FIXME: add this information to the registry.
@@ -168,10 +330,20 @@ async fn download_components(
         fac.enable_hasher(ExecutableFactoryHasher::Sha256);
 
         let mut resp_data = response.bytes_stream();
-        while let Some(v) = resp_data.next().await {
-            let v = v?;
-            fac.write(&v[..])?;
+        let mut downloaded = 0;
+        let streamed: Fallible<()> = async {
+            while let Some(v) = resp_data.next().await {
+                let v = v?;
+                downloaded += v.len() as u64;
+                fac.write(&v[..])?;
+            }
+            Ok(())
         }
+        .await;
+
+        // what was actually transferred, even if it was cut short.
+        metrics.downloaded(&dam.package_id, downloaded, started.elapsed());
+        streamed?;
 
         fac.validate_hash(ExecutableFactoryHasher::Sha256, &sha)?;
         Ok(fac.finalize_executable())
@@ -180,14 +352,28 @@ async fn download_components(
 
 fn reify_service_connections(
     dm: &DeploymentManifest,
+    trace_id: Ksuid,
     mut component_artifacts: HashMap<PackageKey, Component>,
     approot: &Path,
+    intrinsics: &intrinsic::Registry,
 ) -> Result<Vec<crate::ExecSomething>, Box<dyn StdError>> {
     let mut instances = HashMap::<Uuid, ExecSomething>::new();
     let mut instance_components = HashMap::<Uuid, &DeployedApplicationManifest>::new();
     let mut instance_by_package = HashMap::<&str, Uuid>::new();
 
     for component in &dm.components {
+        if instance_by_package.contains_key(&*component.package_id) {
+            return Err(format!(
+                "package {:?} appears more than once in the deployment manifest",
+                component.package_id
+            )
+            .into());
+        }
+        process::check(&component.package_id, &component.process)
+            .map_err(|e| e.to_string())?;
+        process::check_activation(component).map_err(|e| e.to_string())?;
+        adapter::check(component).map_err(|e| e.to_string())?;
+
         let pkg_key = PackageKey {
             package_id: component.package_id.clone(),
             version: component.version.clone(),
@@ -201,14 +387,23 @@ fn reify_service_connections(
 
         let mut builder = ExecExtras::builder();
 
-        let mut workdir = approot.to_owned();
-        workdir.push(&dm.deployment_name);
-        workdir.push(&component.package_id);
+        let workdir = workdir::path(approot, &dm.deployment_name, &component.package_id);
+        workdir::prepare(&workdir, &component.workdir)?;
         builder.set_workdir(&workdir).unwrap();
 
         if let Sandbox::UnixUserConfinement(ref user, ref group) = component.sandbox {
-            builder.set_user(user).unwrap();
-            builder.set_group(group).unwrap();
+            let confined = builder
+                .set_user(user)
+                .and_then(|()| builder.set_group(group));
+            audit::record(
+                Action::Confine {
+                    package_id: &component.package_id,
+                    user,
+                    group,
+                },
+                &confined,
+            );
+            confined.unwrap();
         }
 
         instances.insert(
@@ -217,6 +412,7 @@ fn reify_service_connections(
                 extras: builder.build(),
                 cfg: AppPreforkConfiguration {
                     deployment_name: dm.deployment_name.clone(),
+                    trace_id,
                     package_id: component.package_id.clone(),
                     artifact: component_artifact.executable,
                     version: format!("{}", component.version),
@@ -224,6 +420,22 @@ fn reify_service_connections(
                     files: Default::default(),
                     extras: component.extras.clone(),
                     container_mounts: Default::default(),
+                    control: None,
+                    fd_protocol: component.fd_protocol,
+                    arguments: component.process.arguments.clone(),
+                    environment: process::environment(
+                        &component.process,
+                        process::PASS_ENVIRONMENT,
+                        std::env::vars(),
+                    ),
+                },
+                control: None,
+                activation: component.activation.clone(),
+                identity_signer: None,
+                intrinsic: intrinsics.is_binary(&component.package_id),
+                ephemeral_workdir: match component.workdir {
+                    Workdir::Ephemeral => Some(workdir),
+                    _ => None,
                 },
             },
         );
@@ -251,7 +463,16 @@ fn reify_service_connections(
             ps.service_id.service_name,
             ps.binder
         );
-        let service_sock = bind_service(&ps.binder)?;
+        let service_sock = bind_service(&ps.binder);
+        audit::record(
+            Action::BindPublicService {
+                package_id: &ps.service_id.package_id,
+                service_name: &ps.service_id.service_name,
+                binder: &ps.binder,
+            },
+            &service_sock,
+        );
+        let service_sock = service_sock?;
         event!(
             Level::INFO,
             "binded public service {} to {:?} - fd = {}",
@@ -273,11 +494,28 @@ fn reify_service_connections(
                     PublicServiceBinder::WebServiceBinder(ref ws) => ws.flags.clone(),
                 },
             }),
+            trace_id: None,
         });
     }
 
     for (local_instance_id, local_cfg) in &instance_components {
         for ls in &local_cfg.required_local_services {
+            if ls.package_id == INTRINSIC_PACKAGE_ID {
+                let file = intrinsics.connect(
+                    &ls.service_name,
+                    Identity {
+                        deployment_name: dm.deployment_name.clone(),
+                        package_id: local_cfg.package_id.clone(),
+                        instance_id: *local_instance_id,
+                    },
+                )?;
+                let local_instance = instances.get_mut(local_instance_id).ok_or_else(|| {
+                    format!("internal error: unknown instance {:?}", local_instance_id)
+                })?;
+                local_instance.cfg.files.push(file);
+                continue;
+            }
+
             let remote_instance_id = instance_by_package
                 .get(&*ls.package_id)
                 .ok_or_else(|| format!("internal error: unknown package {:?}", ls.package_id))?;
@@ -287,6 +525,15 @@ fn reify_service_connections(
             })?;
 
             let (local_sock, remote_sock) = socketpair_raw()?;
+            let connection_trace_id = Ksuid::generate();
+            event!(
+                Level::DEBUG,
+                trace_id = %connection_trace_id.fmt_base62(),
+                "connecting {} to {} for {}",
+                local_cfg.package_id,
+                remote_cfg.package_id,
+                ls.service_name
+            );
 
             {
                 let local_instance = instances.get_mut(local_instance_id).ok_or_else(|| {
@@ -302,6 +549,7 @@ fn reify_service_connections(
                         package_id: remote_cfg.package_id.clone(),
                         version: remote_cfg.version.clone(),
                     }),
+                    trace_id: Some(connection_trace_id),
                 });
             }
 
@@ -319,10 +567,18 @@ fn reify_service_connections(
                         package_id: local_cfg.package_id.clone(),
                         version: local_cfg.version.clone(),
                     }),
+                    trace_id: Some(connection_trace_id),
                 });
             }
         }
     }
 
+    for (instance_id, component) in &instance_components {
+        if let Some(ref adapter) = component.adapter {
+            let workdir = workdir::path(approot, &dm.deployment_name, &component.package_id);
+            adapter::write(adapter, &component.sandbox, &instances[instance_id].cfg, &workdir)?;
+        }
+    }
+
     Ok(instances.into_iter().map(|(_, v)| v).collect())
 }
@@ -0,0 +1,256 @@
+//! Assembles an `ApplicationDeploymentTemplate` from a base document, any
+//! number of per-environment overlays and a set of variables.
+//!
+//! Overlays are applied in order with JSON merge-patch semantics (RFC 7386),
+//! then `${NAME}` references in string values are replaced with values from
+//! the variables.  A string which consists of nothing but a single reference
+//! takes on the variable's JSON type, so `"port": "${HTTPS_PORT}"` can become
+//! a number.  `$${` produces a literal `${`.
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use failure::{Fail, Fallible};
+use serde_json::{Map, Value};
+
+use yscloud_config_model::{migrate, ApplicationDeploymentTemplate};
+
+use crate::document::{self, Format};
+
+pub type Variables = BTreeMap<String, Value>;
+
+pub fn load_template(
+    base: &Path,
+    overlays: &[&Path],
+    vars: &Variables,
+    format: Option<Format>,
+) -> Fallible<ApplicationDeploymentTemplate> {
+    let mut doc = document::read_value(base, format)?;
+    for overlay in overlays {
+        merge_patch(&mut doc, &document::read_value(overlay, format)?);
+    }
+
+    substitute_vars(&mut doc, vars)?;
+
+    Ok(migrate::load_deployment_template(doc)?)
+}
+
+pub fn load_vars(path: &Path, format: Option<Format>) -> Fallible<Variables> {
+    document::read(path, format)
+}
+
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let patch = match *patch {
+        Value::Object(ref patch) => patch,
+        _ => {
+            *target = patch.clone();
+            return;
+        }
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let target = target.as_object_mut().unwrap();
+
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+#[derive(Debug, Fail)]
+pub struct UnresolvedVariables {
+    // (JSON pointer, variable name)
+    missing: Vec<(String, String)>,
+}
+
+impl fmt::Display for UnresolvedVariables {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unresolved template variables:")?;
+        for (path, name) in &self.missing {
+            write!(f, "\n    {}: ${{{}}}", path, name)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Fail)]
+pub struct InvalidVariable {
+    path: String,
+    reason: String,
+}
+
+impl fmt::Display for InvalidVariable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.reason)
+    }
+}
+
+pub fn substitute_vars(document: &mut Value, vars: &Variables) -> Fallible<()> {
+    let mut missing = Vec::new();
+    substitute_value(document, vars, &mut String::new(), &mut missing)?;
+    if !missing.is_empty() {
+        return Err(UnresolvedVariables { missing }.into());
+    }
+    Ok(())
+}
+
+fn substitute_value(
+    value: &mut Value,
+    vars: &Variables,
+    path: &mut String,
+    missing: &mut Vec<(String, String)>,
+) -> Result<(), InvalidVariable> {
+    match *value {
+        Value::Object(ref mut map) => {
+            for (key, child) in map.iter_mut() {
+                let path_len = path.len();
+                path.push('/');
+                path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                substitute_value(child, vars, path, missing)?;
+                path.truncate(path_len);
+            }
+        }
+        Value::Array(ref mut items) => {
+            for (idx, child) in items.iter_mut().enumerate() {
+                let path_len = path.len();
+                path.push('/');
+                path.push_str(&idx.to_string());
+                substitute_value(child, vars, path, missing)?;
+                path.truncate(path_len);
+            }
+        }
+        Value::String(ref s) => {
+            if let Some(replacement) = substitute_string(s, vars, path, missing)? {
+                *value = replacement;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Returns `None` when `input` contains no references.
+fn substitute_string(
+    input: &str,
+    vars: &Variables,
+    path: &str,
+    missing: &mut Vec<(String, String)>,
+) -> Result<Option<Value>, InvalidVariable> {
+    if !input.contains("${") {
+        return Ok(None);
+    }
+
+    if input.starts_with("${")
+        && input.ends_with('}')
+        && input[2..].find('}') == Some(input.len() - 3)
+    {
+        let name = &input[2..input.len() - 1];
+        return match vars.get(name) {
+            Some(value) => Ok(Some(value.clone())),
+            None => {
+                missing.push((path.to_string(), name.to_string()));
+                Ok(None)
+            }
+        };
+    }
+
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(idx) = rest.find("${") {
+        if rest[..idx].ends_with('$') {
+            out.push_str(&rest[..idx - 1]);
+            out.push_str("${");
+            rest = &rest[idx + 2..];
+            continue;
+        }
+
+        out.push_str(&rest[..idx]);
+        let after = &rest[idx + 2..];
+        let end = after.find('}').ok_or_else(|| InvalidVariable {
+            path: path.to_string(),
+            reason: format!("unterminated variable reference in {:?}", input),
+        })?;
+
+        let name = &after[..end];
+        match vars.get(name) {
+            Some(Value::String(s)) => out.push_str(s),
+            Some(v @ Value::Number(_)) | Some(v @ Value::Bool(_)) => out.push_str(&v.to_string()),
+            Some(_) => {
+                return Err(InvalidVariable {
+                    path: path.to_string(),
+                    reason: format!("${{{}}} can not be interpolated into a string", name),
+                });
+            }
+            None => missing.push((path.to_string(), name.to_string())),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+
+    Ok(Some(Value::String(out)))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{merge_patch, substitute_vars, Variables};
+
+    #[test]
+    fn test_merge_patch() {
+        let mut doc = json!({
+            "deployment_name": "staging.yshi.org",
+            "configuration": {
+                "org.yshi.sfshost": {"debug": true, "vhosts": {"a": 1, "b": 2}}
+            }
+        });
+        merge_patch(
+            &mut doc,
+            &json!({
+                "deployment_name": "yshi.org",
+                "configuration": {
+                    "org.yshi.sfshost": {"debug": null, "vhosts": {"b": 3}}
+                }
+            }),
+        );
+        assert_eq!(
+            doc,
+            json!({
+                "deployment_name": "yshi.org",
+                "configuration": {"org.yshi.sfshost": {"vhosts": {"a": 1, "b": 3}}}
+            })
+        );
+    }
+
+    #[test]
+    fn test_substitute_vars() {
+        let mut vars = Variables::new();
+        vars.insert("HOST".into(), json!("yshi.org"));
+        vars.insert("PORT".into(), json!(1443));
+
+        let mut doc = json!({
+            "port": "${PORT}",
+            "hostnames": ["www.${HOST}", "${HOST}:${PORT}", "$${HOST}"]
+        });
+        substitute_vars(&mut doc, &vars).unwrap();
+        assert_eq!(
+            doc,
+            json!({
+                "port": 1443,
+                "hostnames": ["www.yshi.org", "yshi.org:1443", "${HOST}"]
+            })
+        );
+
+        let mut doc = json!({"configuration": {"a/b": {"password": "${DB_PASSWORD}"}}});
+        let err = substitute_vars(&mut doc, &vars).unwrap_err();
+        assert_eq!(
+            format!("{}", err),
+            "unresolved template variables:\n    /configuration/a~1b/password: ${DB_PASSWORD}"
+        );
+    }
+}
@@ -1,3 +1,10 @@
+use std::path::Path;
+
+use failure::Fallible;
+use yscloud_config_model::{migrate, DeploymentManifest};
+
+use crate::document::{self, Format};
+
 pub fn hexify<'sc>(scratch: &'sc mut [u8], data: &[u8]) -> Option<&'sc str> {
     static HEX_CHARS: &[u8] = b"0123456789abcdef";
     let hex_length = data.len() * 2;
@@ -21,3 +28,12 @@ pub fn hexify<'sc>(scratch: &'sc mut [u8], data: &[u8]) -> Option<&'sc str> {
 
     Some(::std::str::from_utf8(&scratch[..hex_length]).unwrap())
 }
+
+/// Reads a deployment manifest, upgrading it from older formats as needed.
+pub fn load_deployment_manifest<P: AsRef<Path>>(
+    path: P,
+    format: Option<Format>,
+) -> Fallible<DeploymentManifest> {
+    let doc = document::read_value(path.as_ref(), format)?;
+    Ok(migrate::load_deployment_manifest(doc)?)
+}
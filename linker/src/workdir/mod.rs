@@ -0,0 +1,274 @@
+//! Components' working directories, `<approot>/<deployment>/<package>`, and
+//! what becomes of them between runs (see `Workdir`).  The linker creates
+//! them and the component's sandbox chowns them as it starts.
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use tracing::{event, Level};
+use yscloud_config_model::Workdir;
+
+use crate::audit::{self, Action};
+use crate::identity_signer;
+
+/// Approot entries which are the linker's own rather than a deployment's.
+const RESERVED_NAMES: &[&str] = &[identity_signer::PACKAGE_ID];
+
+/// The working directory of `package_id` in `deployment_name`.
+pub fn path(approot: &Path, deployment_name: &str, package_id: &str) -> PathBuf {
+    let mut workdir = approot.to_owned();
+    workdir.push(deployment_name);
+    workdir.push(package_id);
+    workdir
+}
+
+/// Gets `path` ready for a component that is about to start.
+pub fn prepare(path: &Path, workdir: &Workdir) -> io::Result<()> {
+    match *workdir {
+        Workdir::Persistent => Ok(()),
+        // whatever was left by a linker that didn't get to stop cleanly.
+        Workdir::Ephemeral => empty(path),
+        Workdir::Quota { size_bytes } => mount_image(path, size_bytes),
+    }
+}
+
+/// Removes everything in `path`, but not `path` itself.
+pub fn empty(path: &Path) -> io::Result<()> {
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    for entry in entries {
+        let entry = entry?;
+        // doesn't follow symlinks, so nothing outside `path` is removed.
+        if entry.file_type()?.is_dir() {
+            fs::remove_dir_all(entry.path())?;
+        } else {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// The filesystem image backing a `Workdir::Quota` working directory, next
+/// to the directory itself.
+fn image_path(path: &Path) -> PathBuf {
+    let mut image = OsString::from(path);
+    image.push(".img");
+    PathBuf::from(image)
+}
+
+fn is_mount_point(path: &Path) -> io::Result<bool> {
+    let parent = path.parent().unwrap_or(path);
+    Ok(fs::metadata(path)?.dev() != fs::metadata(parent)?.dev())
+}
+
+/// Loop-mounts an ext4 image of `size_bytes` over `path`, making the image
+/// first if there is none.  An existing image is grown to `size_bytes`, but
+/// never shrunk.
+fn mount_image(path: &Path, size_bytes: u64) -> io::Result<()> {
+    fs::create_dir_all(path)?;
+    if is_mount_point(path)? {
+        return Ok(());
+    }
+
+    let image = image_path(path);
+    match fs::metadata(&image) {
+        Ok(meta) if meta.len() < size_bytes => {
+            fs::OpenOptions::new()
+                .write(true)
+                .open(&image)?
+                .set_len(size_bytes)?;
+            run(Command::new("e2fsck").arg("-f").arg("-p").arg(&image))?;
+            run(Command::new("resize2fs").arg(&image))?;
+        }
+        Ok(meta) => {
+            if size_bytes < meta.len() {
+                event!(
+                    Level::WARN,
+                    "not shrinking {} to {} bytes",
+                    image.display(),
+                    size_bytes
+                );
+            }
+        }
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => {
+            let made = fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .mode(0o600)
+                .open(&image)
+                .and_then(|file| file.set_len(size_bytes))
+                .and_then(|()| run(Command::new("mkfs.ext4").arg("-q").arg(&image)));
+            if let Err(err) = made {
+                let _ = fs::remove_file(&image);
+                return Err(err);
+            }
+        }
+        Err(err) => return Err(err),
+    }
+
+    let mounted = run(Command::new("mount")
+        .arg("-o")
+        .arg("loop,nosuid,nodev")
+        .arg(&image)
+        .arg(path));
+    audit::record(
+        Action::MountWorkdirImage {
+            image: &image,
+            workdir: path,
+        },
+        &mounted,
+    );
+    mounted
+}
+
+fn run(command: &mut Command) -> io::Result<()> {
+    let status = command.status()?;
+    if !status.success() {
+        let msg = format!("{:?} failed: {}", command, status);
+        return Err(io::Error::new(io::ErrorKind::Other, msg));
+    }
+    Ok(())
+}
+
+/// Directories in `approot` of deployments other than those in `live`.
+/// Those still in use are skipped with a warning.
+pub fn plan_gc(approot: &Path, live: &BTreeSet<String>) -> io::Result<Vec<PathBuf>> {
+    let cwds = process_cwds();
+    let mut remove = Vec::new();
+    for entry in fs::read_dir(approot)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let name = entry.file_name();
+        let name = match name.to_str() {
+            Some(name) => name,
+            None => continue,
+        };
+        if name.starts_with('.') || RESERVED_NAMES.contains(&name) || live.contains(name) {
+            continue;
+        }
+        let path = entry.path();
+        if is_mount_point(&path)? {
+            event!(Level::WARN, "not removing {}: mounted", path.display());
+            continue;
+        }
+        let canonical = path.canonicalize()?;
+        if let Some(cwd) = cwds.iter().find(|cwd| cwd.starts_with(&canonical)) {
+            event!(
+                Level::WARN,
+                "not removing {}: {} is in use",
+                path.display(),
+                cwd.display()
+            );
+            continue;
+        }
+        remove.push(path);
+    }
+    remove.sort();
+    Ok(remove)
+}
+
+/// The working directories of the processes we can see.  Processes of
+/// other users are only visible to root.
+fn process_cwds() -> Vec<PathBuf> {
+    let entries = match fs::read_dir("/proc") {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .map_or(false, |name| name.parse::<u32>().is_ok())
+        })
+        .filter_map(|entry| fs::read_link(entry.path().join("cwd")).ok())
+        .collect()
+}
+
+/// Removes a deployment's directory, unmounting any `Workdir::Quota` images
+/// first.  Nothing is removed if one of them is still in use.
+pub fn remove_deployment(path: &Path) -> io::Result<()> {
+    let removed = unmount_images(path).and_then(|()| fs::remove_dir_all(path));
+    audit::record(Action::RemoveWorkdir { path }, &removed);
+    removed
+}
+
+fn unmount_images(path: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() && is_mount_point(&entry.path())? {
+            run(Command::new("umount").arg(entry.path()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::fs;
+    use std::os::unix::fs::symlink;
+    use std::process::Command;
+
+    use tempfile::tempdir;
+
+    use super::{empty, path, plan_gc};
+
+    #[test]
+    fn test_empty() {
+        let outside = tempdir().unwrap();
+        fs::write(outside.path().join("keep"), b"").unwrap();
+
+        let approot = tempdir().unwrap();
+        let workdir = path(approot.path(), "irc", "org.inspircd");
+        fs::create_dir_all(workdir.join("logs")).unwrap();
+        fs::write(workdir.join("logs/irc.log"), b"").unwrap();
+        fs::write(workdir.join("inspircd.pid"), b"").unwrap();
+        symlink(outside.path(), workdir.join("outside")).unwrap();
+
+        empty(&workdir).unwrap();
+        assert_eq!(fs::read_dir(&workdir).unwrap().count(), 0);
+        assert!(outside.path().join("keep").exists());
+
+        empty(&approot.path().join("missing")).unwrap();
+    }
+
+    #[test]
+    fn test_plan_gc() {
+        let approot = tempdir().unwrap();
+        for name in &[
+            "irc",
+            "old-irc",
+            "busy",
+            "yscloud.identity-signer",
+            ".trash",
+        ] {
+            fs::create_dir(approot.path().join(name)).unwrap();
+        }
+        fs::create_dir(approot.path().join("busy/org.inspircd")).unwrap();
+        let mut busy = Command::new("sleep")
+            .arg("10")
+            .current_dir(approot.path().join("busy/org.inspircd"))
+            .spawn()
+            .unwrap();
+        fs::write(approot.path().join("daemon-journal.json"), b"{}").unwrap();
+
+        let live: BTreeSet<String> = vec!["irc".to_string(), "web".to_string()]
+            .into_iter()
+            .collect();
+        let plan = plan_gc(approot.path(), &live);
+        busy.kill().unwrap();
+        busy.wait().unwrap();
+        assert_eq!(plan.unwrap(), vec![approot.path().join("old-irc")]);
+    }
+}
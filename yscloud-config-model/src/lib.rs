@@ -1,14 +1,23 @@
 use std::borrow::Cow;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::PathBuf;
 
 use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+pub use ksuid::Ksuid;
+
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+
+pub mod migrate;
 pub mod permissions;
+#[cfg(feature = "schema")]
+pub mod schema;
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum SocketFlag {
     BehindHaproxy,
@@ -16,21 +25,26 @@ pub enum SocketFlag {
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct DeploymentManifest {
+    // see `migrate::CURRENT_FORMAT_VERSION`
+    pub format_version: u32,
     pub deployment_name: String,
     pub public_services: Vec<DeployedPublicService>,
     pub components: Vec<DeployedApplicationManifest>,
 
     // internal use only.
-    #[serde(default = "Default::default")]
+    #[serde(default)]
     pub path_overrides: HashMap<String, String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct DeployedApplicationManifest {
     pub package_id: String,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub version: Version,
     pub provided_local_services: Vec<String>,
     pub provided_remote_services: Vec<String>,
@@ -39,15 +53,40 @@ pub struct DeployedApplicationManifest {
     pub sandbox: Sandbox,
     pub extras: serde_json::Value,
 
-    #[serde(default = "Default::default")]
+    #[serde(default)]
     pub image_type: ImageType,
 
+    // copied from `ApplicationManifest::live_config`
+    #[serde(default)]
+    pub live_config: bool,
+
+    // copied from `ApplicationManifest::fd_protocol`
+    #[serde(default)]
+    pub fd_protocol: FdProtocol,
+
+    // copied from the template's `process` entry for this package
+    #[serde(default)]
+    pub process: ProcessSettings,
+
+    // copied from the template's `activation` entry for this package
+    #[serde(default)]
+    pub activation: Activation,
+
+    // copied from the template's `adapter` entry for this package
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adapter: Option<Adapter>,
+
+    // copied from the template's `workdir` entry for this package
+    #[serde(default)]
+    pub workdir: Workdir,
+
     // platform triple -> ArtifactHashSet
-    #[serde(default = "Default::default")]
+    #[serde(default)]
     pub artifacts: BTreeMap<String, ArtifactHashSet>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum Sandbox {
     Unconfined,
@@ -56,13 +95,19 @@ pub enum Sandbox {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct DeployedPublicService {
     pub service_id: ServiceId,
     pub binder: PublicServiceBinder,
 }
 
+/// The `package_id` of a required service that is a platform intrinsic: the
+/// linker running the deployment decides what provides it.
+pub const INTRINSIC_PACKAGE_ID: &str = "yscloud.intrinsic";
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct ServiceId {
     pub package_id: String,
@@ -70,6 +115,7 @@ pub struct ServiceId {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct PublicService {
     pub service_name: String,
@@ -77,6 +123,7 @@ pub struct PublicService {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum PublicServiceBinder {
     UnixDomainBinder(UnixDomainBinder),
@@ -87,23 +134,25 @@ pub enum PublicServiceBinder {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct UnixDomainBinder {
     pub path: PathBuf,
     #[serde(default = "start_listen_default")]
     pub start_listen: bool,
-    #[serde(default = "Default::default")]
+    #[serde(default)]
     pub flags: Vec<SocketFlag>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct NativePortBinder {
     pub bind_address: String,
     pub port: u16,
     #[serde(default = "start_listen_default")]
     pub start_listen: bool,
-    #[serde(default = "Default::default")]
+    #[serde(default)]
     pub flags: Vec<SocketFlag>,
 }
 
@@ -119,6 +168,7 @@ fn start_listen_default() -> bool {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum ImageType {
     Executable,
@@ -140,18 +190,105 @@ impl ImageType {
     }
 }
 
+/// How a component learns which file descriptors it was given.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum FdProtocol {
+    /// `--config-fd N`, pointing at an `AppConfiguration`.
+    ConfigFd,
+    /// systemd-style `LISTEN_FDS`, `LISTEN_PID` and `LISTEN_FDNAMES`, for
+    /// third-party binaries.  No configuration file is passed.
+    ListenFds,
+}
+
+impl Default for FdProtocol {
+    fn default() -> FdProtocol {
+        FdProtocol::ConfigFd
+    }
+}
+
+/// Extra command line arguments and environment variables for a component.
+/// Secrets don't belong here: both end up in the deployment manifest and in
+/// the process table.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct ProcessSettings {
+    #[serde(default)]
+    pub arguments: Vec<String>,
+    #[serde(default)]
+    pub environment: BTreeMap<String, String>,
+}
+
+/// What becomes of a component's working directory,
+/// `<approot>/<deployment>/<package>`.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Workdir {
+    /// Kept for as long as the deployment exists.
+    Persistent,
+    /// Emptied when the deployment starts and when it stops.
+    Ephemeral,
+    /// Persistent, but a filesystem image of `size_bytes` loop-mounted over
+    /// the directory, so the component can't fill up the approot.
+    Quota { size_bytes: u64 },
+}
+
+impl Default for Workdir {
+    fn default() -> Workdir {
+        Workdir::Persistent
+    }
+}
+
+/// A configuration file the linker renders for a third-party daemon that
+/// can't read an `AppConfiguration`.  `template` may refer to the
+/// component's services and extras with `{{...}}` placeholders.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct Adapter {
+    /// Where to write the file, relative to the component's working
+    /// directory.
+    pub path: String,
+    pub template: String,
+}
+
+/// When the linker starts a component.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Activation {
+    /// Together with the rest of the deployment.
+    Always,
+    /// On the first connection to one of its public services, whose sockets
+    /// the linker holds until then.  Stopped again once it has been idle for
+    /// `idle_timeout_secs`.
+    OnDemand { idle_timeout_secs: u64 },
+}
+
+impl Default for Activation {
+    fn default() -> Activation {
+        Activation::Always
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct WebServiceBinder {
     pub hostname: String,
-    #[serde(default = "Default::default")]
+    #[serde(default)]
     pub flags: Vec<SocketFlag>,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Permission(Cow<'static, str>);
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct ServiceConnection {
     pub providing_instance_id: Uuid,
@@ -159,7 +296,13 @@ pub struct ServiceConnection {
     pub service_name: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Sent on the control socket ahead of an updated `AppConfiguration`.  The
+/// message is a single `SOCK_SEQPACKET` packet carrying one file descriptor
+/// (`SCM_RIGHTS`), which holds the new configuration as JSON.
+pub const CONFIG_UPDATE_MESSAGE: &[u8] = b"config-update";
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct AppConfiguration {
     pub deployment_name: String,
@@ -168,17 +311,34 @@ pub struct AppConfiguration {
     pub version: String,
     pub files: Vec<FileDescriptorInfo>,
     pub extras: serde_json::Value,
+
+    // the linker's end is used to send configuration updates, only present
+    // for packages with `live_config`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub control_fd: Option<i32>,
+
+    /// Shared by every component of this run of the deployment, for
+    /// correlating their logs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    pub trace_id: Option<Ksuid>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct FileDescriptorInfo {
     pub file_num: i32,
     pub direction: ServiceFileDirection,
     pub service_name: String,
     pub remote: FileDescriptorRemote,
+    /// The connection's, which its other end is given too.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    pub trace_id: Option<Ksuid>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum FileDescriptorRemote {
     SideCarService(SideCarServiceInfo),
@@ -187,23 +347,27 @@ pub enum FileDescriptorRemote {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct SideCarServiceInfo {
     pub instance_id: Uuid,
     pub package_id: String,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub version: Version,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct SocketInfo {
     pub mode: SocketMode,
     pub protocol: Protocol,
-    #[serde(default = "Default::default")]
+    #[serde(default)]
     pub flags: Vec<SocketFlag>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum SocketMode {
     Listening,
@@ -211,6 +375,7 @@ pub enum SocketMode {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum Protocol {
     Stream,
@@ -218,52 +383,107 @@ pub enum Protocol {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct FileDescriptorCapabilityInfo {
     // nothing yet, just here to get the right json format.
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 struct ApplicationDependency {
     pub package_id: String,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub version_req: VersionReq,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct ApplicationDeploymentTemplate {
+    // see `migrate::CURRENT_FORMAT_VERSION`
+    pub format_version: u32,
     pub deployment_name: String,
     pub public_services: Vec<PublicService>,
     pub service_implementations: BTreeMap<String, ApplicationDeploymentRequirement>,
     pub configuration: BTreeMap<String, serde_json::Value>,
     pub sandbox: BTreeMap<String, Sandbox>,
+
+    // package id -> extra arguments and environment
+    #[serde(default)]
+    pub process: BTreeMap<String, ProcessSettings>,
+
+    // package id -> activation, for packages not started with the deployment
+    #[serde(default)]
+    pub activation: BTreeMap<String, Activation>,
+
+    // package id -> configuration file rendered before the package starts
+    #[serde(default)]
+    pub adapter: BTreeMap<String, Adapter>,
+
+    // package id -> working directory lifetime
+    #[serde(default)]
+    pub workdir: BTreeMap<String, Workdir>,
+
+    // services left to the linker, besides the ones built into it
+    #[serde(default)]
+    pub intrinsic_services: BTreeSet<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct RegistryEntry {
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub version: Version,
     // platform triple -> hex of hash
     pub sha256s: HashMap<String, String>,
     pub manifest: ApplicationManifest,
 }
 
+/// The outcome of resolving an `ApplicationDeploymentTemplate`, recorded so a
+/// release can be pressed again with the same versions.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct ReleaseLock {
+    pub deployment_name: String,
+    // service name -> resolved implementation
+    pub services: BTreeMap<String, LockedService>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct LockedService {
+    pub package_id: String,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub version: Version,
+    // platform triple -> hex of hash
+    pub sha256s: BTreeMap<String, String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct ArtifactInfo {
     pub package_id: String,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub version: Version,
     pub sha256s: HashMap<String, String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct ApplicationDeploymentRequirement {
     pub package_id: String,
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub version_req: VersionReq,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct ApplicationManifest {
     pub provided_remote_services: Vec<String>,
@@ -271,9 +491,21 @@ pub struct ApplicationManifest {
     pub required_remote_services: Vec<String>,
     pub required_local_services: Vec<String>,
     pub permissions: Vec<Permission>,
+
+    // JSON Schema for the package's entry in a template's `configuration`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extras_schema: Option<serde_json::Value>,
+
+    // accepts new extras without restarting, see `CONFIG_UPDATE_MESSAGE`.
+    #[serde(default)]
+    pub live_config: bool,
+
+    #[serde(default)]
+    pub fd_protocol: FdProtocol,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub enum ServiceFileDirection {
     ServingListening,
@@ -282,6 +514,7 @@ pub enum ServiceFileDirection {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct ArtifactHashSet {
     // make mandatory later
@@ -0,0 +1,128 @@
+//! Upgrades documents written by older linkers to the current format.
+//!
+//! Migrations operate on the raw JSON so that templates, which may still
+//! contain `${VAR}` references in typed positions, can be upgraded too.
+use std::error::Error;
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::{ApplicationDeploymentTemplate, DeploymentManifest};
+
+pub const CURRENT_FORMAT_VERSION: u32 = 1;
+
+const FORMAT_VERSION_KEY: &str = "format_version";
+
+type Migration = fn(&mut Map<String, Value>);
+
+// MIGRATIONS[n] upgrades format `n` to `n + 1`.  Documents without a
+// `format_version` predate versioning and are format 0.
+const DEPLOYMENT_MANIFEST_MIGRATIONS: &[Migration] = &[migrate_unversioned];
+const DEPLOYMENT_TEMPLATE_MIGRATIONS: &[Migration] = &[migrate_unversioned];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentKind {
+    DeploymentManifest,
+    DeploymentTemplate,
+}
+
+impl DocumentKind {
+    fn migrations(self) -> &'static [Migration] {
+        match self {
+            DocumentKind::DeploymentManifest => DEPLOYMENT_MANIFEST_MIGRATIONS,
+            DocumentKind::DeploymentTemplate => DEPLOYMENT_TEMPLATE_MIGRATIONS,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MigrateError {
+    NotAnObject,
+    InvalidFormatVersion(Value),
+    UnsupportedFormatVersion(u64),
+    Deserialize(serde_json::Error),
+}
+
+impl fmt::Display for MigrateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MigrateError::NotAnObject => write!(f, "document is not a JSON object"),
+            MigrateError::InvalidFormatVersion(ref v) => {
+                write!(f, "invalid {}: {}", FORMAT_VERSION_KEY, v)
+            }
+            MigrateError::UnsupportedFormatVersion(v) => write!(
+                f,
+                "{} {} is newer than this linker supports ({})",
+                FORMAT_VERSION_KEY, v, CURRENT_FORMAT_VERSION
+            ),
+            MigrateError::Deserialize(ref err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for MigrateError {}
+
+/// Upgrades `doc` in place, returning the format version it was read as.
+pub fn upgrade_document(kind: DocumentKind, doc: &mut Value) -> Result<u32, MigrateError> {
+    let map = doc.as_object_mut().ok_or(MigrateError::NotAnObject)?;
+
+    let original = match map.get(FORMAT_VERSION_KEY) {
+        None => 0,
+        Some(v) => match v.as_u64() {
+            Some(v) if v <= u64::from(CURRENT_FORMAT_VERSION) => v as u32,
+            Some(v) => return Err(MigrateError::UnsupportedFormatVersion(v)),
+            None => return Err(MigrateError::InvalidFormatVersion(v.clone())),
+        },
+    };
+
+    for migration in &kind.migrations()[original as usize..] {
+        migration(map);
+    }
+    map.insert(FORMAT_VERSION_KEY.into(), CURRENT_FORMAT_VERSION.into());
+
+    Ok(original)
+}
+
+fn load<T: DeserializeOwned>(kind: DocumentKind, mut doc: Value) -> Result<T, MigrateError> {
+    upgrade_document(kind, &mut doc)?;
+    serde_json::from_value(doc).map_err(MigrateError::Deserialize)
+}
+
+pub fn load_deployment_manifest(doc: Value) -> Result<DeploymentManifest, MigrateError> {
+    load(DocumentKind::DeploymentManifest, doc)
+}
+
+pub fn load_deployment_template(doc: Value) -> Result<ApplicationDeploymentTemplate, MigrateError> {
+    load(DocumentKind::DeploymentTemplate, doc)
+}
+
+// Format 1 only introduced `format_version` itself.
+fn migrate_unversioned(_doc: &mut Map<String, Value>) {}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{load_deployment_manifest, upgrade_document, DocumentKind, CURRENT_FORMAT_VERSION};
+
+    #[test]
+    fn test_upgrade_unversioned_manifest() {
+        let doc = json!({
+            "deployment_name": "yshi.org",
+            "public_services": [],
+            "components": []
+        });
+
+        let mut upgraded = doc.clone();
+        let original = upgrade_document(DocumentKind::DeploymentManifest, &mut upgraded).unwrap();
+        assert_eq!(original, 0);
+        assert_eq!(upgraded["format_version"], json!(CURRENT_FORMAT_VERSION));
+
+        let manifest = load_deployment_manifest(doc).unwrap();
+        assert_eq!(manifest.format_version, CURRENT_FORMAT_VERSION);
+
+        let mut too_new = json!({"format_version": CURRENT_FORMAT_VERSION + 1});
+        assert!(upgrade_document(DocumentKind::DeploymentManifest, &mut too_new).is_err());
+    }
+}
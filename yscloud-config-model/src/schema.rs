@@ -0,0 +1,21 @@
+//! JSON Schemas for the documents the linker reads and writes.
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+use crate::{ApplicationDeploymentTemplate, ApplicationManifest, DeploymentManifest, ReleaseLock};
+
+pub fn deployment_manifest() -> RootSchema {
+    schema_for!(DeploymentManifest)
+}
+
+pub fn deployment_template() -> RootSchema {
+    schema_for!(ApplicationDeploymentTemplate)
+}
+
+pub fn application_manifest() -> RootSchema {
+    schema_for!(ApplicationManifest)
+}
+
+pub fn release_lock() -> RootSchema {
+    schema_for!(ReleaseLock)
+}
@@ -0,0 +1,85 @@
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+
+use yscloud_config_model::ServiceFileDirection;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The process wasn't started with `--config-fd`.
+    MissingConfigFd,
+    InvalidConfigFd(String),
+    InvalidConfig(serde_json::Error),
+    InvalidExtras(serde_json::Error),
+    ServiceNotFound {
+        service_name: String,
+        direction: ServiceFileDirection,
+    },
+    /// The file descriptor for this service was already handed out.
+    ServiceTaken {
+        service_name: String,
+        direction: ServiceFileDirection,
+    },
+    UnsupportedAddressFamily {
+        service_name: String,
+    },
+    /// The package doesn't set `live_config`, so there is no control socket.
+    NoControlFd,
+    ControlTaken,
+    InvalidConfigUpdate,
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::MissingConfigFd => write!(f, "missing --config-fd, not started by the linker?"),
+            Error::InvalidConfigFd(ref v) => write!(f, "invalid --config-fd: {:?}", v),
+            Error::InvalidConfig(ref err) => write!(f, "invalid app configuration: {}", err),
+            Error::InvalidExtras(ref err) => write!(f, "invalid extras: {}", err),
+            Error::ServiceNotFound {
+                ref service_name,
+                ref direction,
+            } => write!(f, "no {:?} file for service {}", direction, service_name),
+            Error::ServiceTaken {
+                ref service_name,
+                ref direction,
+            } => write!(
+                f,
+                "{:?} file for service {} was already taken",
+                direction, service_name
+            ),
+            Error::UnsupportedAddressFamily { ref service_name } => write!(
+                f,
+                "listener for service {} has an unsupported address family",
+                service_name
+            ),
+            Error::NoControlFd => write!(f, "no control socket, is live_config set?"),
+            Error::ControlTaken => write!(f, "control socket was already taken"),
+            Error::InvalidConfigUpdate => write!(f, "invalid configuration update message"),
+            Error::Io(ref err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            Error::InvalidConfig(ref err) | Error::InvalidExtras(ref err) => Some(err),
+            Error::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl From<nix::Error> for Error {
+    fn from(err: nix::Error) -> Error {
+        Error::Io(io::Error::new(io::ErrorKind::Other, err))
+    }
+}
@@ -0,0 +1,398 @@
+//! Client side of the linker's `--config-fd` protocol.
+//!
+//! The linker starts each component with `--config-fd N`, where `N` is a
+//! file holding an `AppConfiguration`.  That configuration names the other
+//! file descriptors the component inherited, keyed by service name and
+//! direction.  `Runtime` parses it and hands each of those descriptors out
+//! once, as the matching std socket type.
+//!
+//! With the `async` feature, `yscloud_runtime::tokio` converts them for use under
+//! tokio.
+//!
+//! Packages that set `live_config` in their manifest also get a control
+//! socket, on which the linker sends new configuration; see `ConfigUpdates`.
+use std::collections::BTreeSet;
+use std::env;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::Read;
+use std::net::TcpListener;
+use std::os::unix::io::{FromRawFd, RawFd};
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use nix::sys::socket::{getsockname, listen, AddressFamily};
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+use yscloud_config_model::{
+    AppConfiguration, FileDescriptorInfo, FileDescriptorRemote, Ksuid, ServiceFileDirection,
+    SocketFlag,
+};
+
+mod error;
+#[cfg(feature = "async")]
+pub mod tokio;
+mod updates;
+
+pub use self::error::Error;
+pub use self::updates::ConfigUpdates;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+const CONFIG_FD_ARG: &str = "--config-fd";
+
+// 128 taken from rust stdlib
+const LISTEN_BACKLOG: usize = 128;
+
+/// A listening socket for a service this component provides.  Public
+/// services may be bound to TCP or unix addresses; sidecar services are
+/// always unix.
+#[derive(Debug)]
+pub enum Listener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+}
+
+#[derive(Debug)]
+pub struct Runtime {
+    config: AppConfiguration,
+    taken: BTreeSet<RawFd>,
+}
+
+impl Runtime {
+    /// Reads the configuration named by `--config-fd` in this process's
+    /// arguments.  Both `--config-fd N` and `--config-fd=N` are accepted.
+    pub fn from_args() -> Result<Runtime> {
+        let fd = config_fd_from_args(env::args_os())?;
+        // the linker gives us ownership of this descriptor.
+        unsafe { Runtime::from_config_fd(fd) }
+    }
+
+    /// Takes ownership of `fd` and reads the configuration from it.
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be an open file descriptor that nothing else owns.
+    pub unsafe fn from_config_fd(fd: RawFd) -> Result<Runtime> {
+        Runtime::from_reader(File::from_raw_fd(fd))
+    }
+
+    pub fn from_reader<R: Read>(rdr: R) -> Result<Runtime> {
+        let config = serde_json::from_reader(rdr).map_err(Error::InvalidConfig)?;
+        Ok(Runtime::from_config(config))
+    }
+
+    pub fn from_config(config: AppConfiguration) -> Runtime {
+        Runtime {
+            config,
+            taken: BTreeSet::new(),
+        }
+    }
+
+    pub fn config(&self) -> &AppConfiguration {
+        &self.config
+    }
+
+    pub fn deployment_name(&self) -> &str {
+        &self.config.deployment_name
+    }
+
+    pub fn package_id(&self) -> &str {
+        &self.config.package_id
+    }
+
+    pub fn instance_id(&self) -> Uuid {
+        self.config.instance_id
+    }
+
+    /// Identifies this run of the deployment in every component's logs.
+    /// Each connection has one of its own, in `config().files`.
+    pub fn trace_id(&self) -> Option<Ksuid> {
+        self.config.trace_id
+    }
+
+    /// Deserializes this component's entry in the deployment's
+    /// `configuration`.
+    pub fn extras<T: DeserializeOwned>(&self) -> Result<T> {
+        T::deserialize(&self.config.extras).map_err(Error::InvalidExtras)
+    }
+
+    /// The socket flags a public service was deployed with, or an empty list
+    /// for sidecar services.
+    pub fn socket_flags(&self, service_name: &str) -> Result<&[SocketFlag]> {
+        let info = self.find(service_name, ServiceFileDirection::ServingListening)?;
+        match info.remote {
+            FileDescriptorRemote::Socket(ref si) => Ok(&si.flags),
+            _ => Ok(&[]),
+        }
+    }
+
+    /// Takes the listening socket for a service this component provides.
+    /// The socket is put into the listening state if it isn't already.
+    pub fn listener(&mut self, service_name: &str) -> Result<Listener> {
+        let fd = self.take(service_name, ServiceFileDirection::ServingListening)?;
+
+        let family = getsockname(fd).map(|addr| addr.family());
+        let family = match family {
+            Ok(family) => family,
+            Err(err) => {
+                // we own it now, don't leak it.
+                drop(unsafe { File::from_raw_fd(fd) });
+                return Err(err.into());
+            }
+        };
+
+        let listener = match family {
+            AddressFamily::Inet | AddressFamily::Inet6 => {
+                Listener::Tcp(unsafe { TcpListener::from_raw_fd(fd) })
+            }
+            AddressFamily::Unix => Listener::Unix(unsafe { UnixListener::from_raw_fd(fd) }),
+            _ => {
+                drop(unsafe { File::from_raw_fd(fd) });
+                return Err(Error::UnsupportedAddressFamily {
+                    service_name: service_name.to_string(),
+                });
+            }
+        };
+
+        listen(fd, LISTEN_BACKLOG)?;
+        Ok(listener)
+    }
+
+    /// Takes the pre-connected streams for a service this component
+    /// provides, one per consuming component.
+    pub fn connected_streams(&mut self, service_name: &str) -> Result<Vec<UnixStream>> {
+        let direction = ServiceFileDirection::ServingConnected;
+        let fds: Vec<RawFd> = self
+            .files(service_name, &direction)
+            .into_iter()
+            .map(|f| f.file_num)
+            .collect();
+
+        if fds.is_empty() {
+            return Err(Error::ServiceNotFound {
+                service_name: service_name.to_string(),
+                direction,
+            });
+        }
+        if fds.iter().any(|fd| self.taken.contains(fd)) {
+            return Err(Error::ServiceTaken {
+                service_name: service_name.to_string(),
+                direction,
+            });
+        }
+
+        self.taken.extend(fds.iter().cloned());
+        Ok(fds
+            .into_iter()
+            .map(|fd| unsafe { UnixStream::from_raw_fd(fd) })
+            .collect())
+    }
+
+    /// Takes the stream connected to the component providing a service this
+    /// component requires.
+    pub fn consumer(&mut self, service_name: &str) -> Result<UnixStream> {
+        let fd = self.take(service_name, ServiceFileDirection::Consuming)?;
+        Ok(unsafe { UnixStream::from_raw_fd(fd) })
+    }
+
+    /// Takes the control socket configuration updates arrive on.  The
+    /// configuration returned by `config` and `extras` isn't updated.
+    pub fn config_updates(&mut self) -> Result<ConfigUpdates> {
+        let fd = self.config.control_fd.ok_or(Error::NoControlFd)?;
+        if !self.taken.insert(fd) {
+            return Err(Error::ControlTaken);
+        }
+        Ok(unsafe { ConfigUpdates::from_raw_fd(fd) })
+    }
+
+    fn files(
+        &self,
+        service_name: &str,
+        direction: &ServiceFileDirection,
+    ) -> Vec<&FileDescriptorInfo> {
+        self.config
+            .files
+            .iter()
+            .filter(|f| f.direction == *direction && f.service_name == service_name)
+            .collect()
+    }
+
+    fn find(
+        &self,
+        service_name: &str,
+        direction: ServiceFileDirection,
+    ) -> Result<&FileDescriptorInfo> {
+        match self.files(service_name, &direction).first() {
+            Some(info) => Ok(info),
+            None => Err(Error::ServiceNotFound {
+                service_name: service_name.to_string(),
+                direction,
+            }),
+        }
+    }
+
+    fn take(&mut self, service_name: &str, direction: ServiceFileDirection) -> Result<RawFd> {
+        let fd = self.find(service_name, direction.clone())?.file_num;
+        if !self.taken.insert(fd) {
+            return Err(Error::ServiceTaken {
+                service_name: service_name.to_string(),
+                direction,
+            });
+        }
+        Ok(fd)
+    }
+}
+
+fn config_fd_from_args<I>(args: I) -> Result<RawFd>
+where
+    I: IntoIterator<Item = OsString>,
+{
+    let mut args = args.into_iter().map(|a| a.to_string_lossy().into_owned());
+    while let Some(arg) = args.next() {
+        let value = if arg == CONFIG_FD_ARG {
+            args.next().ok_or(Error::MissingConfigFd)?
+        } else if arg.starts_with(CONFIG_FD_ARG) && arg[CONFIG_FD_ARG.len()..].starts_with('=') {
+            arg[CONFIG_FD_ARG.len() + 1..].to_string()
+        } else {
+            continue;
+        };
+        return value.parse().map_err(|_| Error::InvalidConfigFd(value));
+    }
+    Err(Error::MissingConfigFd)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+    use std::io::{Read, Seek, SeekFrom, Write};
+    use std::os::unix::io::{AsRawFd, IntoRawFd};
+    use std::os::unix::net::UnixStream;
+
+    use nix::sys::socket::{
+        sendmsg, socketpair, AddressFamily, ControlMessage, MsgFlags, SockFlag, SockType,
+    };
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+    use serde::Deserialize;
+    use serde_json::json;
+    use uuid::Uuid;
+    use yscloud_config_model::{
+        AppConfiguration, FileDescriptorInfo, FileDescriptorRemote, Ksuid, ServiceFileDirection,
+        SideCarServiceInfo, CONFIG_UPDATE_MESSAGE,
+    };
+
+    use super::{config_fd_from_args, Error, Runtime};
+
+    #[test]
+    fn test_config_fd_from_args() {
+        let args = |a: &[&str]| a.iter().map(OsString::from).collect::<Vec<_>>();
+
+        assert_eq!(
+            config_fd_from_args(args(&["yscloud-executable", "--config-fd", "3"])).unwrap(),
+            3
+        );
+        assert_eq!(
+            config_fd_from_args(args(&["yscloud-executable", "--config-fd=7"])).unwrap(),
+            7
+        );
+        assert!(config_fd_from_args(args(&["yscloud-executable"])).is_err());
+        assert!(config_fd_from_args(args(&["yscloud-executable", "--config-fd", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_runtime() {
+        let (consumer, provider) = UnixStream::pair().unwrap();
+
+        let config = AppConfiguration {
+            deployment_name: "example-deployment".into(),
+            package_id: "org.yshi.staticserver".into(),
+            instance_id: Uuid::new_v4(),
+            version: "1.0.0".into(),
+            files: vec![FileDescriptorInfo {
+                file_num: consumer.into_raw_fd(),
+                direction: ServiceFileDirection::Consuming,
+                service_name: "org.yshi.log_target.v1.LogTarget".into(),
+                remote: FileDescriptorRemote::SideCarService(SideCarServiceInfo {
+                    instance_id: Uuid::new_v4(),
+                    package_id: "org.yshi.file-logger".into(),
+                    version: "1.0.2".parse().unwrap(),
+                }),
+                trace_id: None,
+            }],
+            extras: json!({"vhosts": ["yshi.org"]}),
+            control_fd: None,
+            trace_id: None,
+        };
+        let mut rt = Runtime::from_config(config);
+
+        #[derive(Deserialize)]
+        struct Extras {
+            vhosts: Vec<String>,
+        }
+        assert_eq!(rt.extras::<Extras>().unwrap().vhosts, vec!["yshi.org"]);
+
+        let mut stream = rt.consumer("org.yshi.log_target.v1.LogTarget").unwrap();
+        stream.write_all(b"hello").unwrap();
+        let mut buf = [0; 5];
+        (&provider).read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"hello");
+
+        match rt.consumer("org.yshi.log_target.v1.LogTarget") {
+            Err(Error::ServiceTaken { .. }) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+        match rt.listener("org.yshi.staticserver.http") {
+            Err(Error::ServiceNotFound { .. }) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+        match rt.config_updates() {
+            Err(Error::NoControlFd) => (),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_config_updates() {
+        let (linker_end, component_end) = socketpair(
+            AddressFamily::Unix,
+            SockType::SeqPacket,
+            None,
+            SockFlag::SOCK_CLOEXEC,
+        )
+        .unwrap();
+
+        let mut config = AppConfiguration {
+            deployment_name: "example-deployment".into(),
+            package_id: "org.yshi.sni_multiplexor".into(),
+            instance_id: Uuid::new_v4(),
+            version: "0.1.21".into(),
+            files: vec![],
+            extras: json!({"hostnames": {}}),
+            control_fd: Some(component_end),
+            trace_id: Some(Ksuid::generate()),
+        };
+        let mut rt = Runtime::from_config(config.clone());
+        let mut updates = rt.config_updates().unwrap();
+        assert!(rt.config_updates().is_err());
+
+        config.extras = json!({"hostnames": {"yshi.org": {}}});
+        let mut file = tempfile::tempfile().unwrap();
+        serde_json::to_writer(&mut file, &config).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        sendmsg(
+            linker_end,
+            &[IoVec::from_slice(CONFIG_UPDATE_MESSAGE)],
+            &[ControlMessage::ScmRights(&[file.as_raw_fd()])],
+            MsgFlags::empty(),
+            None,
+        )
+        .unwrap();
+
+        let update = updates.recv().unwrap().unwrap();
+        assert_eq!(update.extras, config.extras);
+        assert_eq!(update.trace_id, config.trace_id);
+
+        close(linker_end).unwrap();
+        assert!(updates.recv().unwrap().is_none());
+    }
+}
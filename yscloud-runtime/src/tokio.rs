@@ -0,0 +1,64 @@
+//! Conversions into tokio's socket types.  These must be called from within
+//! a tokio runtime.
+use std::io;
+use std::os::unix::net::UnixStream as StdUnixStream;
+
+use ::tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+
+use crate::{Result, Runtime};
+
+pub enum Listener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+}
+
+/// A stream accepted from a `Listener`.
+pub enum Stream {
+    Tcp(TcpStream),
+    Unix(UnixStream),
+}
+
+impl Listener {
+    pub fn from_std(listener: crate::Listener) -> io::Result<Listener> {
+        match listener {
+            crate::Listener::Tcp(l) => {
+                l.set_nonblocking(true)?;
+                Ok(Listener::Tcp(TcpListener::from_std(l)?))
+            }
+            crate::Listener::Unix(l) => {
+                l.set_nonblocking(true)?;
+                Ok(Listener::Unix(UnixListener::from_std(l)?))
+            }
+        }
+    }
+
+    pub async fn accept(&mut self) -> io::Result<Stream> {
+        match *self {
+            Listener::Tcp(ref mut l) => Ok(Stream::Tcp(l.accept().await?.0)),
+            Listener::Unix(ref mut l) => Ok(Stream::Unix(l.accept().await?.0)),
+        }
+    }
+}
+
+fn stream_from_std(stream: StdUnixStream) -> io::Result<UnixStream> {
+    stream.set_nonblocking(true)?;
+    UnixStream::from_std(stream)
+}
+
+impl Runtime {
+    pub fn tokio_listener(&mut self, service_name: &str) -> Result<Listener> {
+        Ok(Listener::from_std(self.listener(service_name)?)?)
+    }
+
+    pub fn tokio_connected_streams(&mut self, service_name: &str) -> Result<Vec<UnixStream>> {
+        let mut out = Vec::new();
+        for stream in self.connected_streams(service_name)? {
+            out.push(stream_from_std(stream)?);
+        }
+        Ok(out)
+    }
+
+    pub fn tokio_consumer(&mut self, service_name: &str) -> Result<UnixStream> {
+        Ok(stream_from_std(self.consumer(service_name)?)?)
+    }
+}
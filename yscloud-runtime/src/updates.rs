@@ -0,0 +1,67 @@
+use std::fs::File;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+
+use nix::sys::socket::{recvmsg, ControlMessageOwned, MsgFlags};
+use nix::sys::uio::IoVec;
+use yscloud_config_model::{AppConfiguration, CONFIG_UPDATE_MESSAGE};
+
+use crate::{Error, Result};
+
+/// The component's end of its control socket, on which the linker sends a
+/// new `AppConfiguration` when the deployment's extras for it change.
+#[derive(Debug)]
+pub struct ConfigUpdates {
+    fd: RawFd,
+}
+
+impl ConfigUpdates {
+    /// # Safety
+    ///
+    /// `fd` must be a control socket that nothing else owns.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> ConfigUpdates {
+        ConfigUpdates { fd }
+    }
+
+    /// Blocks until the linker sends a new configuration.  The files are the
+    /// ones the component was started with, only the extras change.  Returns
+    /// `None` once the linker has gone away.
+    pub fn recv(&mut self) -> Result<Option<AppConfiguration>> {
+        let mut buf = [0; 64];
+        let mut cmsg_buffer = nix::cmsg_space!([RawFd; 1]);
+
+        let (bytes, files) = {
+            let iov = [IoVec::from_mut_slice(&mut buf)];
+            let msg = recvmsg(self.fd, &iov, Some(&mut cmsg_buffer), MsgFlags::empty())?;
+
+            let mut files = Vec::new();
+            for cmsg in msg.cmsgs() {
+                if let ControlMessageOwned::ScmRights(fds) = cmsg {
+                    files.extend(fds.into_iter().map(|fd| unsafe { File::from_raw_fd(fd) }));
+                }
+            }
+            (msg.bytes, files)
+        };
+
+        if bytes == 0 && files.is_empty() {
+            return Ok(None);
+        }
+        if &buf[..bytes] != CONFIG_UPDATE_MESSAGE || files.len() != 1 {
+            return Err(Error::InvalidConfigUpdate);
+        }
+
+        let config = serde_json::from_reader(&files[0]).map_err(Error::InvalidConfig)?;
+        Ok(Some(config))
+    }
+}
+
+impl AsRawFd for ConfigUpdates {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl Drop for ConfigUpdates {
+    fn drop(&mut self) {
+        let _ = nix::unistd::close(self.fd);
+    }
+}
@@ -0,0 +1,413 @@
+//! Integration-test support for components.
+//!
+//! `Harness` creates, from a component's `ApplicationManifest`, the file
+//! descriptors the linker would hand it, and starts the binary under test
+//! with `--config-fd`.  The test keeps the other end of every service, so it
+//! can play the part of each sidecar and of the component's public clients:
+//!
+//! * `provided_remote_services` get a listening TCP socket on localhost; the
+//!   test gets its address.
+//! * `provided_local_services` get a serving-connected stream; the test gets
+//!   the consumer's end.
+//! * `required_local_services` get a consuming stream; the test gets the
+//!   provider's end.
+//!
+//! The linker doesn't connect remote requirements, so neither does this.
+//!
+//! For packages with `live_config`, `Running::update_extras` plays the
+//! linker's part in a configuration reload.
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::io::{self, Seek, SeekFrom, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, ExitStatus};
+
+use nix::fcntl::{fcntl, FcntlArg};
+use nix::sys::socket::{
+    sendmsg, socketpair, AddressFamily, ControlMessage, MsgFlags, SockFlag, SockType,
+};
+use nix::sys::uio::IoVec;
+use nix::unistd::dup2;
+use owned_fd::OwnedFd;
+use semver::Version;
+use uuid::Uuid;
+use yscloud_config_model::{
+    AppConfiguration, ApplicationManifest, FileDescriptorInfo, FileDescriptorRemote, Ksuid,
+    Protocol, ServiceFileDirection, SideCarServiceInfo, SocketInfo, SocketMode,
+    CONFIG_UPDATE_MESSAGE,
+};
+
+/// The package the mocked sidecars claim to be.
+pub const MOCK_PACKAGE_ID: &str = "org.yshi.testkit";
+
+const DEFAULT_DEPLOYMENT_NAME: &str = "testkit";
+const DEFAULT_VERSION: &str = "0.0.0";
+
+// the first descriptor after stdio.
+const FIRST_FD: RawFd = 3;
+
+enum ChildEnd {
+    Tcp(TcpListener),
+    Unix(UnixStream),
+}
+
+impl AsRawFd for ChildEnd {
+    fn as_raw_fd(&self) -> RawFd {
+        match *self {
+            ChildEnd::Tcp(ref l) => l.as_raw_fd(),
+            ChildEnd::Unix(ref s) => s.as_raw_fd(),
+        }
+    }
+}
+
+struct ServiceFile {
+    end: ChildEnd,
+    direction: ServiceFileDirection,
+    service_name: String,
+    remote: FileDescriptorRemote,
+    trace_id: Option<Ksuid>,
+}
+
+#[derive(Default)]
+struct Peers {
+    public: HashMap<String, SocketAddr>,
+    consumers: HashMap<String, UnixStream>,
+    providers: HashMap<String, UnixStream>,
+}
+
+pub struct Harness {
+    deployment_name: String,
+    package_id: String,
+    version: String,
+    instance_id: Uuid,
+    trace_id: Ksuid,
+    extras: serde_json::Value,
+    files: Vec<ServiceFile>,
+    // (test end, component end), for packages with `live_config`.
+    control: Option<(OwnedFd, OwnedFd)>,
+    peers: Peers,
+}
+
+impl Harness {
+    pub fn new(package_id: &str, manifest: &ApplicationManifest) -> io::Result<Harness> {
+        let mut files = Vec::new();
+        let mut peers = Peers::default();
+
+        for service_name in &manifest.provided_remote_services {
+            let listener = TcpListener::bind("127.0.0.1:0")?;
+            peers
+                .public
+                .insert(service_name.clone(), listener.local_addr()?);
+            files.push(ServiceFile {
+                end: ChildEnd::Tcp(listener),
+                direction: ServiceFileDirection::ServingListening,
+                service_name: service_name.clone(),
+                remote: FileDescriptorRemote::Socket(SocketInfo {
+                    mode: SocketMode::Listening,
+                    protocol: Protocol::Stream,
+                    flags: Vec::new(),
+                }),
+                trace_id: None,
+            });
+        }
+
+        for service_name in &manifest.provided_local_services {
+            let (child_end, test_end) = UnixStream::pair()?;
+            peers.consumers.insert(service_name.clone(), test_end);
+            files.push(ServiceFile {
+                end: ChildEnd::Unix(child_end),
+                direction: ServiceFileDirection::ServingConnected,
+                service_name: service_name.clone(),
+                remote: mock_sidecar(),
+                trace_id: Some(Ksuid::generate()),
+            });
+        }
+
+        for service_name in &manifest.required_local_services {
+            let (child_end, test_end) = UnixStream::pair()?;
+            peers.providers.insert(service_name.clone(), test_end);
+            files.push(ServiceFile {
+                end: ChildEnd::Unix(child_end),
+                direction: ServiceFileDirection::Consuming,
+                service_name: service_name.clone(),
+                remote: mock_sidecar(),
+                trace_id: Some(Ksuid::generate()),
+            });
+        }
+
+        let mut control = None;
+        if manifest.live_config {
+            let (test_end, child_end) = socketpair(
+                AddressFamily::Unix,
+                SockType::SeqPacket,
+                None,
+                SockFlag::SOCK_CLOEXEC,
+            )
+            .map_err(nix_error)?;
+            control = unsafe {
+                Some((
+                    OwnedFd::from_raw_fd(test_end),
+                    OwnedFd::from_raw_fd(child_end),
+                ))
+            };
+        }
+
+        Ok(Harness {
+            deployment_name: DEFAULT_DEPLOYMENT_NAME.to_string(),
+            package_id: package_id.to_string(),
+            version: DEFAULT_VERSION.to_string(),
+            instance_id: Uuid::new_v4(),
+            trace_id: Ksuid::generate(),
+            extras: serde_json::Value::Object(Default::default()),
+            files,
+            control,
+            peers,
+        })
+    }
+
+    pub fn deployment_name(&mut self, deployment_name: &str) -> &mut Harness {
+        self.deployment_name = deployment_name.to_string();
+        self
+    }
+
+    pub fn version(&mut self, version: &str) -> &mut Harness {
+        self.version = version.to_string();
+        self
+    }
+
+    /// The component's entry in the deployment's `configuration`.
+    pub fn extras(&mut self, extras: serde_json::Value) -> &mut Harness {
+        self.extras = extras;
+        self
+    }
+
+    /// The configuration the component will be started with.  Service files
+    /// are numbered from 3 in manifest order, followed by the control socket
+    /// if there is one, and then the configuration itself.
+    pub fn app_configuration(&self) -> AppConfiguration {
+        AppConfiguration {
+            deployment_name: self.deployment_name.clone(),
+            package_id: self.package_id.clone(),
+            instance_id: self.instance_id,
+            version: self.version.clone(),
+            files: self
+                .files
+                .iter()
+                .enumerate()
+                .map(|(i, f)| FileDescriptorInfo {
+                    file_num: FIRST_FD + i as RawFd,
+                    direction: f.direction.clone(),
+                    service_name: f.service_name.clone(),
+                    remote: f.remote.clone(),
+                    trace_id: f.trace_id,
+                })
+                .collect(),
+            extras: self.extras.clone(),
+            control_fd: self
+                .control
+                .as_ref()
+                .map(|_| FIRST_FD + self.files.len() as RawFd),
+            trace_id: Some(self.trace_id),
+        }
+    }
+
+    pub fn spawn<S: AsRef<OsStr>>(self, program: S) -> io::Result<Running> {
+        self.spawn_command(Command::new(program))
+    }
+
+    /// Appends `--config-fd N` to `cmd` and starts it.
+    pub fn spawn_command(self, mut cmd: Command) -> io::Result<Running> {
+        let config = self.app_configuration();
+        let config_file = config_file(&config)?;
+
+        let mut fds: Vec<RawFd> = self.files.iter().map(|f| f.end.as_raw_fd()).collect();
+        if let Some((_, ref child_end)) = self.control {
+            fds.push(child_end.as_raw_fd());
+        }
+        let config_fd = FIRST_FD + fds.len() as RawFd;
+        fds.push(config_file.as_raw_fd());
+
+        cmd.arg("--config-fd")
+            .arg(config_fd.to_string())
+            .env("YSCLOUD", "1");
+        unsafe {
+            cmd.pre_exec(move || place_file_descriptors(&mut fds));
+        }
+        let child = cmd.spawn()?;
+
+        // close our copies of the child's ends, so the test sees EOF
+        // when the component goes away.
+        drop(config_file);
+        drop(self.files);
+
+        Ok(Running {
+            child,
+            config,
+            control: self.control.map(|(test_end, _)| test_end),
+            peers: self.peers,
+        })
+    }
+}
+
+/// A started component.  It is killed when dropped.
+pub struct Running {
+    child: Child,
+    config: AppConfiguration,
+    control: Option<OwnedFd>,
+    peers: Peers,
+}
+
+impl Running {
+    pub fn child(&mut self) -> &mut Child {
+        &mut self.child
+    }
+
+    /// Where a public service is listening.
+    pub fn public_addr(&self, service_name: &str) -> Option<SocketAddr> {
+        self.peers.public.get(service_name).cloned()
+    }
+
+    /// Takes the consumer's end of a service the component provides.
+    pub fn take_consumer(&mut self, service_name: &str) -> Option<UnixStream> {
+        self.peers.consumers.remove(service_name)
+    }
+
+    /// Takes the provider's end of a service the component requires.
+    pub fn take_provider(&mut self, service_name: &str) -> Option<UnixStream> {
+        self.peers.providers.remove(service_name)
+    }
+
+    /// Sends the component a configuration update with new extras, as the
+    /// linker does on reload.
+    pub fn update_extras(&mut self, extras: serde_json::Value) -> io::Result<()> {
+        let control = match self.control {
+            Some(ref control) => control,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "the package doesn't set live_config",
+                ))
+            }
+        };
+
+        self.config.extras = extras;
+        let file = config_file(&self.config)?;
+
+        let fds = [file.as_raw_fd()];
+        let iov = [IoVec::from_slice(CONFIG_UPDATE_MESSAGE)];
+        let cmsgs = [ControlMessage::ScmRights(&fds)];
+        sendmsg(control.as_raw_fd(), &iov, &cmsgs, MsgFlags::empty(), None).map_err(nix_error)?;
+        Ok(())
+    }
+
+    pub fn wait(&mut self) -> io::Result<ExitStatus> {
+        self.child.wait()
+    }
+
+    pub fn kill(&mut self) -> io::Result<ExitStatus> {
+        // fails if it already exited, which is fine.
+        let _ = self.child.kill();
+        self.child.wait()
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            let _ = self.kill();
+        }
+    }
+}
+
+// an unlinked temporary, as the linker uses.
+fn config_file(config: &AppConfiguration) -> io::Result<std::fs::File> {
+    let mut file = tempfile::tempfile()?;
+    serde_json::to_writer(&mut file, config)?;
+    file.flush()?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(file)
+}
+
+fn mock_sidecar() -> FileDescriptorRemote {
+    FileDescriptorRemote::SideCarService(SideCarServiceInfo {
+        instance_id: Uuid::new_v4(),
+        package_id: MOCK_PACKAGE_ID.to_string(),
+        version: Version::new(0, 0, 0),
+    })
+}
+
+// runs in the forked child, so it mustn't allocate.
+fn place_file_descriptors(fds: &mut [RawFd]) -> io::Result<()> {
+    // move everything above the target range first, so placing one
+    // descriptor can't clobber another that is yet to be placed.
+    let above = FIRST_FD + fds.len() as RawFd;
+    for fd in fds.iter_mut() {
+        *fd = fcntl(*fd, FcntlArg::F_DUPFD_CLOEXEC(above)).map_err(nix_error)?;
+    }
+    // dup2 clears close-on-exec on the target.
+    for (i, fd) in fds.iter().enumerate() {
+        dup2(*fd, FIRST_FD + i as RawFd).map_err(nix_error)?;
+    }
+    Ok(())
+}
+
+fn nix_error(err: nix::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::process::Command;
+
+    use serde_json::json;
+    use yscloud_config_model::{
+        AppConfiguration, ApplicationManifest, FdProtocol, ServiceFileDirection,
+    };
+
+    use super::Harness;
+
+    #[test]
+    fn test_spawn() {
+        let manifest = ApplicationManifest {
+            provided_remote_services: vec!["org.yshi.staticserver.http".into()],
+            provided_local_services: vec![],
+            required_remote_services: vec![],
+            required_local_services: vec!["org.yshi.log_target.v1.LogTarget".into()],
+            permissions: vec![],
+            extras_schema: None,
+            live_config: false,
+            fd_protocol: FdProtocol::ConfigFd,
+        };
+        let mut harness = Harness::new("org.yshi.staticserver", &manifest).unwrap();
+        harness.extras(json!({"vhosts": ["yshi.org"]}));
+
+        // echoes its configuration to the log target: `--config-fd N` are $1 and $2.
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "cat <&$2 >&4", "sh"]);
+        let mut running = harness.spawn_command(cmd).unwrap();
+
+        assert!(running.public_addr("org.yshi.staticserver.http").is_some());
+        let mut log_target = running
+            .take_provider("org.yshi.log_target.v1.LogTarget")
+            .unwrap();
+        let mut buf = String::new();
+        log_target.read_to_string(&mut buf).unwrap();
+        assert!(running.wait().unwrap().success());
+
+        let config: AppConfiguration = serde_json::from_str(&buf).unwrap();
+        assert_eq!(config.package_id, "org.yshi.staticserver");
+        assert_eq!(config.extras, json!({"vhosts": ["yshi.org"]}));
+        assert_eq!(config.files.len(), 2);
+        assert_eq!(config.files[0].file_num, 3);
+        assert_eq!(
+            config.files[0].direction,
+            ServiceFileDirection::ServingListening
+        );
+        assert_eq!(config.files[1].file_num, 4);
+        assert_eq!(config.files[1].direction, ServiceFileDirection::Consuming);
+    }
+}